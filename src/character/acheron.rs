@@ -1,416 +1,279 @@
-use super::Evaluator;
-use crate::{
-    character::Support,
-    domain::{
-        AttackType, Character, CritEnum, DamageType, Enemy, LightConeEntity, Relics, SkillType,
-        Stats,
-    },
-    utils::calculator::{
-        base_stats_and_bonus, crit_dmg, def, dmg_boost, dmg_mit, res, toughness, vul, weaken,
-    },
+use super::kit::{
+    Ability, Amount, DmgMultiplier, Hit, Kit, KitOption, KitTarget, Modifier, Requirement, Scaling,
+    ScalingTable, SkillLevel, Stacks, Targeting, Unlock,
 };
-use eyre::Result;
-use std::collections::HashMap;
-
-pub struct Acheron {
-    pub character: Character,
-    pub light_cone: Option<LightConeEntity>,
-    pub crimson_knot: u8,
-    pub thunder_core_bonus_stack: u8,
-    pub crit: CritEnum,
-    pub activate_eidolon_1: bool,
-}
-
-#[derive(strum_macros::Display)]
-pub enum AcheronEvaluationTarget {
-    Skill,
-    UltimateSingle,
-    UltimateAoe,
-}
-
-impl Evaluator for Acheron {
-    type Target = AcheronEvaluationTarget;
-
-    fn evaluate(
-        &self,
-        relics: &Relics,
-        enemy: &Enemy,
-        target: &Self::Target,
-        teammates: &[Box<dyn Support>],
-    ) -> Result<f64> {
-        match target {
-            AcheronEvaluationTarget::UltimateSingle => {
-                self.full_ultimate_multiplier_on_single_enemy(&relics, &enemy, teammates)
-            }
-            AcheronEvaluationTarget::UltimateAoe => {
-                self.full_ultimate_multiplier_on_three_enemies(&relics, &enemy, teammates)
-            }
-            AcheronEvaluationTarget::Skill => self.skill(relics, enemy, teammates),
-        }
-    }
-}
-
-impl Acheron {
-    fn calculate_damage(
-        &self,
-        teammates: &[Box<dyn Support>],
-        ability_multiplier: f64,
-        base_stats: &mut HashMap<Stats, f64>,
-        bonus: &HashMap<Stats, f64>,
-        enemy: &Enemy,
-    ) -> Result<f64> {
-        let the_abyss = self.the_abyss_multiplier(teammates);
-        let base_dmg = ability_multiplier
-            * base_stats.get(&Stats::Atk).cloned().unwrap_or_default()
-            * the_abyss;
-        *base_stats.entry(Stats::CritRate_).or_default() += self.eidolon_1();
-        let crit = crit_dmg(self.crit, &base_stats, &self.character);
-        let dmg_boost = dmg_boost(&bonus);
-        let def = def(enemy, &bonus, &self.character);
-        let res = res(enemy, &bonus);
-        let vul = vul(enemy);
-        let dmg_mit = dmg_mit(enemy)?;
-        let weaken = weaken(enemy);
-        let broken = toughness(enemy);
-        Ok(base_dmg * crit * dmg_boost * weaken * def * res * vul * dmg_mit * broken)
-    }
-
-    fn talent(&self) -> f64 {
-        let resistance_penalty_scale = vec![
-            0.0, 10.00, 11.00, 12.00, 13.00, 14.00, 15.00, 16.25, 17.50, 18.75, 20.00, 21.00,
-            22.00, 23.00, 24.00, 25.00,
-        ];
-        resistance_penalty_scale[self.character.skills.talent as usize]
-    }
-
-    fn crinsom_knot_bonus(&self) -> f64 {
-        if self.character.traces.ability_3 {
-            return 30.0 * std::cmp::min(3, self.thunder_core_bonus_stack) as f64;
-        } else {
-            return 0.0;
-        }
-    }
-
-    fn the_abyss_multiplier(&self, teammates: &[Box<dyn Support>]) -> f64 {
-        let mut num_same_path = 0;
-        for teammate in teammates {
-            if teammate.get_path() == self.character.path {
-                num_same_path += 1;
-            }
-        }
-        if self.character.eidolon >= 2 {
-            num_same_path += 1;
-        }
-        num_same_path = std::cmp::min(2, num_same_path);
-        let the_abyss = match self.character.traces.ability_2 {
-            true => [1.0, 1.15, 1.6][num_same_path as usize],
-            false => 1.0,
-        };
-        the_abyss
-    }
-
-    fn eidolon_1(&self) -> f64 {
-        if self.character.eidolon >= 1 && self.activate_eidolon_1 {
-            return 18.0;
-        } else {
-            return 0.0;
-        }
-    }
-
-    fn eidolon_4(&self, enemy: &mut Enemy) {
-        if self.character.eidolon >= 4 {
-            enemy.vulnerability += 0.08;
-        }
-    }
-
-    fn eidolon_6(&self) -> f64 {
-        if self.character.eidolon >= 6 {
-            return 20.0;
-        } else {
-            return 0.0;
-        }
-    }
-
-    fn eidolon_6_skill_type(&self, skill_type: SkillType) -> SkillType {
-        if self.character.eidolon >= 6 {
-            return SkillType::Ultimate;
-        }
-        return skill_type;
-    }
-
-    fn crimson_knot(
-        &self,
-        relics: &Relics,
-        enemy: &Enemy,
-        teammates: &[Box<dyn Support>],
-    ) -> Result<f64> {
-        let skill_type = self.eidolon_6_skill_type(SkillType::Ultimate);
-        let (mut base_stats, mut bonus) = base_stats_and_bonus(
-            &self.character,
-            &self.light_cone,
-            relics,
-            &AttackType::Lightning,
-            &skill_type,
-            &DamageType::Normal,
-            teammates,
-        )?;
-        let ability_multiplier = [
-            0.0, 0.0900, 0.0960, 0.1020, 0.1080, 0.1140, 0.1200, 0.1275, 0.1350, 0.1425, 0.1500,
-            0.1560, 0.1620, 0.1680, 0.1740, 0.1800,
-        ][self.character.skills.ult as usize];
-        let ability_multiplier = match self.crimson_knot {
-            1 | 2 => ability_multiplier + ability_multiplier * self.crimson_knot as f64,
-            0 => ability_multiplier,
-            _ => ability_multiplier + ability_multiplier * 3.0,
-        };
-        let mut enemy = enemy.clone();
-        self.eidolon_4(&mut enemy);
-        *bonus.entry(Stats::ResPenentration_).or_default() += self.talent() + self.eidolon_6();
-        *bonus.entry(Stats::DmgBoost_).or_default() += self.crinsom_knot_bonus();
-        self.calculate_damage(
-            teammates,
-            ability_multiplier,
-            &mut base_stats,
-            &bonus,
-            &enemy,
-        )
-    }
-
-    fn rainblade(
-        &self,
-        relics: &Relics,
-        enemy: &Enemy,
-        teammates: &[Box<dyn Support>],
-    ) -> Result<f64> {
-        let skill_type = self.eidolon_6_skill_type(SkillType::Ultimate);
-        let (mut base_stats, mut bonus) = base_stats_and_bonus(
-            &self.character,
-            &self.light_cone,
-            relics,
-            &AttackType::Lightning,
-            &skill_type,
-            &DamageType::Normal,
-            teammates,
-        )?;
-        let ability_multiplier = [
-            0.0, 0.1440, 0.1536, 0.1632, 0.1728, 0.1824, 0.1920, 0.2040, 0.2160, 0.2280, 0.2400,
-            0.2496, 0.2592, 0.2688, 0.2784, 0.2880,
-        ][self.character.skills.ult as usize];
-        let mut enemy = enemy.clone();
-        self.eidolon_4(&mut enemy);
-        *bonus.entry(Stats::ResPenentration_).or_default() += self.talent() + self.eidolon_6();
-        *bonus.entry(Stats::DmgBoost_).or_default() += self.crinsom_knot_bonus();
-        self.calculate_damage(
-            teammates,
-            ability_multiplier,
-            &mut base_stats,
-            &bonus,
-            &enemy,
-        )
-    }
-
-    fn stygian_resurge(
-        &self,
-        relics: &Relics,
-        enemy: &Enemy,
-        teammates: &[Box<dyn Support>],
-    ) -> Result<f64> {
-        let skill_type = self.eidolon_6_skill_type(SkillType::Ultimate);
-        let (mut base_stats, mut bonus) = base_stats_and_bonus(
-            &self.character,
-            &self.light_cone,
-            relics,
-            &AttackType::Lightning,
-            &skill_type,
-            &DamageType::Normal,
-            teammates,
-        )?;
-        let ability_multiplier = [
-            0.0, 0.7200, 0.7680, 0.8160, 0.8640, 0.9120, 0.9600, 1.0200, 1.0800, 1.1400, 1.2000,
-            1.2480, 1.2960, 1.3440, 1.3920, 1.4400,
-        ][self.character.skills.ult as usize];
-        let mut enemy = enemy.clone();
-        self.eidolon_4(&mut enemy);
-        *bonus.entry(Stats::ResPenentration_).or_default() += self.talent() + self.eidolon_6();
-        *bonus.entry(Stats::DmgBoost_).or_default() += self.crinsom_knot_bonus();
-        self.calculate_damage(
-            teammates,
-            ability_multiplier,
-            &mut base_stats,
-            &bonus,
-            &enemy,
-        )
-    }
-
-    fn thunder_core(
-        &self,
-        relics: &Relics,
-        enemy: &Enemy,
-        teammates: &[Box<dyn Support>],
-    ) -> Result<f64> {
-        let skill_type = self.eidolon_6_skill_type(SkillType::Ultimate);
-        let (mut base_stats, mut bonus) = base_stats_and_bonus(
-            &self.character,
-            &self.light_cone,
-            relics,
-            &AttackType::Lightning,
-            &skill_type,
-            &DamageType::Normal,
-            teammates,
-        )?;
-        let ability_multiplier = if self.character.traces.ability_3 {
-            0.25
-        } else {
-            0.0
-        };
-        let mut enemy = enemy.clone();
-        self.eidolon_4(&mut enemy);
-        *bonus.entry(Stats::ResPenentration_).or_default() += self.talent() + self.eidolon_6();
-        *bonus.entry(Stats::DmgBoost_).or_default() += self.crinsom_knot_bonus();
-        self.calculate_damage(
-            teammates,
-            ability_multiplier,
-            &mut base_stats,
-            &bonus,
-            &enemy,
-        )
-    }
-
-    fn full_ultimate_multiplier_on_three_enemies(
-        &self,
-        relics: &Relics,
-        enemy: &Enemy,
-        teammates: &[Box<dyn Support>],
-    ) -> Result<f64> {
-        let rainblade = self.rainblade(relics, enemy, teammates)?;
-        let crinsom_knot = self.crimson_knot(relics, enemy, teammates)?;
-        let bounce_atk = self.thunder_core(relics, enemy, teammates)?;
-        let stygian_resurge = self.stygian_resurge(relics, enemy, teammates)?;
-        Ok(rainblade * 3.0 + crinsom_knot * 9.0 + bounce_atk * 6.0 + stygian_resurge * 3.0)
-    }
-
-    fn full_ultimate_multiplier_on_single_enemy(
-        &self,
-        relics: &Relics,
-        enemy: &Enemy,
-        teammates: &[Box<dyn Support>],
-    ) -> Result<f64> {
-        let rainblade = self.rainblade(relics, enemy, teammates)?;
-        let crinsom_knot = self.crimson_knot(relics, enemy, teammates)?;
-        let bounce_atk = self.thunder_core(relics, enemy, teammates)?;
-        let stygian_resurge = self.stygian_resurge(relics, enemy, teammates)?;
-        Ok(rainblade * 3.0 + crinsom_knot * 3.0 + bounce_atk * 6.0 + stygian_resurge)
-    }
-
-    fn skill_main_target(
-        &self,
-        relics: &Relics,
-        enemy: &Enemy,
-        teammates: &[Box<dyn Support>],
-    ) -> Result<f64> {
-        let skill_type = self.eidolon_6_skill_type(SkillType::Skill);
-        let (mut base_stats, bonus) = base_stats_and_bonus(
-            &self.character,
-            &self.light_cone,
-            relics,
-            &AttackType::Lightning,
-            &skill_type,
-            &DamageType::Normal,
-            teammates,
-        )?;
-        let ability_multiplier = [
-            0.0, 0.8, 0.88, 0.96, 1.04, 1.12, 1.2, 1.3, 1.4, 1.5, 1.6, 1.68, 1.76, 1.84, 1.92, 2.0,
-        ][self.character.skills.skill as usize];
-        self.calculate_damage(
-            teammates,
-            ability_multiplier,
-            &mut base_stats,
-            &bonus,
-            enemy,
-        )
-    }
-
-    fn skill_adjacent_target(
-        &self,
-        relics: &Relics,
-        enemy: &Enemy,
-        teammates: &[Box<dyn Support>],
-    ) -> Result<f64> {
-        let skill_type = self.eidolon_6_skill_type(SkillType::Skill);
-        let (mut base_stats, bonus) = base_stats_and_bonus(
-            &self.character,
-            &self.light_cone,
-            relics,
-            &AttackType::Lightning,
-            &skill_type,
-            &DamageType::Normal,
-            teammates,
-        )?;
-        let ability_multiplier = [
-            0.0, 0.3, 0.33, 0.36, 0.39, 0.42, 0.45, 0.4875, 0.525, 0.5625, 0.6, 0.63, 0.66, 0.69,
-            0.72, 0.75,
-        ][self.character.skills.skill as usize];
-        self.calculate_damage(
-            teammates,
-            ability_multiplier,
-            &mut base_stats,
-            &bonus,
-            enemy,
-        )
+use crate::domain::{DamageType, SkillType, Stats};
+
+/// Acheron (1308).
+///
+/// Options:
+/// - `crimson_knot`: stacks of Crimson Knot on the enemies, the ultimate removing up to 3 for
+///   extra DMG (default 9).
+/// - `thunder_core_bonus_stack`: stacks of the A6 DMG bonus, up to 3 (default 3).
+/// - `activate_eidolon_1`: whether the enemy is debuffed for the eidolon 1 CRIT Rate
+///   (default false).
+///
+/// The Abyss counts the Nihility teammates of the team.
+pub fn kit() -> Kit {
+    let ability = |name, skill_type, targeting, level, multiplier: &[f64]| Ability {
+        name,
+        unlock: Unlock::Always,
+        skill_type,
+        damage_type: DamageType::Normal,
+        targeting,
+        scaling: Scaling::Stat(Stats::Atk),
+        level,
+        multiplier: ScalingTable(multiplier.to_vec()),
+    };
+    let skill = ability(
+        "skill",
+        SkillType::Skill,
+        Targeting::MainTarget,
+        SkillLevel::Skill,
+        &[
+            0.8, 0.88, 0.96, 1.04, 1.12, 1.2, 1.3, 1.4, 1.5, 1.6, 1.68, 1.76, 1.84, 1.92, 2.0,
+        ],
+    );
+    let skill_adjacent = ability(
+        "skill_adjacent",
+        SkillType::Skill,
+        Targeting::Adjacent,
+        SkillLevel::Skill,
+        &[
+            0.3, 0.33, 0.36, 0.39, 0.42, 0.45, 0.4875, 0.525, 0.5625, 0.6, 0.63, 0.66, 0.69, 0.72,
+            0.75,
+        ],
+    );
+    let mut abilities = vec![
+        ability(
+            "rainblade",
+            SkillType::Ultimate,
+            Targeting::MainTarget,
+            SkillLevel::Ultimate,
+            &[
+                0.144, 0.1536, 0.1632, 0.1728, 0.1824, 0.192, 0.204, 0.216, 0.228, 0.24, 0.2496,
+                0.2592, 0.2688, 0.2784, 0.288,
+            ],
+        ),
+        ability(
+            "crimson_knot",
+            SkillType::Ultimate,
+            Targeting::All,
+            SkillLevel::Ultimate,
+            &[
+                0.09, 0.096, 0.102, 0.108, 0.114, 0.12, 0.1275, 0.135, 0.1425, 0.15, 0.156, 0.162,
+                0.168, 0.174, 0.18,
+            ],
+        ),
+        Ability {
+            unlock: Unlock::Trace(3),
+            ..ability(
+                "thunder_core",
+                SkillType::Ultimate,
+                Targeting::Bounce,
+                SkillLevel::Ultimate,
+                &[0.25],
+            )
+        },
+        ability(
+            "stygian_resurge",
+            SkillType::Ultimate,
+            Targeting::All,
+            SkillLevel::Ultimate,
+            &[
+                0.72, 0.768, 0.816, 0.864, 0.912, 0.96, 1.02, 1.08, 1.14, 1.2, 1.248, 1.296, 1.344,
+                1.392, 1.44,
+            ],
+        ),
+    ];
+    // Eidolon 6 has the skill deal Ultimate DMG.
+    for skill in [skill, skill_adjacent] {
+        abilities.extend([
+            Ability {
+                unlock: Unlock::BeforeEidolon(6),
+                ..skill.clone()
+            },
+            Ability {
+                unlock: Unlock::Eidolon(6),
+                skill_type: SkillType::Ultimate,
+                ..skill
+            },
+        ]);
     }
-
-    fn skill(&self, relics: &Relics, enemy: &Enemy, teammates: &[Box<dyn Support>]) -> Result<f64> {
-        Ok(self.skill_adjacent_target(relics, enemy, teammates)? * 2.0
-            + self.skill_main_target(relics, enemy, teammates)?)
+    let ultimate = vec![
+        Hit::new("rainblade", 3.0),
+        // Each Crimson Knot removed adds the hit's DMG once more.
+        Hit {
+            stacks: Some(Stacks {
+                option: "crimson_knot",
+                offset: -1.0,
+                max: 4.0,
+            }),
+            ..Hit::new("crimson_knot", 3.0)
+        },
+        Hit::new("thunder_core", 6.0),
+        Hit::new("stygian_resurge", 1.0),
+    ];
+    let the_abyss = |unlock, offset| DmgMultiplier {
+        name: "The Abyss",
+        unlock: Unlock::All(vec![Unlock::Trace(2), unlock]),
+        skill_types: vec![
+            SkillType::BasicAttack,
+            SkillType::Skill,
+            SkillType::Ultimate,
+        ],
+        stacks: Some(Stacks {
+            option: "same_path_teammates",
+            offset,
+            max: 2.0,
+        }),
+        values: vec![1.0, 1.15, 1.6],
+    };
+    Kit {
+        options: vec![
+            KitOption {
+                name: "crimson_knot",
+                default: 9.0,
+            },
+            KitOption {
+                name: "thunder_core_bonus_stack",
+                default: 3.0,
+            },
+            KitOption {
+                name: "activate_eidolon_1",
+                default: 0.0,
+            },
+        ],
+        abilities,
+        modifiers: vec![
+            Modifier {
+                name: "Atop Rainleaf Hangs Oneness",
+                unlock: Unlock::Always,
+                stat: Stats::ResPenentration_,
+                amount: Amount::Scaling(SkillLevel::Talent, ScalingTable::skill(20.0)),
+                skill_types: vec![SkillType::Ultimate],
+                ..Default::default()
+            },
+            Modifier {
+                name: "Thunder Core",
+                unlock: Unlock::Trace(3),
+                stat: Stats::DmgBoost_,
+                amount: Amount::Flat(30.0),
+                skill_types: vec![SkillType::Ultimate],
+                stacks: Some(Stacks {
+                    option: "thunder_core_bonus_stack",
+                    offset: 0.0,
+                    max: 3.0,
+                }),
+                ..Default::default()
+            },
+            Modifier {
+                name: "Silenced Sky Spake Sooth",
+                unlock: Unlock::Eidolon(1),
+                stat: Stats::CritRate_,
+                amount: Amount::Flat(18.0),
+                requires: vec![Requirement {
+                    option: "activate_eidolon_1",
+                    min: 1.0,
+                }],
+                ..Default::default()
+            },
+            Modifier {
+                name: "Shrined Fire for Mirrored Soul",
+                unlock: Unlock::Eidolon(4),
+                stat: Stats::Vulnerebility_,
+                amount: Amount::Flat(8.0),
+                skill_types: vec![SkillType::Ultimate],
+                ..Default::default()
+            },
+            Modifier {
+                name: "Apocalypse, the Emancipator",
+                unlock: Unlock::Eidolon(6),
+                stat: Stats::ResPenentration_,
+                amount: Amount::Flat(20.0),
+                skill_types: vec![SkillType::Ultimate],
+                ..Default::default()
+            },
+        ],
+        // Eidolon 2 counts as one more Nihility teammate.
+        dmg_multipliers: vec![
+            the_abyss(Unlock::BeforeEidolon(2), 0.0),
+            the_abyss(Unlock::Eidolon(2), -1.0),
+        ],
+        targets: vec![
+            KitTarget {
+                name: "Skill",
+                hits: vec![Hit::new("skill_adjacent", 1.0), Hit::new("skill", 1.0)],
+                ..Default::default()
+            },
+            KitTarget {
+                name: "UltimateSingle",
+                hits: ultimate.clone(),
+                single_target: true,
+            },
+            KitTarget {
+                name: "UltimateAoe",
+                hits: ultimate,
+                ..Default::default()
+            },
+        ],
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::domain::{
-        BaseStats, CharacterSkills, CharacterTraces, LightCone, LightConePassiveConfig, Path,
-        RawRelic, RelicSetConfig, Slot, SubStats,
-    };
-
     use super::*;
+    use crate::{
+        character::{kit::KitEvaluator, Evaluator, Support},
+        domain::{
+            AttackType, BaseStats, Character, CharacterSkills, CharacterTraces, CritEnum,
+            Encounter, Enemy, LightCone, LightConeEntity, LightConePassiveConfig, Path, RawRelic,
+            RelicSetConfig, Relics, Slot, SubStats,
+        },
+    };
+    use eyre::Result;
 
     #[test]
     fn test_ultimate_single() -> Result<()> {
-        let (acheron, relics, enemy, teammates) = setup()?;
-        let target = AcheronEvaluationTarget::UltimateSingle;
-
-        assert_eq!(
-            acheron.evaluate(&relics, &enemy, &target, &teammates)?,
-            86053.53299468735
-        );
+        let damage = setup()?.damage("UltimateSingle")?;
+        assert!((damage - 86053.53299468735).abs() < 1e-6);
         Ok(())
     }
 
     #[test]
     fn test_ultimate_aoe() -> Result<()> {
-        let (acheron, relics, enemy, teammates) = setup()?;
-        let target = AcheronEvaluationTarget::UltimateAoe;
-
-        assert_eq!(
-            acheron.evaluate(&relics, &enemy, &target, &teammates)?,
-            183491.9791746075
-        );
+        let damage = setup()?.damage("UltimateAoe")?;
+        assert!((damage - 183491.9791746075).abs() < 1e-6);
         Ok(())
     }
 
     #[test]
     fn test_skill() -> Result<()> {
-        let (acheron, relics, enemy, teammates) = setup()?;
-        let target = AcheronEvaluationTarget::Skill;
-
-        assert_eq!(
-            acheron.evaluate(&relics, &enemy, &target, &teammates)?,
-            25324.79422299673
-        );
+        let damage = setup()?.damage("Skill")?;
+        assert!((damage - 25324.79422299673).abs() < 1e-6);
         Ok(())
     }
 
-    fn setup() -> Result<(Acheron, Relics, Enemy, Vec<Box<dyn Support>>)> {
+    /// Acheron with her build, against 3 enemies alongside two Nihility teammates.
+    struct Setup {
+        acheron: KitEvaluator,
+        relics: Relics,
+        encounter: Encounter,
+        teammates: Vec<Box<dyn Support>>,
+    }
+
+    impl Setup {
+        fn damage(&self, target: &str) -> Result<f64> {
+            self.acheron.evaluate(
+                &self.relics,
+                &self.encounter,
+                &target.to_string(),
+                &self.teammates,
+            )
+        }
+    }
+
+    fn setup() -> Result<Setup> {
         let character = Character {
             id: "1308".to_string(),
             name: "Acheron".to_string(),
@@ -472,6 +335,7 @@ mod tests {
                 stack_21001: 2,
                 ..Default::default()
             },
+            ignore_passive: false,
         };
         let relics = Relics {
             relics: vec![
@@ -689,6 +553,7 @@ mod tests {
                 activate_316: true,
                 activate_318: true,
                 stack_321: 4,
+                ..Default::default()
             },
         };
         let enemy = Enemy {
@@ -699,21 +564,25 @@ mod tests {
             vulnerability: 0.0,
             toughness_break: false,
             weaken: 0.0,
+            ..Default::default()
         };
-        let acheron = Acheron {
+        let acheron = KitEvaluator::new(
+            kit(),
             character,
-            light_cone: Some(light_cone),
-            crimson_knot: 9,
-            crit: CritEnum::Avg,
-            thunder_core_bonus_stack: 3,
-            activate_eidolon_1: false,
-        };
-        Ok((
+            Some(light_cone),
+            CritEnum::Avg,
+            serde_json::json!({
+                "crimson_knot": 9,
+                "thunder_core_bonus_stack": 3,
+                "activate_eidolon_1": false,
+            }),
+        )?;
+        Ok(Setup {
             acheron,
             relics,
-            enemy,
-            vec![Box::new(Pela {}), Box::new(Jiaoqiu {})],
-        ))
+            encounter: Encounter::copies(enemy, 3),
+            teammates: vec![Box::new(Pela {}), Box::new(Jiaoqiu {})],
+        })
     }
 
     struct Pela {}
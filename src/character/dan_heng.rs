@@ -0,0 +1,207 @@
+use super::kit::{
+    Ability, Amount, Hit, Kit, KitOption, KitTarget, Modifier, Requirement, Scaling, ScalingTable,
+    SkillLevel, Stacks, Targeting, Unlock,
+};
+use crate::domain::{DamageType, SkillType, Stats};
+
+const SLOWED: Requirement = Requirement {
+    option: "enemy_slowed",
+    min: 1.0,
+};
+/// Super break only lands on enemies whose weakness is broken.
+const BROKEN: Requirement = Requirement {
+    option: "enemy_broken",
+    min: 1.0,
+};
+const SUPER_BREAK: Stacks = Stacks {
+    option: "super_break",
+    offset: 0.0,
+    max: f64::INFINITY,
+};
+
+/// Dan Heng (1002).
+///
+/// Options:
+/// - `enemy_slowed`: the target is slowed by his skill (default on).
+/// - `talent_active`: he was targeted by an ally's ability since his last attack (default on).
+/// - `enemy_hp_above_half`: condition of eidolon 1 (default on).
+/// - `super_break`: super break DMG granted by a teammate, as a ratio of the toughness his hits
+///   reduce, e.g. 1.6 from the Harmony Trailblazer's Backup Dancer against a lone enemy
+///   (default 0).
+///
+/// The `Break` target is the weakness break of his Wind element and the first tick of the Wind
+/// Shear it inflicts, 3 stacks against elites and bosses.
+pub fn kit() -> Kit {
+    let super_break = |name, skill_type, toughness| Ability {
+        name,
+        unlock: Unlock::Always,
+        skill_type,
+        damage_type: DamageType::SuperBreakDamage,
+        targeting: Targeting::MainTarget,
+        scaling: Scaling::SuperBreak(toughness),
+        level: SkillLevel::Basic,
+        multiplier: ScalingTable::flat(1.0),
+    };
+    let super_break_hit = |ability| Hit {
+        requires: vec![BROKEN],
+        stacks: Some(SUPER_BREAK),
+        ..Hit::new(ability, 1.0)
+    };
+    Kit {
+        options: vec![
+            KitOption {
+                name: "enemy_slowed",
+                default: 1.0,
+            },
+            KitOption {
+                name: "talent_active",
+                default: 1.0,
+            },
+            KitOption {
+                name: "enemy_hp_above_half",
+                default: 1.0,
+            },
+            KitOption {
+                name: "super_break",
+                default: 0.0,
+            },
+        ],
+        abilities: vec![
+            Ability {
+                name: "basic",
+                unlock: Unlock::Always,
+                skill_type: SkillType::BasicAttack,
+                damage_type: DamageType::Normal,
+                targeting: Targeting::MainTarget,
+                scaling: Scaling::Stat(Stats::Atk),
+                level: SkillLevel::Basic,
+                multiplier: ScalingTable::basic(1.0),
+            },
+            Ability {
+                name: "skill",
+                unlock: Unlock::Always,
+                skill_type: SkillType::Skill,
+                damage_type: DamageType::Normal,
+                targeting: Targeting::MainTarget,
+                scaling: Scaling::Stat(Stats::Atk),
+                level: SkillLevel::Skill,
+                multiplier: ScalingTable::skill(2.6),
+            },
+            Ability {
+                name: "ultimate",
+                unlock: Unlock::Always,
+                skill_type: SkillType::Ultimate,
+                damage_type: DamageType::Normal,
+                targeting: Targeting::MainTarget,
+                scaling: Scaling::Stat(Stats::Atk),
+                level: SkillLevel::Ultimate,
+                multiplier: ScalingTable::ultimate(4.0),
+            },
+            Ability {
+                name: "ultimate_slowed",
+                unlock: Unlock::Always,
+                skill_type: SkillType::Ultimate,
+                damage_type: DamageType::Normal,
+                targeting: Targeting::MainTarget,
+                scaling: Scaling::Stat(Stats::Atk),
+                level: SkillLevel::Ultimate,
+                multiplier: ScalingTable::ultimate(1.2),
+            },
+            super_break("basic_super_break", SkillType::BasicAttack, 30.0),
+            super_break("skill_super_break", SkillType::Skill, 60.0),
+            super_break("ultimate_super_break", SkillType::Ultimate, 90.0),
+            Ability {
+                name: "weakness_break",
+                unlock: Unlock::Always,
+                skill_type: SkillType::Skill,
+                damage_type: DamageType::BreakDamage,
+                targeting: Targeting::MainTarget,
+                scaling: Scaling::Break,
+                level: SkillLevel::Skill,
+                multiplier: ScalingTable::flat(1.0),
+            },
+            Ability {
+                name: "wind_shear",
+                unlock: Unlock::Always,
+                skill_type: SkillType::Skill,
+                damage_type: DamageType::DamageOnTime,
+                targeting: Targeting::MainTarget,
+                scaling: Scaling::BreakDot,
+                level: SkillLevel::Skill,
+                multiplier: ScalingTable::flat(1.0),
+            },
+        ],
+        modifiers: vec![
+            Modifier {
+                name: "Superiority of Reach",
+                stat: Stats::ResPenentration_,
+                amount: Amount::Scaling(SkillLevel::Talent, ScalingTable::skill(36.0)),
+                requires: vec![Requirement {
+                    option: "talent_active",
+                    min: 1.0,
+                }],
+                ..Default::default()
+            },
+            Modifier {
+                name: "High Gale",
+                unlock: Unlock::Trace(3),
+                stat: Stats::DmgBoost_,
+                amount: Amount::Flat(40.0),
+                skill_types: vec![SkillType::BasicAttack],
+                requires: vec![SLOWED],
+                ..Default::default()
+            },
+            Modifier {
+                name: "The Higher You Fly, the Harder You Fall",
+                unlock: Unlock::Eidolon(1),
+                stat: Stats::CritRate_,
+                amount: Amount::Flat(12.0),
+                requires: vec![Requirement {
+                    option: "enemy_hp_above_half",
+                    min: 1.0,
+                }],
+                ..Default::default()
+            },
+        ],
+        targets: vec![
+            KitTarget {
+                name: "Basic",
+                hits: vec![Hit::new("basic", 1.0), super_break_hit("basic_super_break")],
+                ..Default::default()
+            },
+            KitTarget {
+                name: "Skill",
+                hits: vec![Hit::new("skill", 1.0), super_break_hit("skill_super_break")],
+                ..Default::default()
+            },
+            KitTarget {
+                name: "Ultimate",
+                hits: vec![
+                    Hit::new("ultimate", 1.0),
+                    Hit {
+                        requires: vec![SLOWED],
+                        ..Hit::new("ultimate_slowed", 1.0)
+                    },
+                    super_break_hit("ultimate_super_break"),
+                ],
+                ..Default::default()
+            },
+            KitTarget {
+                name: "Break",
+                hits: vec![
+                    Hit::new("weakness_break", 1.0),
+                    Hit::new("wind_shear", 1.0),
+                    Hit {
+                        requires: vec![Requirement {
+                            option: "enemy_elite",
+                            min: 1.0,
+                        }],
+                        ..Hit::new("wind_shear", 2.0)
+                    },
+                ],
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    }
+}
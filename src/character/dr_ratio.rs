@@ -0,0 +1,190 @@
+use super::kit::{
+    Ability, Amount, Hit, Kit, KitOption, KitTarget, Modifier, Requirement, Scaling, ScalingTable,
+    SkillLevel, Stacks, Targeting, Unlock,
+};
+use crate::domain::{DamageType, SkillType, Stats};
+
+const SUMMATION: Stacks = Stacks {
+    option: "summation_stacks",
+    offset: 0.0,
+    max: 6.0,
+};
+/// Eidolon 1 raises the cap of Summation by 4.
+const SUMMATION_E1: Stacks = Stacks {
+    option: "summation_stacks",
+    offset: 6.0,
+    max: 4.0,
+};
+
+/// Dr. Ratio (1305).
+///
+/// Options:
+/// - `enemy_debuffs`: number of debuffs on the target (default 3).
+/// - `summation_stacks`: stacks of Summation, up to 6 or 10 from eidolon 1 (default 6).
+pub fn kit() -> Kit {
+    Kit {
+        options: vec![
+            KitOption {
+                name: "enemy_debuffs",
+                default: 3.0,
+            },
+            KitOption {
+                name: "summation_stacks",
+                default: 6.0,
+            },
+        ],
+        abilities: vec![
+            Ability {
+                name: "basic",
+                unlock: Unlock::Always,
+                skill_type: SkillType::BasicAttack,
+                damage_type: DamageType::Normal,
+                targeting: Targeting::MainTarget,
+                scaling: Scaling::Stat(Stats::Atk),
+                level: SkillLevel::Basic,
+                multiplier: ScalingTable::basic(1.0),
+            },
+            Ability {
+                name: "skill",
+                unlock: Unlock::Always,
+                skill_type: SkillType::Skill,
+                damage_type: DamageType::Normal,
+                targeting: Targeting::MainTarget,
+                scaling: Scaling::Stat(Stats::Atk),
+                level: SkillLevel::Skill,
+                multiplier: ScalingTable::skill(1.5),
+            },
+            Ability {
+                name: "ultimate",
+                unlock: Unlock::Always,
+                skill_type: SkillType::Ultimate,
+                damage_type: DamageType::Normal,
+                targeting: Targeting::MainTarget,
+                scaling: Scaling::Stat(Stats::Atk),
+                level: SkillLevel::Ultimate,
+                multiplier: ScalingTable::ultimate(2.4),
+            },
+            Ability {
+                name: "follow_up",
+                unlock: Unlock::Always,
+                skill_type: SkillType::FollowUpAttack,
+                damage_type: DamageType::Normal,
+                targeting: Targeting::MainTarget,
+                scaling: Scaling::Stat(Stats::Atk),
+                level: SkillLevel::Talent,
+                multiplier: ScalingTable::skill(2.7),
+            },
+            Ability {
+                name: "follow_up_e2",
+                unlock: Unlock::Eidolon(2),
+                skill_type: SkillType::FollowUpAttack,
+                damage_type: DamageType::Normal,
+                targeting: Targeting::MainTarget,
+                scaling: Scaling::Stat(Stats::Atk),
+                level: SkillLevel::Talent,
+                multiplier: ScalingTable::flat(0.2),
+            },
+        ],
+        modifiers: vec![
+            Modifier {
+                name: "Summation",
+                unlock: Unlock::Trace(1),
+                stat: Stats::CritRate_,
+                amount: Amount::Flat(2.5),
+                stacks: Some(SUMMATION),
+                ..Default::default()
+            },
+            Modifier {
+                name: "Summation",
+                unlock: Unlock::Trace(1),
+                stat: Stats::CritDmg_,
+                amount: Amount::Flat(5.0),
+                stacks: Some(SUMMATION),
+                ..Default::default()
+            },
+            Modifier {
+                name: "Pride Comes Before a Fall",
+                unlock: Unlock::Eidolon(1),
+                stat: Stats::CritRate_,
+                amount: Amount::Flat(2.5),
+                stacks: Some(SUMMATION_E1),
+                ..Default::default()
+            },
+            Modifier {
+                name: "Pride Comes Before a Fall",
+                unlock: Unlock::Eidolon(1),
+                stat: Stats::CritDmg_,
+                amount: Amount::Flat(5.0),
+                stacks: Some(SUMMATION_E1),
+                ..Default::default()
+            },
+            Modifier {
+                name: "Deduction",
+                unlock: Unlock::Trace(3),
+                stat: Stats::DmgBoost_,
+                amount: Amount::Flat(10.0),
+                requires: vec![Requirement {
+                    option: "enemy_debuffs",
+                    min: 3.0,
+                }],
+                stacks: Some(Stacks {
+                    option: "enemy_debuffs",
+                    offset: 0.0,
+                    max: 5.0,
+                }),
+                ..Default::default()
+            },
+            Modifier {
+                name: "Thesis",
+                unlock: Unlock::Eidolon(6),
+                stat: Stats::DmgBoost_,
+                amount: Amount::Flat(50.0),
+                skill_types: vec![SkillType::FollowUpAttack],
+                ..Default::default()
+            },
+        ],
+        targets: vec![
+            KitTarget {
+                name: "Basic",
+                hits: vec![Hit::new("basic", 1.0)],
+                ..Default::default()
+            },
+            KitTarget {
+                name: "Skill",
+                hits: vec![Hit::new("skill", 1.0)],
+                ..Default::default()
+            },
+            KitTarget {
+                name: "Ultimate",
+                hits: vec![Hit::new("ultimate", 1.0)],
+                ..Default::default()
+            },
+            KitTarget {
+                name: "FollowUp",
+                hits: follow_up(),
+                ..Default::default()
+            },
+            KitTarget {
+                name: "SkillWithFollowUp",
+                hits: [vec![Hit::new("skill", 1.0)], follow_up()].concat(),
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    }
+}
+
+/// Talent follow-up with the extra hits of eidolon 2, one per debuff up to 4.
+fn follow_up() -> Vec<Hit> {
+    vec![
+        Hit::new("follow_up", 1.0),
+        Hit {
+            stacks: Some(Stacks {
+                option: "enemy_debuffs",
+                offset: 0.0,
+                max: 4.0,
+            }),
+            ..Hit::new("follow_up_e2", 1.0)
+        },
+    ]
+}
@@ -0,0 +1,60 @@
+use super::kit::{
+    Ability, Hit, Kit, KitTarget, Scaling, ScalingTable, SkillLevel, Targeting, Unlock,
+};
+use crate::domain::{DamageType, SkillType, Stats};
+
+/// Gepard (1104). The ultimate shield is the one of a single ally, every ally getting the same.
+pub fn kit() -> Kit {
+    Kit {
+        abilities: vec![
+            Ability {
+                name: "basic",
+                unlock: Unlock::Always,
+                skill_type: SkillType::BasicAttack,
+                damage_type: DamageType::Normal,
+                targeting: Targeting::MainTarget,
+                scaling: Scaling::Stat(Stats::Atk),
+                level: SkillLevel::Basic,
+                multiplier: ScalingTable::basic(1.0),
+            },
+            Ability {
+                name: "skill",
+                unlock: Unlock::Always,
+                skill_type: SkillType::Skill,
+                damage_type: DamageType::Normal,
+                targeting: Targeting::MainTarget,
+                scaling: Scaling::Stat(Stats::Atk),
+                level: SkillLevel::Skill,
+                multiplier: ScalingTable::skill(2.0),
+            },
+            Ability {
+                name: "ultimate_shield",
+                unlock: Unlock::Always,
+                skill_type: SkillType::Ultimate,
+                damage_type: DamageType::Shield,
+                targeting: Targeting::MainTarget,
+                scaling: Scaling::StatAndFlat(Stats::Def, ScalingTable::between(150.0, 600.0)),
+                level: SkillLevel::Ultimate,
+                multiplier: ScalingTable::between(0.3, 0.45),
+            },
+        ],
+        targets: vec![
+            KitTarget {
+                name: "Basic",
+                hits: vec![Hit::new("basic", 1.0)],
+                ..Default::default()
+            },
+            KitTarget {
+                name: "Skill",
+                hits: vec![Hit::new("skill", 1.0)],
+                ..Default::default()
+            },
+            KitTarget {
+                name: "Shield",
+                hits: vec![Hit::new("ultimate_shield", 1.0)],
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    }
+}
@@ -0,0 +1,1089 @@
+use super::{total_damage, DamageHit, Evaluator, Support};
+use crate::{
+    domain::{
+        Character, CritEnum, DamageType, Encounter, Enemy, LightConeEntity, Relics, SkillType,
+        Stats,
+    },
+    utils::calculator::{
+        base_stats_and_bonus, break_base_dmg, break_dot_base_dmg, calculate_stats,
+        super_break_base_dmg, DamageMultipliers,
+    },
+};
+use eyre::{bail, eyre, Result};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Multipliers of an ability from level 1 upwards, as listed in game.
+#[derive(Clone, Debug, Default)]
+pub struct ScalingTable(pub Vec<f64>);
+
+impl ScalingTable {
+    /// Basic attacks, relative to level 6.
+    const BASIC_CURVE: [f64; 9] = [0.5, 0.6, 0.7, 0.8, 0.9, 1.0, 1.1, 1.2, 1.3];
+    /// Most skills and talents, relative to level 10.
+    const SKILL_CURVE: [f64; 15] = [
+        0.5, 0.55, 0.6, 0.65, 0.7, 0.75, 0.8125, 0.875, 0.9375, 1.0, 1.05, 1.1, 1.15, 1.2, 1.25,
+    ];
+    /// Most ultimates, relative to level 10.
+    const ULTIMATE_CURVE: [f64; 15] = [
+        0.6, 0.64, 0.68, 0.72, 0.76, 0.8, 0.85, 0.9, 0.95, 1.0, 1.04, 1.08, 1.12, 1.16, 1.2,
+    ];
+
+    pub fn basic(at_level_6: f64) -> Self {
+        Self::from_curve(&Self::BASIC_CURVE, at_level_6)
+    }
+
+    pub fn skill(at_level_10: f64) -> Self {
+        Self::from_curve(&Self::SKILL_CURVE, at_level_10)
+    }
+
+    pub fn ultimate(at_level_10: f64) -> Self {
+        Self::from_curve(&Self::ULTIMATE_CURVE, at_level_10)
+    }
+
+    /// Values following the shape of the skill and ultimate curves between levels 1 and 10, for
+    /// tables that start elsewhere, e.g. Pela's DEF reduction going from 30% to 40%.
+    pub fn between(at_level_1: f64, at_level_10: f64) -> Self {
+        let step = (at_level_10 - at_level_1) / 10.0;
+        let mut value = at_level_1;
+        let mut table = vec![value];
+        for level in 2..=15 {
+            value += if (7..=10).contains(&level) {
+                1.25 * step
+            } else {
+                step
+            };
+            table.push(value);
+        }
+        Self(table)
+    }
+
+    /// The same multiplier at every level, e.g. for traces and eidolons.
+    pub fn flat(value: f64) -> Self {
+        Self(vec![value])
+    }
+
+    fn from_curve(curve: &[f64], reference: f64) -> Self {
+        Self(curve.iter().map(|ratio| ratio * reference).collect())
+    }
+
+    pub fn at(&self, level: u8) -> Result<f64> {
+        match self.0.as_slice() {
+            [flat] => Ok(*flat),
+            table => table
+                .get((level as usize).wrapping_sub(1))
+                .cloned()
+                .ok_or(eyre!("No multiplier for level {level}")),
+        }
+    }
+}
+
+/// Which skill level of the character drives a scaling table.
+#[derive(Clone, Debug, Default)]
+pub enum SkillLevel {
+    Basic,
+    #[default]
+    Skill,
+    Ultimate,
+    Talent,
+}
+
+impl SkillLevel {
+    pub fn of(&self, character: &Character) -> u8 {
+        let skills = &character.skills;
+        match self {
+            SkillLevel::Basic => skills.basic,
+            SkillLevel::Skill => skills.skill,
+            SkillLevel::Ultimate => skills.ult,
+            SkillLevel::Talent => skills.talent,
+        }
+    }
+}
+
+/// What has to be unlocked on the character for an ability or modifier to exist.
+#[derive(Clone, Debug, Default)]
+pub enum Unlock {
+    #[default]
+    Always,
+    /// Major trace 1 to 3, i.e. A2, A4 and A6.
+    Trace(u8),
+    Eidolon(u8),
+    /// Below the eidolon, for abilities an eidolon changes.
+    BeforeEidolon(u8),
+    /// Every one of the unlocks.
+    All(Vec<Unlock>),
+}
+
+impl Unlock {
+    pub fn holds(&self, character: &Character) -> bool {
+        let traces = &character.traces;
+        match self {
+            Unlock::Always => true,
+            Unlock::Trace(1) => traces.ability_1,
+            Unlock::Trace(2) => traces.ability_2,
+            Unlock::Trace(3) => traces.ability_3,
+            Unlock::Trace(_) => false,
+            Unlock::Eidolon(eidolon) => character.eidolon >= *eidolon,
+            Unlock::BeforeEidolon(eidolon) => character.eidolon < *eidolon,
+            Unlock::All(unlocks) => unlocks.iter().all(|unlock| unlock.holds(character)),
+        }
+    }
+}
+
+/// Enemies of the encounter a damage instance lands on.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Targeting {
+    #[default]
+    MainTarget,
+    /// The enemies next to the main target, e.g. the side hits of blast abilities.
+    Adjacent,
+    /// Every enemy once.
+    All,
+    /// Random enemies, each hit being spread evenly over the encounter.
+    Bounce,
+}
+
+/// What the multiplier of an ability applies to.
+#[derive(Clone, Debug)]
+pub enum Scaling {
+    /// A stat of the character, e.g. `Stats::Atk`.
+    Stat(Stats),
+    /// A stat of the character plus a flat amount from the table, e.g. heals of 10.5% of max HP
+    /// plus 280.
+    StatAndFlat(Stats, ScalingTable),
+    /// The weakness break DMG of the character's element, for `DamageType::BreakDamage`.
+    Break,
+    /// Super break DMG of a hit reducing the given toughness, for
+    /// `DamageType::SuperBreakDamage`.
+    SuperBreak(f64),
+    /// A tick of the DoT of the character's weakness break, for `DamageType::DamageOnTime`.
+    /// Wind shear stacks are counted as hits.
+    BreakDot,
+}
+
+/// A single damage instance of the kit. An ability can be declared several times under exclusive
+/// unlocks, e.g. when an eidolon changes its skill type, the first unlocked one being used.
+#[derive(Clone, Debug)]
+pub struct Ability {
+    pub name: &'static str,
+    pub unlock: Unlock,
+    pub skill_type: SkillType,
+    pub damage_type: DamageType,
+    pub targeting: Targeting,
+    pub scaling: Scaling,
+    pub level: SkillLevel,
+    pub multiplier: ScalingTable,
+}
+
+#[derive(Clone, Debug)]
+pub enum Amount {
+    Flat(f64),
+    Scaling(SkillLevel, ScalingTable),
+    /// Percent of a stat of the character granting the bonus, e.g. Bronya's ultimate sharing 16%
+    /// of her CRIT DMG.
+    StatShare(Stats, SkillLevel, ScalingTable),
+    /// Percent of the base ATK of the character receiving the bonus, e.g. Tingyun's skill.
+    BaseAtkShare(SkillLevel, ScalingTable),
+}
+
+impl Default for Amount {
+    fn default() -> Self {
+        Amount::Flat(0.0)
+    }
+}
+
+impl Amount {
+    /// Value granted by `source`, whose stat sheet is `stats`, to a character of `base_atk`.
+    pub fn value(
+        &self,
+        source: &Character,
+        stats: &HashMap<Stats, f64>,
+        base_atk: f64,
+    ) -> Result<f64> {
+        Ok(match self {
+            Amount::Flat(value) => *value,
+            Amount::Scaling(level, table) => table.at(level.of(source))?,
+            Amount::StatShare(stat, level, table) => {
+                stats.get(stat).cloned().unwrap_or_default() * table.at(level.of(source))? / 100.0
+            }
+            Amount::BaseAtkShare(level, table) => base_atk * table.at(level.of(source))? / 100.0,
+        })
+    }
+}
+
+/// A bonus granted by the kit, a trace or an eidolon, also describing what supports grant the
+/// carry. Enemy debuffs such as `Stats::Vulnerebility_` are added to the bonus like any other
+/// stat.
+#[derive(Clone, Debug, Default)]
+pub struct Modifier {
+    pub name: &'static str,
+    pub unlock: Unlock,
+    pub stat: Stats,
+    pub amount: Amount,
+    /// Restricts the bonus to these skill types, every ability when empty.
+    pub skill_types: Vec<SkillType>,
+    pub requires: Vec<Requirement>,
+    pub stacks: Option<Stacks>,
+    /// Upper bound of the bonus, e.g. Tingyun's skill ATK being capped by a share of her own.
+    pub cap: Option<Amount>,
+}
+
+impl Modifier {
+    pub fn applies_to(&self, skill_type: &SkillType) -> bool {
+        self.skill_types.is_empty() || self.skill_types.contains(skill_type)
+    }
+
+    /// Bonus at `stacks`, see `Amount::value`.
+    pub fn value(
+        &self,
+        source: &Character,
+        stats: &HashMap<Stats, f64>,
+        base_atk: f64,
+        stacks: f64,
+    ) -> Result<f64> {
+        let value = self.amount.value(source, stats, base_atk)? * stacks;
+        Ok(match &self.cap {
+            Some(cap) => value.min(cap.value(source, stats, base_atk)?),
+            None => value,
+        })
+    }
+}
+
+/// Holds when the kit option is at least `min`.
+#[derive(Clone, Debug)]
+pub struct Requirement {
+    pub option: &'static str,
+    pub min: f64,
+}
+
+/// Multiplies a value by a kit option counted from `offset`, capped at `max`.
+#[derive(Clone, Debug)]
+pub struct Stacks {
+    pub option: &'static str,
+    pub offset: f64,
+    pub max: f64,
+}
+
+impl Stacks {
+    /// Stack count when the option is at `value`.
+    pub fn count(&self, value: f64) -> f64 {
+        (value - self.offset).clamp(0.0, self.max)
+    }
+}
+
+/// Multiplies the base DMG of abilities by the value at the stack count, e.g. 1.15 and 1.6 for
+/// Acheron's The Abyss with one or two Nihility teammates.
+#[derive(Clone, Debug, Default)]
+pub struct DmgMultiplier {
+    pub name: &'static str,
+    pub unlock: Unlock,
+    /// Restricts the multiplier to these skill types, every ability when empty.
+    pub skill_types: Vec<SkillType>,
+    pub stacks: Option<Stacks>,
+    pub values: Vec<f64>,
+}
+
+/// Battle state the kit depends on, given as evaluator options.
+#[derive(Clone, Debug)]
+pub struct KitOption {
+    pub name: &'static str,
+    pub default: f64,
+}
+
+/// Options the evaluator works out from the team and the enemy rather than reading them from the
+/// user.
+const DERIVED_OPTIONS: [&str; 3] = [
+    // Teammates on the character's path.
+    "same_path_teammates",
+    // 1 against elites and bosses.
+    "enemy_elite",
+    // 1 when the enemy's weakness is broken.
+    "enemy_broken",
+];
+
+/// Abilities summed up into an evaluation target.
+#[derive(Clone, Debug, Default)]
+pub struct KitTarget {
+    pub name: &'static str,
+    pub hits: Vec<Hit>,
+    /// Only the main target of the encounter is hit, e.g. to compare AoE abilities against a
+    /// lone boss.
+    pub single_target: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct Hit {
+    pub ability: &'static str,
+    pub count: f64,
+    pub requires: Vec<Requirement>,
+    pub stacks: Option<Stacks>,
+}
+
+impl Hit {
+    pub fn new(ability: &'static str, count: f64) -> Self {
+        Self {
+            ability,
+            count,
+            requires: vec![],
+            stacks: None,
+        }
+    }
+}
+
+/// Declarative description of a character.
+#[derive(Clone, Debug, Default)]
+pub struct Kit {
+    pub options: Vec<KitOption>,
+    pub abilities: Vec<Ability>,
+    pub modifiers: Vec<Modifier>,
+    pub dmg_multipliers: Vec<DmgMultiplier>,
+    pub targets: Vec<KitTarget>,
+}
+
+impl Kit {
+    /// Makes sure every option and ability the kit refers to is declared, and that abilities
+    /// scale with what their damage type calls for.
+    fn check(&self) -> Result<()> {
+        for ability in &self.abilities {
+            let matches = match (&ability.scaling, &ability.damage_type) {
+                (Scaling::Break, DamageType::BreakDamage)
+                | (Scaling::SuperBreak(_), DamageType::SuperBreakDamage)
+                | (Scaling::BreakDot, DamageType::DamageOnTime) => true,
+                (Scaling::StatAndFlat(..), damage_type) => damage_type.is_sustain(),
+                (Scaling::Stat(_), damage_type) => !matches!(
+                    damage_type,
+                    DamageType::BreakDamage | DamageType::SuperBreakDamage
+                ),
+                _ => false,
+            };
+            if !matches {
+                bail!(
+                    "Ability {} of type {:?} can't scale with {:?}",
+                    ability.name,
+                    ability.damage_type,
+                    ability.scaling
+                );
+            }
+        }
+        let declared = |option: &str| {
+            self.options.iter().any(|o| o.name == option) || DERIVED_OPTIONS.contains(&option)
+        };
+        let options_of = |requires: &[Requirement], stacks: &Option<Stacks>| {
+            requires
+                .iter()
+                .map(|r| r.option)
+                .chain(stacks.as_ref().map(|s| s.option))
+                .collect::<Vec<_>>()
+        };
+        for modifier in &self.modifiers {
+            for option in options_of(&modifier.requires, &modifier.stacks) {
+                if !declared(option) {
+                    bail!(
+                        "Modifier {} reads undeclared option {option}",
+                        modifier.name
+                    );
+                }
+            }
+        }
+        for multiplier in &self.dmg_multipliers {
+            for option in options_of(&[], &multiplier.stacks) {
+                if !declared(option) {
+                    bail!(
+                        "DMG multiplier {} reads undeclared option {option}",
+                        multiplier.name
+                    );
+                }
+            }
+        }
+        for target in &self.targets {
+            for hit in &target.hits {
+                if !self.abilities.iter().any(|a| a.name == hit.ability) {
+                    bail!(
+                        "Target {} uses unknown ability {}",
+                        target.name,
+                        hit.ability
+                    );
+                }
+                for option in options_of(&hit.requires, &hit.stacks) {
+                    if !declared(option) {
+                        bail!("Target {} reads undeclared option {option}", target.name);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Evaluates any character described by a `Kit`.
+pub struct KitEvaluator {
+    pub kit: Kit,
+    pub character: Character,
+    pub light_cone: Option<LightConeEntity>,
+    pub crit: CritEnum,
+    pub options: HashMap<String, f64>,
+}
+
+impl KitEvaluator {
+    /// `options` is a map of kit options, booleans counting as 0 or 1.
+    pub fn new(
+        kit: Kit,
+        character: Character,
+        light_cone: Option<LightConeEntity>,
+        crit: CritEnum,
+        options: Value,
+    ) -> Result<Self> {
+        let mut values: HashMap<String, f64> = kit
+            .options
+            .iter()
+            .map(|o| (o.name.to_string(), o.default))
+            .collect();
+        kit.check()?;
+        let Value::Object(options) = options else {
+            bail!("Options of {} must be a map", character.name);
+        };
+        for (name, value) in options {
+            if !values.contains_key(&name) {
+                let known = kit.options.iter().map(|o| o.name).collect::<Vec<_>>();
+                bail!(
+                    "Unknown option {name} for {}, expected one of {}",
+                    character.name,
+                    known.join(", ")
+                );
+            }
+            let value = match value {
+                Value::Bool(flag) => flag as u8 as f64,
+                Value::Number(number) => number.as_f64().unwrap_or_default(),
+                other => bail!("Option {name} must be a number or a boolean, got {other}"),
+            };
+            values.insert(name, value);
+        }
+        Ok(Self {
+            kit,
+            character,
+            light_cone,
+            crit,
+            options: values,
+        })
+    }
+
+    /// Value of a kit option against the enemy, whose debuff count overrides `enemy_debuffs`.
+    fn option(&self, name: &str, enemy: &Enemy, teammates: &[Box<dyn Support>]) -> f64 {
+        match (name, enemy.debuffs) {
+            ("enemy_debuffs", Some(debuffs)) => debuffs as f64,
+            ("same_path_teammates", _) => teammates
+                .iter()
+                .filter(|teammate| teammate.get_path() == self.character.path)
+                .count() as f64,
+            ("enemy_elite", _) => enemy.elite as u8 as f64,
+            ("enemy_broken", _) => enemy.toughness_break as u8 as f64,
+            _ => self.options.get(name).cloned().unwrap_or_default(),
+        }
+    }
+
+    fn satisfied(
+        &self,
+        requires: &[Requirement],
+        enemy: &Enemy,
+        teammates: &[Box<dyn Support>],
+    ) -> bool {
+        requires
+            .iter()
+            .all(|r| self.option(r.option, enemy, teammates) >= r.min)
+    }
+
+    fn stack_count(
+        &self,
+        stacks: &Option<Stacks>,
+        enemy: &Enemy,
+        teammates: &[Box<dyn Support>],
+    ) -> f64 {
+        stacks
+            .as_ref()
+            .map_or(1.0, |s| s.count(self.option(s.option, enemy, teammates)))
+    }
+
+    /// Product of the DMG multipliers applying to the ability.
+    fn dmg_multiplier(
+        &self,
+        ability: &Ability,
+        enemy: &Enemy,
+        teammates: &[Box<dyn Support>],
+    ) -> Result<f64> {
+        let mut product = 1.0;
+        for multiplier in self.kit.dmg_multipliers.iter().filter(|m| {
+            m.unlock.holds(&self.character)
+                && (m.skill_types.is_empty() || m.skill_types.contains(&ability.skill_type))
+        }) {
+            let stacks = self.stack_count(&multiplier.stacks, enemy, teammates);
+            product *= multiplier
+                .values
+                .get(stacks as usize)
+                .ok_or(eyre!("No value of {} for {stacks} stacks", multiplier.name))?;
+        }
+        Ok(product)
+    }
+
+    fn hit(
+        &self,
+        ability: &Ability,
+        count: f64,
+        relics: &Relics,
+        enemy: &Enemy,
+        teammates: &[Box<dyn Support>],
+    ) -> Result<DamageHit> {
+        let (stats, mut bonus) = base_stats_and_bonus(
+            &self.character,
+            &self.light_cone,
+            relics,
+            &self.character.attack_type,
+            &ability.skill_type,
+            &ability.damage_type,
+            teammates,
+        )?;
+        let base_atk = self.character.base_atk
+            + self
+                .light_cone
+                .as_ref()
+                .map(|lc| lc.base_atk)
+                .unwrap_or_default();
+        let mut kit_bonus = vec![];
+        for modifier in self.kit.modifiers.iter().filter(|m| {
+            m.unlock.holds(&self.character)
+                && self.satisfied(&m.requires, enemy, teammates)
+                && m.applies_to(&ability.skill_type)
+        }) {
+            let stacks = self.stack_count(&modifier.stacks, enemy, teammates);
+            let amount = modifier.value(&self.character, &stats, base_atk, stacks)?;
+            *bonus.entry(modifier.stat.clone()).or_default() += amount;
+            kit_bonus.push((modifier.name.to_string(), modifier.stat.clone(), amount));
+        }
+        let base_stats = calculate_stats(&bonus, &self.character, &self.light_cone);
+        let level = ability.level.of(&self.character);
+        let multiplier = ability.multiplier.at(level)?;
+        let dmg_multiplier = self.dmg_multiplier(ability, enemy, teammates)?;
+        let stat = |stat: &Stats| base_stats.get(stat).cloned().unwrap_or_default();
+        let multipliers = match &ability.scaling {
+            Scaling::Stat(scaling) | Scaling::StatAndFlat(scaling, _)
+                if ability.damage_type.is_sustain() =>
+            {
+                let flat = match &ability.scaling {
+                    Scaling::StatAndFlat(_, flat) => flat.at(level)?,
+                    _ => 0.0,
+                };
+                DamageMultipliers::for_sustain(
+                    multiplier * stat(scaling) + flat,
+                    &ability.damage_type,
+                    &base_stats,
+                    &bonus,
+                )
+            }
+            Scaling::Stat(scaling) => DamageMultipliers::new(
+                multiplier * stat(scaling) * dmg_multiplier,
+                match ability.damage_type {
+                    DamageType::DamageOnTime => CritEnum::NoCrit,
+                    _ => self.crit,
+                },
+                &base_stats,
+                &bonus,
+                enemy,
+                &self.character,
+            )?,
+            scaling => {
+                let base_dmg = match scaling {
+                    Scaling::SuperBreak(toughness) => {
+                        super_break_base_dmg(&self.character, *toughness)?
+                    }
+                    Scaling::BreakDot => break_dot_base_dmg(&self.character, enemy)?,
+                    _ => break_base_dmg(&self.character, enemy)?,
+                };
+                DamageMultipliers::for_break(
+                    multiplier * base_dmg * dmg_multiplier,
+                    &ability.damage_type,
+                    &base_stats,
+                    &bonus,
+                    enemy,
+                    &self.character,
+                )?
+            }
+        };
+        Ok(DamageHit {
+            name: ability.name.to_string(),
+            count,
+            skill_type: ability.skill_type.clone(),
+            damage_type: ability.damage_type.clone(),
+            multipliers,
+            stats: base_stats,
+            kit_bonus,
+        })
+    }
+
+    fn hits(
+        &self,
+        relics: &Relics,
+        encounter: &Encounter,
+        target: &str,
+        teammates: &[Box<dyn Support>],
+    ) -> Result<Vec<DamageHit>> {
+        let target = self
+            .kit
+            .targets
+            .iter()
+            .find(|t| t.name.eq_ignore_ascii_case(target))
+            .ok_or_else(|| {
+                eyre!(
+                    "Unknown evaluation target {target} for {}, expected one of {}",
+                    self.character.name,
+                    self.targets().join(", ")
+                )
+            })?;
+        let main_only;
+        let encounter = match target.single_target {
+            true => {
+                main_only = encounter.main_only();
+                &main_only
+            }
+            false => encounter,
+        };
+        let mut hits = vec![];
+        for hit in &target.hits {
+            let Some(ability) = self
+                .kit
+                .abilities
+                .iter()
+                .filter(|a| a.name == hit.ability)
+                .find(|a| a.unlock.holds(&self.character))
+            else {
+                continue;
+            };
+            let enemies = encounter.enemies.len();
+            let sustain = ability.damage_type.is_sustain();
+            let (targets, share) = match ability.targeting {
+                // Heals and shields land on an ally once, whatever the enemies.
+                _ if sustain => (vec![encounter.target], 1.0),
+                Targeting::MainTarget => (vec![encounter.target], 1.0),
+                Targeting::Adjacent => (encounter.adjacent_targets(), 1.0),
+                Targeting::All => ((0..enemies).collect(), 1.0),
+                Targeting::Bounce => ((0..enemies).collect(), 1.0 / enemies as f64),
+            };
+            for i in targets {
+                let enemy = &encounter.enemies[i];
+                if !self.satisfied(&hit.requires, enemy, teammates) {
+                    continue;
+                }
+                let count = hit.count * share * self.stack_count(&hit.stacks, enemy, teammates);
+                if count > 0.0 {
+                    let mut hit = self.hit(ability, count, relics, enemy, teammates)?;
+                    if enemies > 1 && !sustain {
+                        hit.name = format!("{} (enemy {})", hit.name, i + 1);
+                    }
+                    hits.push(hit);
+                }
+            }
+        }
+        Ok(hits)
+    }
+}
+
+impl Evaluator for KitEvaluator {
+    type Target = String;
+
+    fn evaluate(
+        &self,
+        relics: &Relics,
+        encounter: &Encounter,
+        target: &Self::Target,
+        teammates: &[Box<dyn Support>],
+    ) -> Result<f64> {
+        Ok(total_damage(
+            &self.hits(relics, encounter, target, teammates)?,
+        ))
+    }
+
+    fn targets(&self) -> Vec<String> {
+        self.kit
+            .targets
+            .iter()
+            .map(|t| t.name.to_string())
+            .collect()
+    }
+
+    fn explain(
+        &self,
+        relics: &Relics,
+        encounter: &Encounter,
+        target: &Self::Target,
+        teammates: &[Box<dyn Support>],
+    ) -> Result<Vec<DamageHit>> {
+        self.hits(relics, encounter, target, teammates)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        character::{acheron, dan_heng, dr_ratio, gepard, natasha},
+        domain::{AttackType, BaseStats, CharacterSkills, CharacterTraces, Path, RelicSetConfig},
+    };
+
+    fn dr_ratio_character() -> Character {
+        Character {
+            id: "1305".to_string(),
+            name: "Dr. Ratio".to_string(),
+            path: Path::TheHunt,
+            attack_type: AttackType::Imaginary,
+            level: 80,
+            ascension: 6,
+            eidolon: 0,
+            skills: CharacterSkills {
+                basic: 6,
+                skill: 10,
+                ult: 10,
+                talent: 10,
+            },
+            traces: CharacterTraces {
+                ability_1: true,
+                ability_2: true,
+                ability_3: true,
+                stat_1: false,
+                stat_2: false,
+                stat_3: false,
+                stat_4: false,
+                stat_5: false,
+                stat_6: false,
+                stat_7: false,
+                stat_8: false,
+                stat_9: false,
+                stat_10: false,
+            },
+            base_hp: 1048.0,
+            base_atk: 776.0,
+            base_def: 460.0,
+            base_spd: 103.0,
+            base_aggro: 75,
+            critical_chance: 5.0,
+            critical_damage: 50.0,
+            stat_bonus: BaseStats::default(),
+        }
+    }
+
+    #[test]
+    fn test_kits_are_consistent() -> Result<()> {
+        for kit in [
+            acheron::kit(),
+            dan_heng::kit(),
+            dr_ratio::kit(),
+            gepard::kit(),
+            natasha::kit(),
+        ] {
+            kit.check()?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_targeting() -> Result<()> {
+        let ability = |name, targeting| Ability {
+            name,
+            unlock: Unlock::Always,
+            skill_type: SkillType::Skill,
+            damage_type: DamageType::Normal,
+            targeting,
+            scaling: Scaling::Stat(Stats::Atk),
+            level: SkillLevel::Skill,
+            multiplier: ScalingTable::flat(1.0),
+        };
+        let kit = Kit {
+            abilities: vec![
+                ability("main", Targeting::MainTarget),
+                ability("blast", Targeting::Adjacent),
+                ability("bounce", Targeting::Bounce),
+            ],
+            targets: vec![KitTarget {
+                name: "Skill",
+                hits: vec![
+                    Hit::new("main", 1.0),
+                    Hit::new("blast", 1.0),
+                    Hit::new("bounce", 4.0),
+                ],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let evaluator = KitEvaluator::new(
+            kit,
+            dr_ratio_character(),
+            None,
+            CritEnum::Avg,
+            Value::Object(Default::default()),
+        )?;
+        let relics = Relics {
+            relics: vec![],
+            config: RelicSetConfig::default(),
+        };
+        let target = "Skill".to_string();
+        let single = evaluator.explain(&relics, &Encounter::default(), &target, &[])?;
+        assert_eq!(single.len(), 2);
+        assert_eq!(single[1].count, 4.0);
+
+        let encounter = Encounter::copies(Enemy::default(), 3);
+        let hits = evaluator.explain(&relics, &encounter, &target, &[])?;
+        let names: Vec<_> = hits.iter().map(|hit| hit.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "main (enemy 2)",
+                "blast (enemy 1)",
+                "blast (enemy 3)",
+                "bounce (enemy 1)",
+                "bounce (enemy 2)",
+                "bounce (enemy 3)",
+            ]
+        );
+        let bounces: f64 = hits[3..].iter().map(|hit| hit.count).sum();
+        assert!((bounces - 4.0).abs() < 1e-9);
+        Ok(())
+    }
+
+    #[test]
+    fn test_enemy_debuffs_override_the_option() -> Result<()> {
+        let evaluator = KitEvaluator::new(
+            dr_ratio::kit(),
+            dr_ratio_character(),
+            None,
+            CritEnum::Avg,
+            Value::Object(Default::default()),
+        )?;
+        let relics = Relics {
+            relics: vec![],
+            config: RelicSetConfig::default(),
+        };
+        let target = "Skill".to_string();
+        let against = |debuffs| {
+            let encounter = Encounter::single(Enemy {
+                debuffs,
+                ..Default::default()
+            });
+            evaluator.evaluate(&relics, &encounter, &target, &[])
+        };
+        assert_eq!(against(None)?, against(Some(3))?);
+        assert!(against(Some(0))? < against(Some(3))?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_deduction_applies_to_every_ability() -> Result<()> {
+        let evaluator = KitEvaluator::new(
+            dr_ratio::kit(),
+            dr_ratio_character(),
+            None,
+            CritEnum::Avg,
+            serde_json::json!({ "enemy_debuffs": 4 }),
+        )?;
+        let relics = Relics {
+            relics: vec![],
+            config: RelicSetConfig::default(),
+        };
+        for target in ["Ultimate", "FollowUp", "SkillWithFollowUp"] {
+            let hits =
+                evaluator.explain(&relics, &Encounter::default(), &target.to_string(), &[])?;
+            for hit in hits {
+                let deduction = hit
+                    .kit_bonus
+                    .iter()
+                    .find(|(source, _, _)| source == "Deduction")
+                    .map(|(_, _, amount)| *amount);
+                assert_eq!(deduction, Some(40.0), "{target} {}", hit.name);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_break_abilities() -> Result<()> {
+        let ability = |name, damage_type, scaling| Ability {
+            name,
+            unlock: Unlock::Always,
+            skill_type: SkillType::Skill,
+            damage_type,
+            targeting: Targeting::MainTarget,
+            scaling,
+            level: SkillLevel::Skill,
+            multiplier: ScalingTable::flat(1.0),
+        };
+        let mut kit = Kit {
+            abilities: vec![
+                ability("break", DamageType::BreakDamage, Scaling::Break),
+                ability(
+                    "super_break",
+                    DamageType::SuperBreakDamage,
+                    Scaling::SuperBreak(30.0),
+                ),
+                ability("dot", DamageType::DamageOnTime, Scaling::Stat(Stats::Atk)),
+            ],
+            targets: vec![KitTarget {
+                name: "Break",
+                hits: vec![
+                    Hit::new("break", 1.0),
+                    Hit::new("super_break", 1.0),
+                    Hit::new("dot", 1.0),
+                ],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let relics = Relics {
+            relics: vec![],
+            config: RelicSetConfig::default(),
+        };
+        let evaluator = KitEvaluator::new(
+            kit.clone(),
+            dr_ratio_character(),
+            None,
+            CritEnum::Crit,
+            Value::Object(Default::default()),
+        )?;
+        let encounter = Encounter::single(Enemy {
+            toughness: 300.0,
+            ..Default::default()
+        });
+        let hits = evaluator.explain(&relics, &encounter, &"Break".to_string(), &[])?;
+        // Imaginary break: half the level multiplier, times 3 for 300 toughness.
+        assert!((hits[0].multipliers.base_dmg - 3767.5533 * 1.5).abs() < 1e-6);
+        assert!((hits[1].multipliers.base_dmg - 3767.5533).abs() < 1e-6);
+        assert!(hits.iter().all(|hit| hit.multipliers.crit == 1.0));
+
+        kit.abilities[0].scaling = Scaling::Stat(Stats::Atk);
+        assert!(kit.check().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_dan_heng_break_targets() -> Result<()> {
+        let relics = Relics {
+            relics: vec![],
+            config: RelicSetConfig::default(),
+        };
+        let dan_heng = |options| {
+            KitEvaluator::new(
+                dan_heng::kit(),
+                Character {
+                    id: "1002".to_string(),
+                    name: "Dan Heng".to_string(),
+                    attack_type: AttackType::Wind,
+                    ..dr_ratio_character()
+                },
+                None,
+                CritEnum::Avg,
+                options,
+            )
+        };
+        let evaluator = dan_heng(Value::Object(Default::default()))?;
+        let wind_shear = |elite| -> Result<f64> {
+            let encounter = Encounter::single(Enemy {
+                elite,
+                toughness: 300.0,
+                ..Default::default()
+            });
+            let hits = evaluator.explain(&relics, &encounter, &"Break".to_string(), &[])?;
+            assert_eq!(hits[0].damage_type, DamageType::BreakDamage);
+            Ok(hits[1..].iter().map(|hit| hit.count).sum())
+        };
+        assert_eq!(wind_shear(false)?, 1.0);
+        assert_eq!(wind_shear(true)?, 3.0);
+
+        let evaluator = dan_heng(serde_json::json!({ "super_break": 1.6 }))?;
+        let skill = |toughness_break| {
+            let encounter = Encounter::single(Enemy {
+                toughness_break,
+                ..Default::default()
+            });
+            evaluator.explain(&relics, &encounter, &"Skill".to_string(), &[])
+        };
+        assert_eq!(skill(false)?.len(), 1);
+        let hits = skill(true)?;
+        assert_eq!(hits[1].count, 1.6);
+        // Twice the level multiplier for the 60 toughness of the skill.
+        assert!((hits[1].multipliers.base_dmg - 3767.5533 * 2.0).abs() < 1e-6);
+        Ok(())
+    }
+
+    #[test]
+    fn test_heal_and_shield_targets() -> Result<()> {
+        let relics = Relics {
+            relics: vec![],
+            config: RelicSetConfig::default(),
+        };
+        let encounter = Encounter::copies(Enemy::default(), 3);
+        let natasha = |options| {
+            KitEvaluator::new(
+                natasha::kit(),
+                Character {
+                    id: "1105".to_string(),
+                    name: "Natasha".to_string(),
+                    path: Path::Abundance,
+                    attack_type: AttackType::Physical,
+                    ..dr_ratio_character()
+                },
+                None,
+                CritEnum::Crit,
+                options,
+            )
+        };
+        let target = "Skill".to_string();
+        let hits = natasha(Value::Object(Default::default()))?.explain(
+            &relics,
+            &encounter,
+            &target,
+            &[],
+        )?;
+        assert_eq!(hits.len(), 3);
+        assert!(hits.iter().all(|hit| hit.multipliers.crit == 1.0));
+        // Healer adds 10% outgoing healing, Recuperation a third turn of heal over time.
+        let skill = (0.105 * 1048.0 + 280.0) + 3.0 * (0.072 * 1048.0 + 192.0);
+        assert!((total_damage(&hits) - skill * 1.1).abs() < 1e-6);
+        let low_hp = natasha(serde_json::json!({ "ally_low_hp": true }))?.evaluate(
+            &relics,
+            &encounter,
+            &target,
+            &[],
+        )?;
+        assert!((low_hp - skill * 1.6).abs() < 1e-6);
+
+        let gepard = KitEvaluator::new(
+            gepard::kit(),
+            Character {
+                id: "1104".to_string(),
+                name: "Gepard".to_string(),
+                path: Path::Preservation,
+                attack_type: AttackType::Ice,
+                ..dr_ratio_character()
+            },
+            None,
+            CritEnum::Avg,
+            Value::Object(Default::default()),
+        )?;
+        let shield = gepard.evaluate(&relics, &encounter, &"Shield".to_string(), &[])?;
+        assert!((shield - (0.45 * 460.0 + 600.0)).abs() < 1e-6);
+        Ok(())
+    }
+
+    #[test]
+    fn test_standard_curves() -> Result<()> {
+        // Acheron's Crimson Knot and skill main target.
+        let ultimate = ScalingTable::ultimate(0.15);
+        assert!((ultimate.at(1)? - 0.09).abs() < 1e-9);
+        assert!((ultimate.at(7)? - 0.1275).abs() < 1e-9);
+        let skill = ScalingTable::skill(1.6);
+        assert!((skill.at(8)? - 1.4).abs() < 1e-9);
+        assert!((skill.at(15)? - 2.0).abs() < 1e-9);
+        assert!(skill.at(0).is_err());
+        assert_eq!(ScalingTable::flat(0.25).at(9)?, 0.25);
+        let between = ScalingTable::between(0.9, 1.5);
+        for level in 1..=15 {
+            assert!((between.at(level)? - ultimate.at(level)? * 10.0).abs() < 1e-9);
+        }
+        Ok(())
+    }
+}
@@ -1,8 +1,22 @@
-use crate::domain::{Enemy, Path, Relics};
-use eyre::Result;
+use crate::{
+    domain::{
+        Character, CritEnum, DamageType, Encounter, LightConeEntity, Path, Relics, SkillType, Stats,
+    },
+    utils::calculator::DamageMultipliers,
+};
+use eyre::{bail, Result};
+use std::{collections::HashMap, sync::Arc};
 
 mod acheron;
-pub use acheron::{Acheron, AcheronEvaluationTarget};
+mod dan_heng;
+mod dr_ratio;
+mod gepard;
+mod kit;
+mod natasha;
+mod supports;
+pub use kit::ScalingTable;
+use kit::{Kit, KitEvaluator};
+pub use supports::{CustomSupport, ScannedSupport, SupportConfig, SupportProfile};
 
 pub trait Evaluator {
     type Target;
@@ -10,12 +24,134 @@ pub trait Evaluator {
     fn evaluate(
         &self,
         relics: &Relics,
-        enemy: &Enemy,
+        encounter: &Encounter,
         target: &Self::Target,
         teammates: &[Box<dyn Support>],
     ) -> Result<f64>;
+
+    /// Names of the evaluation targets supported by the character.
+    fn targets(&self) -> Vec<String>;
+
+    /// The hits `evaluate` adds up, with every multiplier of their damage.
+    fn explain(
+        &self,
+        _relics: &Relics,
+        _encounter: &Encounter,
+        _target: &Self::Target,
+        _teammates: &[Box<dyn Support>],
+    ) -> Result<Vec<DamageHit>> {
+        bail!("No damage breakdown is available for this evaluator")
+    }
+}
+
+/// A damage instance of an evaluation target.
+#[derive(Clone, Debug)]
+pub struct DamageHit {
+    pub name: String,
+    /// Times the hit lands in the target.
+    pub count: f64,
+    pub skill_type: SkillType,
+    pub damage_type: DamageType,
+    pub multipliers: DamageMultipliers,
+    /// Stat sheet of the character during the hit.
+    pub stats: HashMap<Stats, f64>,
+    /// Bonus from the character's own kit, as `(source, stat, bonus)`.
+    pub kit_bonus: Vec<(String, Stats, f64)>,
+}
+
+impl DamageHit {
+    pub fn damage(&self) -> f64 {
+        self.multipliers.damage()
+    }
+}
+
+/// Damage of the hits as `evaluate` adds them up.
+pub fn total_damage(hits: &[DamageHit]) -> f64 {
+    hits.iter().map(|hit| hit.damage() * hit.count).sum()
 }
 
 pub trait Support: Sync + Send {
     fn get_path(&self) -> Path;
+
+    /// Name shown in reports, the path when nothing more is known.
+    fn name(&self) -> String {
+        format!("{:?}", self.get_path())
+    }
+
+    /// Stats granted to the carry, e.g. ATK%, CRIT DMG or DMG boost.
+    fn buffs(&self, _carry: &Carry) -> Result<HashMap<Stats, f64>> {
+        Ok(HashMap::new())
+    }
+
+    /// Debuffs applied to the enemy, as `DefReduction_`, `ResPenentration_` and
+    /// `Vulnerebility_`.
+    fn debuffs(&self, _carry: &Carry) -> Result<HashMap<Stats, f64>> {
+        Ok(HashMap::new())
+    }
+
+    /// Buffs and debuffs as `(source, stat, bonus)`, e.g. `Bronya (The Belobog March)`.
+    fn sources(&self, carry: &Carry) -> Result<Vec<(String, Stats, f64)>> {
+        Ok(self
+            .buffs(carry)?
+            .into_iter()
+            .chain(self.debuffs(carry)?)
+            .map(|(stat, value)| (self.name(), stat, value))
+            .collect())
+    }
+}
+
+/// The character being evaluated, as seen by its supports.
+pub struct Carry<'a> {
+    pub character: &'a Character,
+    pub light_cone: &'a Option<LightConeEntity>,
+    pub skill_type: &'a SkillType,
+}
+
+/// Evaluator whose targets are addressed by name, so the character can be picked at runtime.
+pub type NamedEvaluator = Arc<dyn Evaluator<Target = String> + Sync + Send>;
+
+/// A teammate only known by its path.
+pub struct Teammate {
+    pub path: Path,
+}
+
+impl Support for Teammate {
+    fn get_path(&self) -> Path {
+        self.path.clone()
+    }
+}
+
+/// Builds the evaluator of a character, `options` holding the character specific battle state.
+pub fn evaluator_for(
+    character: &Character,
+    light_cone: Option<LightConeEntity>,
+    crit: CritEnum,
+    options: serde_json::Value,
+) -> Result<NamedEvaluator> {
+    match kit_for(character.id.as_str()) {
+        Some(kit) => Ok(Arc::new(KitEvaluator::new(
+            kit,
+            character.clone(),
+            light_cone,
+            crit,
+            options,
+        )?)),
+        None => bail!(
+            "No evaluator is implemented for character {} ({})",
+            character.id,
+            character.name
+        ),
+    }
+}
+
+/// Characters described by a declarative kit.
+fn kit_for(character_id: &str) -> Option<Kit> {
+    match character_id {
+        "1002" => Some(dan_heng::kit()),
+        "1104" => Some(gepard::kit()),
+        "1105" => Some(natasha::kit()),
+        "1305" => Some(dr_ratio::kit()),
+        "1308" => Some(acheron::kit()),
+        _ => None,
+    }
 }
@@ -0,0 +1,113 @@
+use super::kit::{
+    Ability, Amount, Hit, Kit, KitOption, KitTarget, Modifier, Requirement, Scaling, ScalingTable,
+    SkillLevel, Targeting, Unlock,
+};
+use crate::domain::{DamageType, SkillType, Stats};
+
+/// Natasha (1105). Heals are those of a single ally, the ultimate restoring the same amount to
+/// every ally. The skill target adds up the heal over time of its 2 turns, extended by one with
+/// Recuperation.
+///
+/// Options:
+/// - `ally_low_hp`: the healed ally is at or below 30% HP, for her talent (default off).
+pub fn kit() -> Kit {
+    Kit {
+        options: vec![KitOption {
+            name: "ally_low_hp",
+            default: 0.0,
+        }],
+        abilities: vec![
+            Ability {
+                name: "basic",
+                unlock: Unlock::Always,
+                skill_type: SkillType::BasicAttack,
+                damage_type: DamageType::Normal,
+                targeting: Targeting::MainTarget,
+                scaling: Scaling::Stat(Stats::Atk),
+                level: SkillLevel::Basic,
+                multiplier: ScalingTable::basic(1.0),
+            },
+            Ability {
+                name: "skill_heal",
+                unlock: Unlock::Always,
+                skill_type: SkillType::Skill,
+                damage_type: DamageType::Heal,
+                targeting: Targeting::MainTarget,
+                scaling: Scaling::StatAndFlat(Stats::Hp, ScalingTable::between(70.0, 280.0)),
+                level: SkillLevel::Skill,
+                multiplier: ScalingTable::between(0.07, 0.105),
+            },
+            Ability {
+                name: "skill_heal_over_time",
+                unlock: Unlock::Always,
+                skill_type: SkillType::Skill,
+                damage_type: DamageType::Heal,
+                targeting: Targeting::MainTarget,
+                scaling: Scaling::StatAndFlat(Stats::Hp, ScalingTable::between(48.0, 192.0)),
+                level: SkillLevel::Skill,
+                multiplier: ScalingTable::between(0.048, 0.072),
+            },
+            Ability {
+                name: "recuperation",
+                unlock: Unlock::Trace(3),
+                skill_type: SkillType::Skill,
+                damage_type: DamageType::Heal,
+                targeting: Targeting::MainTarget,
+                scaling: Scaling::StatAndFlat(Stats::Hp, ScalingTable::between(48.0, 192.0)),
+                level: SkillLevel::Skill,
+                multiplier: ScalingTable::between(0.048, 0.072),
+            },
+            Ability {
+                name: "ultimate_heal",
+                unlock: Unlock::Always,
+                skill_type: SkillType::Ultimate,
+                damage_type: DamageType::Heal,
+                targeting: Targeting::MainTarget,
+                scaling: Scaling::StatAndFlat(Stats::Hp, ScalingTable::between(92.0, 368.0)),
+                level: SkillLevel::Ultimate,
+                multiplier: ScalingTable::between(0.092, 0.138),
+            },
+        ],
+        modifiers: vec![
+            Modifier {
+                name: "Innervation",
+                stat: Stats::OutgoingHealingBoost_,
+                amount: Amount::Scaling(SkillLevel::Talent, ScalingTable::between(25.0, 50.0)),
+                requires: vec![Requirement {
+                    option: "ally_low_hp",
+                    min: 1.0,
+                }],
+                ..Default::default()
+            },
+            Modifier {
+                name: "Healer",
+                unlock: Unlock::Trace(2),
+                stat: Stats::OutgoingHealingBoost_,
+                amount: Amount::Flat(10.0),
+                ..Default::default()
+            },
+        ],
+        targets: vec![
+            KitTarget {
+                name: "Basic",
+                hits: vec![Hit::new("basic", 1.0)],
+                ..Default::default()
+            },
+            KitTarget {
+                name: "Skill",
+                hits: vec![
+                    Hit::new("skill_heal", 1.0),
+                    Hit::new("skill_heal_over_time", 2.0),
+                    Hit::new("recuperation", 1.0),
+                ],
+                ..Default::default()
+            },
+            KitTarget {
+                name: "Ultimate",
+                hits: vec![Hit::new("ultimate_heal", 1.0)],
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    }
+}
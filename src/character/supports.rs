@@ -0,0 +1,745 @@
+use super::{
+    kit::{Amount, Modifier, Requirement, ScalingTable, SkillLevel, Stacks, Unlock},
+    Carry, Support,
+};
+use crate::{
+    domain::{Character, LightConeEntity, Path, Relics, SkillType, Stats},
+    utils::calculator::{bonus_before_battle, calculate_stats},
+};
+use eyre::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Contributions applied to the enemy rather than to the carry.
+const DEBUFFS: [Stats; 3] = [
+    Stats::DefReduction_,
+    Stats::ResPenentration_,
+    Stats::Vulnerebility_,
+];
+
+/// Uptime of the buffs of a support, everything is up by default.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct SupportConfig {
+    /// The buff of the support's skill is on the carry.
+    pub skill: bool,
+    /// The support's ultimate is active.
+    pub ultimate: bool,
+    /// Stacks of the support's talent, e.g. Ashen Roast for Jiaoqiu. The maximum when unset.
+    pub talent_stacks: Option<u8>,
+}
+
+impl Default for SupportConfig {
+    fn default() -> Self {
+        Self {
+            skill: true,
+            ultimate: true,
+            talent_stacks: None,
+        }
+    }
+}
+
+/// A teammate read from the scan, with the stats of its own build.
+#[derive(Clone, Debug)]
+pub struct SupportProfile {
+    pub character: Character,
+    pub light_cone: Option<LightConeEntity>,
+    /// Stats outside of battle, from its relics, traces and light cone.
+    pub stats: HashMap<Stats, f64>,
+}
+
+impl SupportProfile {
+    pub fn new(
+        character: Character,
+        light_cone: Option<LightConeEntity>,
+        relics: &Relics,
+    ) -> Result<Self> {
+        let bonus = bonus_before_battle(&character, &light_cone, relics, &character.attack_type)?;
+        let stats = calculate_stats(&bonus, &character, &light_cone);
+        Ok(Self {
+            character,
+            light_cone,
+            stats,
+        })
+    }
+
+    /// Whether the buffs of the character's own kit are known, not only its light cone.
+    pub fn has_kit(&self) -> bool {
+        kit_for(&self.character.id).is_some()
+    }
+}
+
+/// A support character from the scan. Its kit contributions depend on its skill levels,
+/// traces, eidolons and own stats, on top of the team buffs of its light cone.
+pub struct ScannedSupport {
+    pub profile: SupportProfile,
+    pub config: SupportConfig,
+    modifiers: Vec<Modifier>,
+}
+
+impl ScannedSupport {
+    pub fn new(profile: SupportProfile, config: SupportConfig) -> Self {
+        let modifiers = kit_for(&profile.character.id).unwrap_or_default();
+        Self {
+            profile,
+            config,
+            modifiers,
+        }
+    }
+
+    /// Value of an option read by the modifiers of the kit:
+    /// - `skill` and `ultimate`: uptime of the buffs, from the config.
+    /// - `talent_stacks`: stacks of the talent from the config, unbounded when unset.
+    /// - `break_effect`: break effect of the support.
+    /// - `carry_<element>`: the carry is of the element, e.g. `carry_ice`.
+    fn option(&self, name: &str, carry: &Carry) -> Result<f64> {
+        let flag = |on: bool| on as u8 as f64;
+        Ok(match name {
+            "skill" => flag(self.config.skill),
+            "ultimate" => flag(self.config.ultimate),
+            "talent_stacks" => self
+                .config
+                .talent_stacks
+                .map_or(f64::INFINITY, |stacks| stacks as f64),
+            "break_effect" => self
+                .profile
+                .stats
+                .get(&Stats::BreakEffect_)
+                .cloned()
+                .unwrap_or_default(),
+            _ => match name.strip_prefix("carry_") {
+                Some(element) => {
+                    flag(format!("{:?}", carry.character.attack_type).eq_ignore_ascii_case(element))
+                }
+                None => bail!("Unknown option {name} in the kit of a support"),
+            },
+        })
+    }
+
+    /// Kit and light cone contributions as `(source, stat, bonus)`.
+    fn contributions(&self, carry: &Carry) -> Result<Vec<(String, Stats, f64)>> {
+        let character = &self.profile.character;
+        let carry_base_atk = carry.character.base_atk
+            + carry
+                .light_cone
+                .as_ref()
+                .map(|lc| lc.base_atk)
+                .unwrap_or_default();
+        let mut sources = vec![];
+        for modifier in &self.modifiers {
+            if !modifier.unlock.holds(character) || !modifier.applies_to(carry.skill_type) {
+                continue;
+            }
+            let mut satisfied = true;
+            for requirement in &modifier.requires {
+                satisfied &= self.option(requirement.option, carry)? >= requirement.min;
+            }
+            if !satisfied {
+                continue;
+            }
+            let stacks = match &modifier.stacks {
+                Some(stacks) => stacks.count(self.option(stacks.option, carry)?),
+                None => 1.0,
+            };
+            let value = modifier.value(character, &self.profile.stats, carry_base_atk, stacks)?;
+            sources.push((modifier.name.to_string(), modifier.stat.clone(), value));
+        }
+        if let Some(light_cone) = &self.profile.light_cone {
+            let team_buffs =
+                light_cone.get_team_buffs(&character.attack_type, &carry.character.attack_type);
+            for (stat, value) in team_buffs {
+                sources.push((light_cone._light_cone.name.clone(), stat, value));
+            }
+        }
+        sources.retain(|(_, _, value)| *value != 0.0);
+        Ok(sources)
+    }
+}
+
+impl Support for ScannedSupport {
+    fn get_path(&self) -> Path {
+        self.profile.character.path.clone()
+    }
+
+    fn name(&self) -> String {
+        self.profile.character.name.clone()
+    }
+
+    fn buffs(&self, carry: &Carry) -> Result<HashMap<Stats, f64>> {
+        Ok(split(total(self.contributions(carry)?), false))
+    }
+
+    fn debuffs(&self, carry: &Carry) -> Result<HashMap<Stats, f64>> {
+        Ok(split(total(self.contributions(carry)?), true))
+    }
+
+    fn sources(&self, carry: &Carry) -> Result<Vec<(String, Stats, f64)>> {
+        Ok(self
+            .contributions(carry)?
+            .into_iter()
+            .map(|(source, stat, value)| (format!("{} ({source})", self.name()), stat, value))
+            .collect())
+    }
+}
+
+/// A teammate whose contributions are given by hand, for characters without a profile.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct CustomSupport {
+    pub path: Path,
+    /// Buffs and debuffs, e.g. `ATK_: 48` or `DefReduction_: 40`.
+    #[serde(default)]
+    pub buffs: HashMap<Stats, f64>,
+}
+
+impl Support for CustomSupport {
+    fn get_path(&self) -> Path {
+        self.path.clone()
+    }
+
+    fn buffs(&self, _carry: &Carry) -> Result<HashMap<Stats, f64>> {
+        Ok(split(self.buffs.clone(), false))
+    }
+
+    fn debuffs(&self, _carry: &Carry) -> Result<HashMap<Stats, f64>> {
+        Ok(split(self.buffs.clone(), true))
+    }
+}
+
+/// Buffs and debuffs of the supports whose kit is known, enemy debuffs being told apart by their
+/// stat.
+fn kit_for(character_id: &str) -> Option<Vec<Modifier>> {
+    match character_id {
+        "1101" => Some(bronya()),
+        "1106" => Some(pela()),
+        "1202" => Some(tingyun()),
+        "1218" => Some(jiaoqiu()),
+        "1303" => Some(ruan_mei()),
+        "1306" => Some(sparkle()),
+        "1309" => Some(robin()),
+        _ => None,
+    }
+}
+
+const SKILL: Requirement = Requirement {
+    option: "skill",
+    min: 1.0,
+};
+const ULTIMATE: Requirement = Requirement {
+    option: "ultimate",
+    min: 1.0,
+};
+
+/// Bronya (1101).
+fn bronya() -> Vec<Modifier> {
+    vec![
+        Modifier {
+            name: "Combat Redeployment",
+            stat: Stats::DmgBoost_,
+            amount: Amount::Scaling(SkillLevel::Skill, ScalingTable::skill(66.0)),
+            requires: vec![SKILL],
+            ..Default::default()
+        },
+        Modifier {
+            name: "The Belobog March",
+            stat: Stats::Atk_,
+            amount: Amount::Scaling(SkillLevel::Ultimate, ScalingTable::ultimate(55.0)),
+            requires: vec![ULTIMATE],
+            ..Default::default()
+        },
+        Modifier {
+            name: "The Belobog March",
+            stat: Stats::CritDmg_,
+            amount: Amount::StatShare(
+                Stats::CritDmg_,
+                SkillLevel::Ultimate,
+                ScalingTable::between(12.0, 16.0),
+            ),
+            requires: vec![ULTIMATE],
+            ..Default::default()
+        },
+        Modifier {
+            name: "The Belobog March",
+            stat: Stats::CritDmg_,
+            amount: Amount::Scaling(SkillLevel::Ultimate, ScalingTable::ultimate(20.0)),
+            requires: vec![ULTIMATE],
+            ..Default::default()
+        },
+        Modifier {
+            name: "Army",
+            unlock: Unlock::Trace(3),
+            stat: Stats::DmgBoost_,
+            amount: Amount::Flat(10.0),
+            ..Default::default()
+        },
+    ]
+}
+
+/// Pela (1106).
+fn pela() -> Vec<Modifier> {
+    vec![
+        Modifier {
+            name: "Zone Suppression",
+            stat: Stats::DefReduction_,
+            amount: Amount::Scaling(SkillLevel::Ultimate, ScalingTable::between(30.0, 40.0)),
+            requires: vec![ULTIMATE],
+            ..Default::default()
+        },
+        Modifier {
+            name: "Full Analysis",
+            unlock: Unlock::Eidolon(4),
+            stat: Stats::ResPenentration_,
+            amount: Amount::Flat(12.0),
+            requires: vec![
+                SKILL,
+                Requirement {
+                    option: "carry_ice",
+                    min: 1.0,
+                },
+            ],
+            ..Default::default()
+        },
+    ]
+}
+
+/// Tingyun (1202).
+fn tingyun() -> Vec<Modifier> {
+    vec![
+        Modifier {
+            name: "Soothing Melody",
+            stat: Stats::Atk,
+            amount: Amount::BaseAtkShare(SkillLevel::Skill, ScalingTable::skill(50.0)),
+            requires: vec![SKILL],
+            // Up to a share of Tingyun's own ATK.
+            cap: Some(Amount::StatShare(
+                Stats::Atk,
+                SkillLevel::Skill,
+                ScalingTable(vec![
+                    15.0, 16.0, 17.0, 18.0, 19.0, 20.0, 21.25, 22.5, 23.75, 25.0, 26.0, 27.0, 28.0,
+                    29.0, 30.0,
+                ]),
+            )),
+            ..Default::default()
+        },
+        Modifier {
+            name: "Amidst the Rejoicing Clouds",
+            stat: Stats::DmgBoost_,
+            amount: Amount::Scaling(SkillLevel::Ultimate, ScalingTable::ultimate(50.0)),
+            requires: vec![ULTIMATE],
+            ..Default::default()
+        },
+    ]
+}
+
+/// Jiaoqiu (1218). Ashen Roast stacks up to 5, or 9 from eidolon 6.
+fn jiaoqiu() -> Vec<Modifier> {
+    const ASHEN_ROAST: Requirement = Requirement {
+        option: "talent_stacks",
+        min: 1.0,
+    };
+    let stacks = |max| Stacks {
+        option: "talent_stacks",
+        offset: 0.0,
+        max,
+    };
+    // Every stack past the first.
+    let extra_stacks = |unlock, max| Modifier {
+        name: "Quartet Finesse, Octave Finery",
+        unlock,
+        stat: Stats::Vulnerebility_,
+        amount: Amount::Scaling(SkillLevel::Talent, ScalingTable::skill(5.0)),
+        stacks: Some(Stacks {
+            offset: 1.0,
+            ..stacks(max - 1.0)
+        }),
+        ..Default::default()
+    };
+    vec![
+        Modifier {
+            name: "Quartet Finesse, Octave Finery",
+            stat: Stats::Vulnerebility_,
+            amount: Amount::Scaling(SkillLevel::Talent, ScalingTable::skill(15.0)),
+            requires: vec![ASHEN_ROAST],
+            ..Default::default()
+        },
+        extra_stacks(Unlock::BeforeEidolon(6), 5.0),
+        extra_stacks(Unlock::Eidolon(6), 9.0),
+        Modifier {
+            name: "Pentapathic Transference",
+            unlock: Unlock::Eidolon(1),
+            stat: Stats::DmgBoost_,
+            amount: Amount::Flat(40.0),
+            requires: vec![ASHEN_ROAST],
+            ..Default::default()
+        },
+        Modifier {
+            name: "Nonamorphic Pyrobind",
+            unlock: Unlock::Eidolon(6),
+            stat: Stats::ResPenentration_,
+            amount: Amount::Flat(3.0),
+            stacks: Some(stacks(9.0)),
+            ..Default::default()
+        },
+        Modifier {
+            name: "Pyrograph Arbitrator",
+            stat: Stats::Vulnerebility_,
+            amount: Amount::Scaling(SkillLevel::Ultimate, ScalingTable::ultimate(15.0)),
+            skill_types: vec![SkillType::Ultimate],
+            requires: vec![ULTIMATE],
+            ..Default::default()
+        },
+    ]
+}
+
+/// Ruan Mei (1303).
+fn ruan_mei() -> Vec<Modifier> {
+    vec![
+        Modifier {
+            name: "Inert Respiration",
+            unlock: Unlock::Trace(1),
+            stat: Stats::BreakEffect_,
+            amount: Amount::Flat(20.0),
+            ..Default::default()
+        },
+        Modifier {
+            name: "String Sings Slow Swirls",
+            stat: Stats::DmgBoost_,
+            amount: Amount::Scaling(SkillLevel::Skill, ScalingTable::skill(32.0)),
+            requires: vec![SKILL],
+            ..Default::default()
+        },
+        // 6% per 10% of break effect past 120%, up to 36%.
+        Modifier {
+            name: "Candle Lights on Face",
+            unlock: Unlock::Trace(3),
+            stat: Stats::DmgBoost_,
+            amount: Amount::Flat(0.6),
+            requires: vec![SKILL],
+            stacks: Some(Stacks {
+                option: "break_effect",
+                offset: 120.0,
+                max: 60.0,
+            }),
+            ..Default::default()
+        },
+        Modifier {
+            name: "Petals to Stream, Repose in Dream",
+            stat: Stats::ResPenentration_,
+            amount: Amount::Scaling(SkillLevel::Ultimate, ScalingTable::ultimate(25.0)),
+            requires: vec![ULTIMATE],
+            ..Default::default()
+        },
+        Modifier {
+            name: "Neuronic Embroidery",
+            unlock: Unlock::Eidolon(1),
+            stat: Stats::DefIgnore_,
+            amount: Amount::Flat(20.0),
+            requires: vec![ULTIMATE],
+            ..Default::default()
+        },
+    ]
+}
+
+/// Sparkle (1306).
+fn sparkle() -> Vec<Modifier> {
+    let red_herring = Stacks {
+        option: "talent_stacks",
+        offset: 0.0,
+        max: 3.0,
+    };
+    vec![
+        Modifier {
+            name: "Dreamdiver",
+            stat: Stats::CritDmg_,
+            amount: Amount::StatShare(
+                Stats::CritDmg_,
+                SkillLevel::Skill,
+                ScalingTable::skill(24.0),
+            ),
+            requires: vec![SKILL],
+            ..Default::default()
+        },
+        Modifier {
+            name: "Dreamdiver",
+            stat: Stats::CritDmg_,
+            amount: Amount::Scaling(
+                SkillLevel::Skill,
+                ScalingTable(vec![
+                    27.0, 28.8, 30.6, 32.4, 34.2, 36.0, 38.25, 40.5, 42.75, 45.0, 46.8, 48.6, 50.4,
+                    52.2, 54.0,
+                ]),
+            ),
+            requires: vec![SKILL],
+            ..Default::default()
+        },
+        Modifier {
+            name: "Parallax Truth",
+            unlock: Unlock::Eidolon(6),
+            stat: Stats::CritDmg_,
+            amount: Amount::StatShare(Stats::CritDmg_, SkillLevel::Skill, ScalingTable::flat(30.0)),
+            requires: vec![SKILL],
+            ..Default::default()
+        },
+        Modifier {
+            name: "Red Herring",
+            stat: Stats::DmgBoost_,
+            amount: Amount::Scaling(SkillLevel::Talent, ScalingTable::skill(6.0)),
+            stacks: Some(red_herring.clone()),
+            ..Default::default()
+        },
+        Modifier {
+            name: "The Hero with a Thousand Faces",
+            stat: Stats::DmgBoost_,
+            amount: Amount::Scaling(SkillLevel::Ultimate, ScalingTable::ultimate(10.0)),
+            requires: vec![ULTIMATE],
+            stacks: Some(red_herring.clone()),
+            ..Default::default()
+        },
+        Modifier {
+            name: "Suspension of Disbelief",
+            unlock: Unlock::Eidolon(1),
+            stat: Stats::Atk_,
+            amount: Amount::Flat(40.0),
+            requires: vec![ULTIMATE],
+            ..Default::default()
+        },
+        Modifier {
+            name: "Purely Fictitious",
+            unlock: Unlock::Eidolon(2),
+            stat: Stats::DefIgnore_,
+            amount: Amount::Flat(8.0),
+            stacks: Some(red_herring),
+            ..Default::default()
+        },
+        Modifier {
+            name: "Nocturne",
+            unlock: Unlock::Trace(3),
+            stat: Stats::Atk_,
+            amount: Amount::Flat(15.0),
+            ..Default::default()
+        },
+        // Sparkle and a Quantum carry make two Quantum allies.
+        Modifier {
+            name: "Nocturne",
+            unlock: Unlock::Trace(3),
+            stat: Stats::Atk_,
+            amount: Amount::Flat(15.0),
+            requires: vec![Requirement {
+                option: "carry_quantum",
+                min: 1.0,
+            }],
+            ..Default::default()
+        },
+    ]
+}
+
+/// Robin (1309).
+fn robin() -> Vec<Modifier> {
+    vec![
+        Modifier {
+            name: "Pinion's Aria",
+            stat: Stats::DmgBoost_,
+            amount: Amount::Scaling(SkillLevel::Skill, ScalingTable::skill(50.0)),
+            requires: vec![SKILL],
+            ..Default::default()
+        },
+        Modifier {
+            name: "Vox Harmonique, Opus Cosmique",
+            stat: Stats::Atk,
+            amount: Amount::StatShare(
+                Stats::Atk,
+                SkillLevel::Ultimate,
+                ScalingTable::between(15.2, 22.8),
+            ),
+            requires: vec![ULTIMATE],
+            ..Default::default()
+        },
+        Modifier {
+            name: "Vox Harmonique, Opus Cosmique",
+            stat: Stats::Atk,
+            amount: Amount::Scaling(SkillLevel::Ultimate, ScalingTable::between(50.0, 200.0)),
+            requires: vec![ULTIMATE],
+            ..Default::default()
+        },
+        Modifier {
+            name: "Impromptu Flourish",
+            unlock: Unlock::Trace(3),
+            stat: Stats::CritDmg_,
+            amount: Amount::Flat(25.0),
+            skill_types: vec![SkillType::FollowUpAttack],
+            requires: vec![ULTIMATE],
+            ..Default::default()
+        },
+        Modifier {
+            name: "Land of Smiles",
+            unlock: Unlock::Eidolon(1),
+            stat: Stats::ResPenentration_,
+            amount: Amount::Flat(24.0),
+            requires: vec![ULTIMATE],
+            ..Default::default()
+        },
+        Modifier {
+            name: "Tonal Resonance",
+            stat: Stats::CritDmg_,
+            amount: Amount::Scaling(SkillLevel::Talent, ScalingTable::skill(20.0)),
+            ..Default::default()
+        },
+    ]
+}
+
+/// Sums up contributions by stat.
+fn total(contributions: Vec<(String, Stats, f64)>) -> HashMap<Stats, f64> {
+    let mut bonus = HashMap::new();
+    for (_, stat, value) in contributions {
+        *bonus.entry(stat).or_default() += value;
+    }
+    bonus
+}
+
+/// Keeps either the enemy debuffs or the carry buffs of `contributions`.
+fn split(contributions: HashMap<Stats, f64>, debuffs: bool) -> HashMap<Stats, f64> {
+    contributions
+        .into_iter()
+        .filter(|(stat, _)| DEBUFFS.contains(stat) == debuffs)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{AttackType, BaseStats, CharacterSkills, CharacterTraces};
+
+    fn character(id: &str, path: Path, attack_type: AttackType, eidolon: u8) -> Character {
+        Character {
+            id: id.to_string(),
+            name: id.to_string(),
+            path,
+            attack_type,
+            level: 80,
+            ascension: 6,
+            eidolon,
+            skills: CharacterSkills {
+                basic: 6,
+                skill: 10,
+                ult: 10,
+                talent: 10,
+            },
+            traces: CharacterTraces {
+                ability_1: true,
+                ability_2: true,
+                ability_3: true,
+                stat_1: true,
+                stat_2: true,
+                stat_3: true,
+                stat_4: true,
+                stat_5: true,
+                stat_6: true,
+                stat_7: true,
+                stat_8: true,
+                stat_9: true,
+                stat_10: true,
+            },
+            base_hp: 1000.0,
+            base_atk: 600.0,
+            base_def: 500.0,
+            base_spd: 100.0,
+            base_aggro: 100,
+            critical_chance: 5.0,
+            critical_damage: 50.0,
+            stat_bonus: BaseStats::default(),
+        }
+    }
+
+    fn support(character: Character, stats: HashMap<Stats, f64>) -> ScannedSupport {
+        ScannedSupport::new(
+            SupportProfile {
+                character,
+                light_cone: None,
+                stats,
+            },
+            SupportConfig::default(),
+        )
+    }
+
+    #[test]
+    fn test_support_contributions() -> Result<()> {
+        let carry = character("1308", Path::Nihility, AttackType::Lightning, 0);
+        let carry = Carry {
+            character: &carry,
+            light_cone: &None,
+            skill_type: &SkillType::Ultimate,
+        };
+
+        let pela = support(
+            character("1106", Path::Nihility, AttackType::Ice, 0),
+            HashMap::new(),
+        );
+        assert_eq!(
+            pela.debuffs(&carry)?,
+            HashMap::from([(Stats::DefReduction_, 40.0)])
+        );
+        assert!(pela.buffs(&carry)?.is_empty());
+
+        let sparkle = support(
+            character("1306", Path::Harmony, AttackType::Quantum, 0),
+            HashMap::from([(Stats::CritDmg_, 200.0)]),
+        );
+        let buffs = sparkle.buffs(&carry)?;
+        assert!((buffs[&Stats::CritDmg_] - (0.24 * 200.0 + 45.0)).abs() < 1e-9);
+        assert!((buffs[&Stats::DmgBoost_] - 3.0 * 16.0).abs() < 1e-9);
+        Ok(())
+    }
+
+    #[test]
+    fn test_support_sources() -> Result<()> {
+        let carry = character("1308", Path::Nihility, AttackType::Lightning, 0);
+        let carry = Carry {
+            character: &carry,
+            light_cone: &None,
+            skill_type: &SkillType::Ultimate,
+        };
+
+        // Half of the carry's 600 base ATK, capped at a quarter of Tingyun's 1000 ATK.
+        let tingyun = support(
+            character("1202", Path::Harmony, AttackType::Lightning, 0),
+            HashMap::from([(Stats::Atk, 1000.0)]),
+        );
+        let sources = tingyun.sources(&carry)?;
+        assert!(sources.contains(&("1202 (Soothing Melody)".to_string(), Stats::Atk, 250.0)));
+
+        let jiaoqiu = ScannedSupport::new(
+            SupportProfile {
+                character: character("1218", Path::Nihility, AttackType::Fire, 0),
+                light_cone: None,
+                stats: HashMap::new(),
+            },
+            SupportConfig {
+                talent_stacks: Some(3),
+                ..Default::default()
+            },
+        );
+        let debuffs = jiaoqiu.debuffs(&carry)?;
+        assert!((debuffs[&Stats::Vulnerebility_] - (15.0 + 2.0 * 5.0 + 15.0)).abs() < 1e-9);
+        Ok(())
+    }
+
+    #[test]
+    fn test_support_kits_read_known_options() -> Result<()> {
+        for id in ["1101", "1106", "1202", "1218", "1303", "1306", "1309"] {
+            let support = support(
+                character(id, Path::Harmony, AttackType::Quantum, 6),
+                HashMap::new(),
+            );
+            assert!(support.profile.has_kit());
+            let carry = character("1308", Path::Nihility, AttackType::Ice, 0);
+            for skill_type in [SkillType::Ultimate, SkillType::FollowUpAttack] {
+                support.sources(&Carry {
+                    character: &carry,
+                    light_cone: &None,
+                    skill_type: &skill_type,
+                })?;
+            }
+        }
+        Ok(())
+    }
+}
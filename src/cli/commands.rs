@@ -0,0 +1,1074 @@
+use super::{
+    scenario::{Scenario, Solver, TeammateSpec},
+    with_overrides, CleanupArgs, ExportArgs, InspectArgs, TeamArgs, UpgradesArgs,
+};
+use crate::{
+    character::{
+        evaluator_for, total_damage, DamageHit, Evaluator, NamedEvaluator, ScannedSupport, Support,
+        SupportConfig, SupportProfile, Teammate,
+    },
+    client::{cache::DiskCache, project_yatta_client::ProjectYattaClient},
+    data_fetcher::{
+        project_yatta_data_fetcher::ProjectYattaDataFetcher,
+        star_rail_res_data_fetcher::{StarRailResDataFetcher, STAR_RAIL_RES_PATH},
+        DataFetcher,
+    },
+    domain::{
+        config_keys, equip_in_scan, import_scan, to_fribbels, Character, DamageType, Encounter,
+        ExportFormat, HsrScanner, Importer, LightConeEntity, LightConePassiveConfig, Relic,
+        RelicSetConfig, Relics, ScannerInput, Slot, Stats,
+    },
+    engine::{
+        branch_and_bound::BranchAndBound,
+        cleanup::{mark_discarded, unused_relics},
+        constraints::{sheet_stats, Constrained, SHEET_STATS},
+        optimizer::{run_seed, Optimizer},
+        ranking::RankedBuild,
+        relic_filter::RelicFilter,
+        sensitivity::substat_weights,
+        simulated_annealing::SimulatedAnnealing,
+        team_optimizer::{MemberObjective, TeamMember, TeamOptimizer},
+        timeline::{
+            damage_per_cycle, simulate as simulate_timeline, uptime, with_state, TimelineSettings,
+        },
+        upgrade::{potential, project, remaining_upgrades, Projection},
+    },
+    service::scanner_parser_service::ScannerParserService,
+    utils::calculator::bonus_sources,
+};
+use eyre::{bail, eyre, Context, Result};
+use itertools::Itertools;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde_json::Value;
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    sync::Arc,
+};
+use strum::IntoEnumIterator;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+const PROJECT_YATTA_URL: &str = "https://sr.yatta.moe/api/v2/";
+
+/// The character of a build with its light cone and the relics it can choose from.
+struct Session {
+    character: Character,
+    light_cone: Option<LightConeEntity>,
+    relic_pool: HashMap<Slot, Vec<Relic>>,
+    /// Teammates from the scan, keyed by character ID.
+    supports: HashMap<String, SupportProfile>,
+}
+
+pub async fn optimize(scenario: &Scenario, export: &ExportArgs, cache: &DiskCache) -> Result<()> {
+    let session = load_session(scenario, cache).await?;
+    let evaluator = build_evaluator(scenario, &session)?;
+    let encounter = &scenario.encounter;
+    let target = &scenario.target;
+
+    let equipped = Relics {
+        relics: equipped_relics(&session.relic_pool, &session.character.id),
+        config: scenario.relic_set_config.clone(),
+    };
+    let current = evaluator.evaluate(
+        &equipped,
+        encounter,
+        target,
+        &teammates(scenario, &session)?,
+    )?;
+    info!(
+        "Currently equipped build of {}: {target} {current}",
+        session.character.name
+    );
+
+    let builds = find_builds(scenario, &session, &evaluator, &equipped)?;
+    let best = builds.first().ok_or_else(|| eyre!("No build found"))?;
+    print_build(&best.relics.relics);
+    report_constraints(scenario, &session, &best.relics)?;
+    println!("Sets: {}", best.sets_summary());
+    println!("{target}: {} (equipped: {current})", best.score);
+    for (rank, build) in builds.iter().enumerate().skip(1) {
+        println!();
+        println!("#{} {}", rank + 1, build.sets_summary());
+        print_build(&build.relics.relics);
+        report_constraints(scenario, &session, &build.relics)?;
+        println!("{target}: {} (-{})", build.score, build.delta);
+    }
+    if let Some(file_path) = &export.export {
+        export_build(
+            scenario,
+            &best.relics.relics,
+            export.export_format,
+            file_path,
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+/// Writes a copy of the scan with the build equipped on the scenario's character.
+async fn export_build(
+    scenario: &Scenario,
+    build: &[Relic],
+    format: ExportFormat,
+    file_path: &std::path::Path,
+) -> Result<()> {
+    let character_id = scenario.character.as_str();
+    let json = match format {
+        ExportFormat::Scanner => {
+            let mut json: Value = serde_json::from_reader(fs::File::open(&scenario.scan)?)?;
+            if !HsrScanner.detect(&json) {
+                bail!("Only HSR-Scanner scans can be exported as is, export to fribbels instead");
+            }
+            let moved = equip_in_scan(&mut json, character_id, build)?;
+            info!("{moved} relics change hands");
+            json
+        }
+        ExportFormat::Fribbels => {
+            let input = load_input_data(&scenario.scan).await?;
+            to_fribbels(&input, character_id, build)?
+        }
+    };
+    fs::write(file_path, serde_json::to_string_pretty(&json)?)?;
+    println!("Build exported to {}", file_path.display());
+    Ok(())
+}
+
+/// The `top` best builds of the scenario with its solver, best first. Builds breaking a stat
+/// constraint are left out, none being left an error.
+fn find_builds(
+    scenario: &Scenario,
+    session: &Session,
+    evaluator: &NamedEvaluator,
+    equipped: &Relics,
+) -> Result<Vec<RankedBuild>> {
+    let encounter = &scenario.encounter;
+    let target = &scenario.target;
+    let character_id = session.character.id.as_str();
+    let relic_pool =
+        scenario
+            .relic_filter
+            .apply(&session.relic_pool, character_id, &[character_id])?;
+    check_pool(&relic_pool)?;
+
+    let params = &scenario.optimizer;
+    let relic_pool = if params.upgrades == Projection::Current {
+        relic_pool
+    } else {
+        let weights = if params.upgrades == Projection::BestCase {
+            substat_weight_map(scenario, session, evaluator, equipped)?
+        } else {
+            HashMap::new()
+        };
+        info!(
+            "Valuing relics that can still be levelled as {:?}",
+            params.upgrades
+        );
+        relic_pool
+            .into_iter()
+            .map(|(slot, relics)| {
+                let relics = relics
+                    .iter()
+                    .map(|relic| project(relic, params.upgrades, &weights))
+                    .collect::<Result<_>>()?;
+                Ok((slot, relics))
+            })
+            .collect::<Result<_>>()?
+    };
+    let builds = match params.solver {
+        Solver::Genetic => {
+            let simulated_annealing = SimulatedAnnealing {
+                initial_temp: params.simulated_annealing.initial_temp,
+                cooling_rate: params.simulated_annealing.cooling_rate,
+                min_temp: params.simulated_annealing.min_temp,
+                aggresive_factor: params.simulated_annealing.aggresive_factor,
+                relic_pool: relic_pool.clone(),
+                evaluator: evaluator.clone(),
+                teammates: teammates(scenario, session)?,
+                encounter: encounter.clone(),
+                target: target.clone(),
+            };
+            let optimizer = Optimizer {
+                relic_pool,
+                generation: params.generation,
+                population_size: params.population_size,
+                mutation_rate: params.mutation_rate,
+                crossover_rate: params.crossover_rate,
+                evaluator: evaluator.clone(),
+                enable_sa: params.enable_sa,
+                simulated_annealing,
+                encounter: encounter.clone(),
+                target: target.clone(),
+                teammates: teammates(scenario, session)?,
+                relic_set_config: scenario.relic_set_config.clone(),
+                seed: params.seed,
+            };
+            println!("----------------- Optimizing Character -----------------");
+            optimizer.optimize_top(params.top)
+        }
+        Solver::BranchAndBound => {
+            let evaluator = character_evaluator(scenario, session)?;
+            let solver = BranchAndBound {
+                relic_pool,
+                evaluator: evaluator.clone(),
+                target: target.clone(),
+                encounter: encounter.clone(),
+                teammates: teammates(scenario, session)?,
+                relic_set_config: scenario.relic_set_config.clone(),
+                top: params.top,
+                constraints: constrained(scenario, session, evaluator),
+            };
+            println!("------------- Optimizing Character (exact) -------------");
+            solver.solve()
+        }
+    }?;
+    feasible_builds(scenario, session, builds)
+}
+
+/// The builds meeting every stat constraint of the scenario.
+fn feasible_builds(
+    scenario: &Scenario,
+    session: &Session,
+    builds: Vec<RankedBuild>,
+) -> Result<Vec<RankedBuild>> {
+    if scenario.constraints.is_empty() {
+        return Ok(builds);
+    }
+    let found = builds.len();
+    let mut feasible = vec![];
+    for build in builds {
+        let stats = sheet_stats(&session.character, &session.light_cone, &build.relics)?;
+        if scenario
+            .constraints
+            .iter()
+            .all(|constraint| constraint.check(&stats).satisfied())
+        {
+            feasible.push(build);
+        }
+    }
+    if feasible.is_empty() {
+        bail!("No feasible build satisfies the constraints");
+    }
+    if feasible.len() < found {
+        warn!(
+            "{} of the {found} builds found break a constraint and are left out",
+            found - feasible.len()
+        );
+    }
+    Ok(feasible)
+}
+
+/// Evaluates the given relic UIDs, or the equipped relics when none are given.
+pub async fn evaluate(
+    scenario: &Scenario,
+    relic_uids: &[String],
+    explain: bool,
+    weights: bool,
+    cache: &DiskCache,
+) -> Result<()> {
+    let session = load_session(scenario, cache).await?;
+    let evaluator = build_evaluator(scenario, &session)?;
+    let relics = if relic_uids.is_empty() {
+        equipped_relics(&session.relic_pool, &session.character.id)
+    } else {
+        relic_uids
+            .iter()
+            .map(|uid| {
+                session
+                    .relic_pool
+                    .values()
+                    .flatten()
+                    .find(|relic| &relic.uid == uid)
+                    .cloned()
+                    .ok_or_else(|| eyre!("Relic {uid} not found in the scan"))
+            })
+            .collect::<Result<_>>()?
+    };
+    let relics = Relics {
+        relics,
+        config: scenario.relic_set_config.clone(),
+    };
+    let teammates = teammates(scenario, &session)?;
+    let score = evaluator.evaluate(&relics, &scenario.encounter, &scenario.target, &teammates)?;
+    print_build(&relics.relics);
+    report_constraints(scenario, &session, &relics)?;
+    if explain {
+        print_breakdown(scenario, &session, evaluator.as_ref(), &relics)?;
+    }
+    if weights {
+        println!("Substat weights, gain per average roll:");
+        for weight in substat_weights(
+            evaluator.as_ref(),
+            &relics,
+            &scenario.encounter,
+            &scenario.target,
+            &teammates,
+        )? {
+            println!(
+                "  {:?} +{:.2}: {:+.2} ({:.2})",
+                weight.stat, weight.roll, weight.gain, weight.weight
+            );
+        }
+    }
+    println!("{}: {score}", scenario.target);
+    Ok(())
+}
+
+/// Ranks the relics that can still be levelled by their simulated score once fully upgraded,
+/// the substats being weighted for the equipped build.
+pub async fn upgrades(scenario: &Scenario, args: &UpgradesArgs, cache: &DiskCache) -> Result<()> {
+    let session = load_session(scenario, cache).await?;
+    let evaluator = build_evaluator(scenario, &session)?;
+    let equipped = Relics {
+        relics: equipped_relics(&session.relic_pool, &session.character.id),
+        config: scenario.relic_set_config.clone(),
+    };
+    let weights = substat_weight_map(scenario, &session, &evaluator, &equipped)?;
+    let character_id = session.character.id.as_str();
+    let relic_pool =
+        scenario
+            .relic_filter
+            .apply(&session.relic_pool, character_id, &[character_id])?;
+
+    let mut rng = ChaCha8Rng::seed_from_u64(run_seed(args.seed));
+    let mut potentials = vec![];
+    for slot in Slot::iter().filter(|slot| *slot != Slot::Dummy) {
+        for relic in relic_pool.get(&slot).into_iter().flatten() {
+            if remaining_upgrades(relic) > 0 {
+                potentials.push((relic, potential(relic, &weights, args.samples, &mut rng)?));
+            }
+        }
+    }
+    println!(
+        "{:<13} {:<40} {:<24} {:>7} {:>8} {:>6} {:>6} {:>6}",
+        "Slot", "Relic", "Main stat", "Current", "Expected", "P10", "P50", "P90"
+    );
+    for (relic, potential) in potentials
+        .into_iter()
+        .sorted_by(|(_, a), (_, b)| b.mean().total_cmp(&a.mean()))
+        .take(args.top)
+    {
+        println!(
+            "{:<13} {:<40} {:<24} {:>7.2} {:>8.2} {:>6.2} {:>6.2} {:>6.2}",
+            format!("{:?}", relic.slot),
+            format!("{} +{}", relic.name, relic.level),
+            format!("{:?}", relic.mainstat),
+            potential.current,
+            potential.mean(),
+            potential.percentile(10.0),
+            potential.percentile(50.0),
+            potential.percentile(90.0),
+        );
+    }
+    println!("Scores are weighted substat rolls, see `evaluate --weights`.");
+    Ok(())
+}
+
+/// Weight of each substat for the build, see `substat_weights`.
+fn substat_weight_map(
+    scenario: &Scenario,
+    session: &Session,
+    evaluator: &NamedEvaluator,
+    build: &Relics,
+) -> Result<HashMap<Stats, f64>> {
+    if build.relics.is_empty() {
+        bail!("Substats are weighted for the equipped build, but no relic is equipped");
+    }
+    Ok(substat_weights(
+        evaluator.as_ref(),
+        build,
+        &scenario.encounter,
+        &scenario.target,
+        &teammates(scenario, session)?,
+    )?
+    .into_iter()
+    .map(|weight| (weight.stat, weight.weight))
+    .collect())
+}
+
+/// Reports the relics that none of the top builds of the scenarios uses, and optionally flags
+/// them as discarded in a copy of the scan. Locked relics are never flagged. Builds come from
+/// the exact search whatever the scenarios' solver, the genetic one varying from run to run.
+pub async fn cleanup(args: &CleanupArgs, cache: &DiskCache) -> Result<()> {
+    let mut kept = HashSet::new();
+    let mut scan = None;
+    let mut relic_pool = HashMap::new();
+    for file_path in &args.scenarios {
+        let mut scenario = Scenario::load(file_path)?;
+        if scan.get_or_insert_with(|| scenario.scan.clone()) != &scenario.scan {
+            bail!(
+                "{} uses another scan than the other scenarios",
+                file_path.display()
+            );
+        }
+        scenario.optimizer.top = args.top;
+        scenario.optimizer.solver = Solver::BranchAndBound;
+        let session = load_session(&scenario, cache).await?;
+        let evaluator = build_evaluator(&scenario, &session)?;
+        let equipped = Relics {
+            relics: equipped_relics(&session.relic_pool, &session.character.id),
+            config: scenario.relic_set_config.clone(),
+        };
+        let builds = find_builds(&scenario, &session, &evaluator, &equipped)
+            .wrap_err_with(|| format!("Scenario {}", file_path.display()))?;
+        let uids: HashSet<String> = builds
+            .iter()
+            .flat_map(|build| &build.relics.relics)
+            .map(|relic| relic.uid.clone())
+            .collect();
+        println!(
+            "{} ({}): {} relics in the top {} builds",
+            session.character.name,
+            scenario.target,
+            uids.len(),
+            builds.len()
+        );
+        kept.extend(uids);
+        relic_pool = session.relic_pool;
+    }
+
+    let cleanup = unused_relics(&relic_pool, &kept);
+    let total: usize = relic_pool.values().map(Vec::len).sum();
+    println!();
+    println!("Discard {} of {total} relics:", cleanup.discard.len());
+    print_build(&cleanup.discard);
+    if !cleanup.locked.is_empty() {
+        println!("{} unused relics are locked and kept", cleanup.locked.len());
+    }
+
+    if let (Some(output), Some(scan)) = (&args.write, scan) {
+        let mut json: Value = serde_json::from_reader(fs::File::open(scan)?)?;
+        let uids = cleanup.discard.iter().map(|r| r.uid.clone()).collect();
+        let marked = mark_discarded(&mut json, &uids)?;
+        fs::write(output, serde_json::to_string_pretty(&json)?)?;
+        println!(
+            "{marked} relics flagged as discarded in {}",
+            output.display()
+        );
+    }
+    Ok(())
+}
+
+/// Optimizes the members of a team together, each relic being worn by one member at most.
+pub async fn team(args: &TeamArgs, cache: &DiskCache) -> Result<()> {
+    let relic_set_config = with_overrides(RelicSetConfig::all_active(), &args.set_config)?;
+    relic_set_config.check_keys()?;
+    let input = load_input_data(&args.scan).await?;
+    let character_ids: Vec<&str> = args.members.iter().map(|m| m.character.as_str()).collect();
+    let (mut characters, light_cones, relic_pool) =
+        parse_characters(input, &character_ids, &[], cache).await?;
+
+    let filter = RelicFilter::from(&args.filter);
+    let members = args
+        .members
+        .iter()
+        .map(|spec| {
+            let relic_pool = filter.apply(&relic_pool, &spec.character, &character_ids)?;
+            check_pool(&relic_pool)?;
+            let character = characters
+                .remove(&spec.character)
+                .ok_or_else(|| eyre!("Character {} listed twice", spec.character))?;
+            let light_cone = equipped_light_cone(&light_cones, &character.id);
+            let objective = match &spec.target {
+                Some(target) => Some(MemberObjective {
+                    evaluator: evaluator_for(
+                        &character,
+                        light_cone.clone(),
+                        args.crit,
+                        Value::Object(Default::default()),
+                    )?,
+                    target: target.clone(),
+                    weight: spec.weight,
+                }),
+                None => None,
+            };
+            Ok(TeamMember {
+                character,
+                light_cone,
+                objective,
+                support_config: SupportConfig::default(),
+                relic_pool,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let optimizer = TeamOptimizer {
+        members: Arc::new(members),
+        relic_set_config,
+        encounter: Encounter::try_from(&args.enemy)?,
+        rounds: args.rounds,
+        generation: args.optimizer.generation,
+        population_size: args.optimizer.population_size,
+        mutation_rate: args.optimizer.mutation_rate,
+        crossover_rate: args.optimizer.crossover_rate,
+        seed: args.optimizer.seed,
+    };
+    println!("------------------- Optimizing Team --------------------");
+    let team = optimizer.optimize()?;
+    for ((member, build), score) in optimizer.members.iter().zip(&team.builds).zip(&team.scores) {
+        println!();
+        match (score, &member.objective) {
+            (Some(score), Some(objective)) => println!(
+                "{} ({}): {} {score}",
+                member.character.name, member.character.id, objective.target
+            ),
+            _ => println!(
+                "{} ({}): support",
+                member.character.name, member.character.id
+            ),
+        }
+        print_build(&build.relics);
+    }
+    println!();
+    println!("Team objective: {}", team.objective);
+    Ok(())
+}
+
+/// Plays the rotation of the scenario with the equipped build, each action of the character
+/// being scored with the relic set and light cone buffs up at that point of the fight.
+pub async fn simulate(scenario: &Scenario, cache: &DiskCache) -> Result<()> {
+    let settings = scenario
+        .timeline
+        .as_ref()
+        .ok_or_else(|| eyre!("The scenario has no timeline to simulate"))?;
+    check_timeline_buffs(settings)?;
+    let session = load_session(scenario, cache).await?;
+    let relics = Relics {
+        relics: equipped_relics(&session.relic_pool, &session.character.id),
+        config: scenario.relic_set_config.clone(),
+    };
+    let stats = sheet_stats(&session.character, &session.light_cone, &relics)?;
+    let spd = stats.get(&Stats::Spd).copied().unwrap_or_default();
+    let err = stats
+        .get(&Stats::EnergyRegenerationRate_)
+        .copied()
+        .unwrap_or(100.0);
+    let actions = simulate_timeline(settings, spd, err)?;
+    let teammates = teammates(scenario, &session)?;
+
+    println!("SPD {spd:.1}, ERR {err:.1}%");
+    let damage = damage_per_cycle(&actions, settings.cycles, |action| {
+        let target = action.target.clone().unwrap_or_default();
+        let relics = Relics {
+            relics: relics.relics.clone(),
+            config: with_state(&scenario.relic_set_config, &action.state, true)?,
+        };
+        relics.config.check_keys()?;
+        let mut light_cone = session.light_cone.clone();
+        if let Some(light_cone) = &mut light_cone {
+            light_cone.config = with_state(&light_cone.config, &action.state, false)?;
+        }
+        let evaluator = evaluator_for(
+            &session.character,
+            light_cone,
+            scenario.crit,
+            Value::Object(scenario.options.clone()),
+        )?;
+        let score = evaluator.evaluate(&relics, &scenario.encounter, &target, &teammates)?;
+        println!(
+            "  {:>6.1} AV (cycle {}) {} {target}: {score:.0}",
+            action.av, action.cycle, action.unit
+        );
+        Ok(score)
+    })?;
+    for (cycle, damage) in damage.iter().enumerate() {
+        println!("Cycle {cycle}: {damage:.0}");
+    }
+    println!(
+        "Average per cycle: {:.0}",
+        damage.iter().sum::<f64>() / damage.len().max(1) as f64
+    );
+    if !settings.buffs.is_empty() {
+        println!("Buff uptime:");
+        for (key, uptime) in uptime(&actions) {
+            println!("  {key}: {:.0}%", uptime * 100.0);
+        }
+    }
+    Ok(())
+}
+
+/// Rejects timeline buffs that neither the light cone nor the relic set config has an entry for.
+fn check_timeline_buffs(settings: &TimelineSettings) -> Result<()> {
+    let fields = |config: Value| match config {
+        Value::Object(fields) => fields.keys().cloned().collect(),
+        _ => vec![],
+    };
+    let mut known: HashSet<String> =
+        fields(serde_json::to_value(LightConePassiveConfig::default())?)
+            .into_iter()
+            .chain(fields(serde_json::to_value(RelicSetConfig::default())?))
+            .collect();
+    known.extend(config_keys()?.into_iter().map(str::to_string));
+    for buff in &settings.buffs {
+        if !known.contains(&buff.config) {
+            bail!(
+                "Unknown timeline buff {}, not a light cone or relic set config entry",
+                buff.config
+            );
+        }
+    }
+    Ok(())
+}
+
+pub async fn inspect(args: &InspectArgs) -> Result<()> {
+    let input = load_input_data(&args.scan).await?;
+    match &args.character {
+        None => {
+            println!(
+                "{:<6} {:<24} {:<14} {:>5} {:>3}  Light cone",
+                "ID", "Name", "Path", "Level", "E"
+            );
+            for character in &input.characters {
+                let light_cone = input
+                    .light_cones
+                    .iter()
+                    .find(|lc| lc.location.as_deref() == Some(character.id.as_str()))
+                    .map(|lc| format!("{} ({})", lc.name, lc._uid))
+                    .unwrap_or_default();
+                println!(
+                    "{:<6} {:<24} {:<14} {:>5} {:>3}  {light_cone}",
+                    character.id,
+                    character.name,
+                    character
+                        .path
+                        .as_ref()
+                        .map(|path| format!("{path:?}"))
+                        .unwrap_or_default(),
+                    character.level,
+                    character.eidolon,
+                );
+            }
+            println!();
+            println!("{} light cones", input.light_cones.len());
+            for slot in Slot::iter().filter(|slot| *slot != Slot::Dummy) {
+                let count = input.relics.iter().filter(|r| r.slot == slot).count();
+                println!("{count:>5} {slot:?}");
+            }
+        }
+        Some(id) => {
+            let character = input
+                .characters
+                .iter()
+                .find(|c| &c.id == id)
+                .ok_or_else(|| eyre!("Character {id} not found in the scan"))?;
+            println!("{character:#?}");
+            if StarRailResDataFetcher::available(STAR_RAIL_RES_PATH) {
+                let data = StarRailResDataFetcher::load(STAR_RAIL_RES_PATH)?;
+                let skills = &character.skills;
+                for (suffix, kind, level) in [
+                    ("01", "Basic ATK", skills.basic),
+                    ("02", "Skill", skills.skill),
+                    ("03", "Ultimate", skills.ult),
+                    ("04", "Talent", skills.talent),
+                ] {
+                    let scaling = data.skill_scaling(&format!("{id}{suffix}"))?;
+                    let params = scaling
+                        .params
+                        .iter()
+                        .map(|table| table.at(level).map(|value| value.to_string()))
+                        .collect::<Result<Vec<_>>>()?;
+                    println!(
+                        "{kind:<9} Lv{level:<2} {}: {}",
+                        scaling.name,
+                        params.join(", ")
+                    );
+                }
+            }
+            for light_cone in input
+                .light_cones
+                .iter()
+                .filter(|lc| lc.location.as_deref() == Some(id.as_str()))
+            {
+                println!(
+                    "Light cone: {} ({}) S{} Lv{}",
+                    light_cone.name, light_cone._uid, light_cone.superimposition, light_cone.level
+                );
+            }
+            for relic in input
+                .relics
+                .iter()
+                .filter(|r| r.location.as_deref() == Some(id.as_str()))
+            {
+                let substats = relic
+                    .substats
+                    .iter()
+                    .map(|s| format!("{:?} {}", s.key, s.value))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                println!(
+                    "{:<13} {:<40} +{:<2} {:?} | {substats} ({})",
+                    format!("{:?}", relic.slot),
+                    relic.name,
+                    relic.level,
+                    relic.mainstat,
+                    relic._uid
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Loads the scan and fetches the data of the requested character and light cone only.
+async fn load_session(scenario: &Scenario, cache: &DiskCache) -> Result<Session> {
+    scenario.relic_set_config.check_keys()?;
+    let input = load_input_data(&scenario.scan).await?;
+    let character_id = scenario.character.as_str();
+    let light_cone_uid = match &scenario.light_cone.uid {
+        Some(uid) => Some(uid.clone()),
+        None => input
+            .light_cones
+            .iter()
+            .find(|lc| lc.location.as_deref() == Some(character_id))
+            .map(|lc| lc._uid.clone()),
+    };
+    let support_ids: Vec<&str> = scenario
+        .teammates
+        .iter()
+        .filter_map(|teammate| match teammate {
+            TeammateSpec::Scanned { character, .. } => Some(character.as_str()),
+            _ => None,
+        })
+        .collect();
+    let character_ids: Vec<&str> = [character_id]
+        .into_iter()
+        .chain(support_ids.iter().copied())
+        .collect();
+    let (mut characters, mut light_cones, relic_pool) =
+        parse_characters(input, &character_ids, light_cone_uid.as_slice(), cache).await?;
+
+    let character = characters
+        .remove(character_id)
+        .ok_or_else(|| eyre!("Character {character_id} not found"))?;
+    let light_cone = match light_cone_uid {
+        Some(uid) => {
+            let mut light_cone = light_cones
+                .remove(&uid)
+                .ok_or_else(|| eyre!("Light cone {uid} not found in the scan"))?;
+            light_cone.config = scenario.light_cone.config.clone();
+            Some(light_cone)
+        }
+        None => None,
+    };
+    let mut supports = HashMap::new();
+    for id in support_ids {
+        let support = characters
+            .remove(id)
+            .ok_or_else(|| eyre!("Teammate {id} not found in the scan"))?;
+        let support_light_cone = equipped_light_cone(&light_cones, id);
+        let relics = Relics {
+            relics: equipped_relics(&relic_pool, id),
+            config: scenario.relic_set_config.clone(),
+        };
+        let profile = SupportProfile::new(support, support_light_cone, &relics)?;
+        if !profile.has_kit() {
+            warn!(
+                "{} has no support kit, only its path and light cone are used",
+                profile.character.name
+            );
+        }
+        supports.insert(id.to_string(), profile);
+    }
+    Ok(Session {
+        character,
+        light_cone,
+        relic_pool,
+        supports,
+    })
+}
+
+type ParsedScan = (
+    HashMap<String, Character>,
+    HashMap<String, LightConeEntity>,
+    HashMap<Slot, Vec<Relic>>,
+);
+
+/// Fetches the data of the given characters, of the light cones they wear and of the light cones
+/// in `light_cone_uids`. Every relic is kept.
+async fn parse_characters(
+    mut input: ScannerInput,
+    character_ids: &[&str],
+    light_cone_uids: &[String],
+    cache: &DiskCache,
+) -> Result<ParsedScan> {
+    for id in character_ids {
+        if !input.characters.iter().any(|c| c.id == *id) {
+            return Err(eyre!("Character {id} not found in the scan"));
+        }
+    }
+    input
+        .characters
+        .retain(|c| character_ids.contains(&c.id.as_str()));
+    input.light_cones.retain(|lc| {
+        light_cone_uids.contains(&lc._uid)
+            || lc
+                .location
+                .as_deref()
+                .is_some_and(|location| character_ids.contains(&location))
+    });
+
+    let data_fetcher: Arc<Mutex<dyn DataFetcher + Send + Sync>> =
+        if StarRailResDataFetcher::available(STAR_RAIL_RES_PATH) {
+            Arc::new(Mutex::new(StarRailResDataFetcher::load(
+                STAR_RAIL_RES_PATH,
+            )?))
+        } else {
+            warn!("No StarRailRes database in {STAR_RAIL_RES_PATH}, fetching game data online");
+            Arc::new(Mutex::new(ProjectYattaDataFetcher {
+                client: ProjectYattaClient {
+                    url: PROJECT_YATTA_URL.to_string(),
+                    language: "en".to_string(),
+                    http: reqwest::Client::new(),
+                    cache: cache.clone(),
+                },
+            }))
+        };
+    let mut scanner_parser_service = ScannerParserService { data_fetcher };
+    scanner_parser_service.parse_scanner_input(&input).await
+}
+
+fn equipped_light_cone(
+    light_cones: &HashMap<String, LightConeEntity>,
+    character_id: &str,
+) -> Option<LightConeEntity> {
+    light_cones
+        .values()
+        .find(|lc| lc._light_cone.location.as_deref() == Some(character_id))
+        .cloned()
+}
+
+/// Evaluator of the scenario, scoring builds that break a stat constraint below any other.
+fn build_evaluator(scenario: &Scenario, session: &Session) -> Result<NamedEvaluator> {
+    let evaluator = character_evaluator(scenario, session)?;
+    Ok(match constrained(scenario, session, evaluator.clone()) {
+        Some(constrained) => Arc::new(constrained),
+        None => evaluator,
+    })
+}
+
+/// Evaluator of the scenario's character, ignoring the stat constraints.
+fn character_evaluator(scenario: &Scenario, session: &Session) -> Result<NamedEvaluator> {
+    evaluator_for(
+        &session.character,
+        session.light_cone.clone(),
+        scenario.crit,
+        Value::Object(scenario.options.clone()),
+    )
+}
+
+/// The evaluator wrapped with the stat constraints of the scenario, if any.
+fn constrained(
+    scenario: &Scenario,
+    session: &Session,
+    evaluator: NamedEvaluator,
+) -> Option<Constrained> {
+    if scenario.constraints.is_empty() {
+        return None;
+    }
+    Some(Constrained {
+        evaluator,
+        character: session.character.clone(),
+        light_cone: session.light_cone.clone(),
+        constraints: scenario.constraints.clone(),
+    })
+}
+
+/// Prints how the build fares against each stat constraint.
+fn report_constraints(scenario: &Scenario, session: &Session, relics: &Relics) -> Result<()> {
+    if scenario.constraints.is_empty() {
+        return Ok(());
+    }
+    let stats = sheet_stats(&session.character, &session.light_cone, relics)?;
+    println!("Constraints:");
+    for constraint in &scenario.constraints {
+        let check = constraint.check(&stats);
+        let status = if !check.satisfied() {
+            "violated"
+        } else if check.binding() {
+            "binding"
+        } else {
+            "slack"
+        };
+        println!(
+            "  {constraint}: {:.2} ({status}, margin {:.2})",
+            check.value, check.slack
+        );
+    }
+    Ok(())
+}
+
+fn teammates(scenario: &Scenario, session: &Session) -> Result<Vec<Box<dyn Support>>> {
+    scenario
+        .teammates
+        .iter()
+        .map(|teammate| -> Result<Box<dyn Support>> {
+            Ok(match teammate {
+                TeammateSpec::Path(path) => Box::new(Teammate { path: path.clone() }),
+                TeammateSpec::Scanned { character, config } => {
+                    let profile = session
+                        .supports
+                        .get(character)
+                        .ok_or_else(|| eyre!("Teammate {character} not found in the scan"))?;
+                    Box::new(ScannedSupport::new(profile.clone(), config.clone()))
+                }
+                TeammateSpec::Custom(custom) => Box::new(custom.clone()),
+            })
+        })
+        .collect()
+}
+
+/// Makes sure the filters leave at least one relic in every slot.
+fn check_pool(relic_pool: &HashMap<Slot, Vec<Relic>>) -> Result<()> {
+    for slot in Slot::iter().filter(|slot| *slot != Slot::Dummy) {
+        if relic_pool.get(&slot).is_none_or(|relics| relics.is_empty()) {
+            return Err(eyre!("No relic left for {slot:?} after filtering"));
+        }
+    }
+    Ok(())
+}
+
+fn equipped_relics(relic_pool: &HashMap<Slot, Vec<Relic>>, character_id: &str) -> Vec<Relic> {
+    Slot::iter()
+        .filter_map(|slot| {
+            relic_pool
+                .get(&slot)?
+                .iter()
+                .find(|relic| relic.location.as_deref() == Some(character_id))
+                .cloned()
+        })
+        .collect()
+}
+
+fn print_build(relics: &[Relic]) {
+    for relic in relics {
+        let substats = relic
+            .substats
+            .iter()
+            .map(|s| format!("{:?} {:.1}", s.key, s.value))
+            .collect::<Vec<_>>()
+            .join(", ");
+        println!(
+            "{:<13} {:<40} {:?} {:.1} | {substats} ({}{})",
+            format!("{:?}", relic.slot),
+            relic.name,
+            relic.mainstat,
+            relic.mainstat_value,
+            relic.uid,
+            match relic.location.as_deref() {
+                Some(location) if !location.is_empty() => format!(", on {location}"),
+                _ => String::new(),
+            }
+        );
+    }
+}
+
+/// Prints the hits the target adds up with every multiplier of their damage, then for each kind
+/// of hit the stat sheet and where the bonus comes from.
+fn print_breakdown(
+    scenario: &Scenario,
+    session: &Session,
+    evaluator: &(dyn Evaluator<Target = String> + Sync + Send),
+    relics: &Relics,
+) -> Result<()> {
+    let teammates = teammates(scenario, session)?;
+    let hits = evaluator.explain(relics, &scenario.encounter, &scenario.target, &teammates)?;
+    println!("------------------- Damage Breakdown -------------------");
+    for hit in &hits {
+        let m = &hit.multipliers;
+        println!(
+            "{} x{} ({:?}, {:?}): {:.1} per hit",
+            hit.name,
+            hit.count,
+            hit.skill_type,
+            hit.damage_type,
+            hit.damage()
+        );
+        match hit.damage_type {
+            DamageType::Heal => {
+                println!(
+                    "  base {:.1} x healing boost {:.4}",
+                    m.base_dmg, m.dmg_boost
+                )
+            }
+            DamageType::Shield => {
+                println!("  base {:.1} x shield bonus {:.4}", m.base_dmg, m.dmg_boost)
+            }
+            _ => println!(
+                "  base {:.1} x crit {:.4} x DMG boost {:.4} x weaken {:.4} x DEF {:.4} \
+                 x RES {:.4} x vulnerability {:.4} x mitigation {:.4} x toughness {:.4}",
+                m.base_dmg,
+                m.crit,
+                m.dmg_boost,
+                m.weaken,
+                m.def,
+                m.res,
+                m.vul,
+                m.dmg_mit,
+                m.toughness
+            ),
+        }
+        for (source, stat, value) in &hit.kit_bonus {
+            println!("  {source}: {stat:?} {value:+.2}");
+        }
+    }
+    println!("Total: {:.1}", total_damage(&hits));
+
+    let mut kinds: Vec<&DamageHit> = vec![];
+    for hit in &hits {
+        if !kinds
+            .iter()
+            .any(|k| k.skill_type == hit.skill_type && k.damage_type == hit.damage_type)
+        {
+            kinds.push(hit);
+        }
+    }
+    for hit in kinds {
+        println!();
+        println!("{:?} {:?} hits", hit.skill_type, hit.damage_type);
+        let sheet = SHEET_STATS
+            .iter()
+            .map(|stat| {
+                let value = hit.stats.get(stat).cloned().unwrap_or_default();
+                format!("{stat:?} {value:.1}")
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        println!("  Stat sheet: {sheet}");
+        let sources = bonus_sources(
+            &session.character,
+            &session.light_cone,
+            relics,
+            &session.character.attack_type,
+            &hit.skill_type,
+            &hit.damage_type,
+            &teammates,
+        )?;
+        let kit = hit
+            .kit_bonus
+            .iter()
+            .map(|(source, stat, value)| (format!("Kit {source}"), stat.clone(), *value));
+        for (source, bonus) in &sources
+            .into_iter()
+            .chain(kit)
+            .chunk_by(|(source, ..)| source.clone())
+        {
+            let bonus = bonus
+                .map(|(_, stat, value)| (format!("{stat:?}"), value))
+                .sorted_by(|(a, _), (b, _)| a.cmp(b))
+                .map(|(stat, value)| format!("{stat} {value:+.2}"))
+                .join(", ");
+            println!("  {source}: {bonus}");
+        }
+    }
+    Ok(())
+}
+
+/// Loads and deserializes the input JSON data.
+async fn load_input_data(file_path: impl AsRef<std::path::Path>) -> Result<ScannerInput> {
+    let file = fs::File::open(file_path)?;
+    let json: serde_json::Value = serde_json::from_reader(file)?;
+    let mut input = import_scan(json)?;
+    input.update().await?;
+    Ok(input)
+}
@@ -0,0 +1,576 @@
+use crate::{
+    character::SupportConfig,
+    client::cache::DiskCache,
+    domain::{
+        enemy_presets, AttackType, CritEnum, Encounter, Enemy, ExportFormat,
+        LightConePassiveConfig, Path, RelicSetConfig,
+    },
+    engine::{constraints::StatConstraint, relic_filter::RelicFilter, upgrade::Projection},
+};
+use clap::{Args, Parser, Subcommand};
+use eyre::{eyre, Result};
+use scenario::{
+    AnnealingSettings, LightConeSetup, OptimizerSettings, Scenario, Solver, TeammateSpec,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::{path::PathBuf, time::Duration};
+
+mod commands;
+mod scenario;
+
+/// Relic optimizer for Honkai: Star Rail working on HSR-Scanner exports.
+#[derive(Parser)]
+#[command(version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+    #[command(flatten)]
+    pub cache: CacheArgs,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Search the relic inventory for the build maximising the evaluation target.
+    Optimize(OptimizeArgs),
+    /// Evaluate the build currently equipped on a character, or an explicit list of relics.
+    Evaluate(EvaluateArgs),
+    /// List the characters, light cones and relics found in a scan.
+    Inspect(InspectArgs),
+    /// Run the optimization described by a scenario file.
+    Run(RunArgs),
+    /// Optimize up to four characters at once from the same relic inventory.
+    Team(TeamArgs),
+    /// Rank the relics that can still be levelled by what they are worth once fully upgraded.
+    Upgrades(UpgradesArgs),
+    /// List the relics that none of the best builds of a set of scenarios uses. Builds are found
+    /// with the exact search, so that the same scan always flags the same relics.
+    Cleanup(CleanupArgs),
+    /// Play the rotation of a scenario turn by turn with the equipped build, reporting the
+    /// damage of each cycle and the uptime of the buffs.
+    Simulate(SimulateArgs),
+}
+
+impl Cli {
+    pub async fn run(&self) -> Result<()> {
+        let cache = DiskCache::from(&self.cache);
+        match &self.command {
+            Command::Optimize(args) => {
+                let scenario = args
+                    .build
+                    .scenario(OptimizerSettings::from(&args.optimizer))?;
+                if let Some(file_path) = &args.save_scenario {
+                    scenario.save(file_path)?;
+                }
+                commands::optimize(&scenario, &args.export, &cache).await
+            }
+            Command::Evaluate(args) => {
+                let scenario = args.build.scenario(OptimizerSettings::default())?;
+                commands::evaluate(&scenario, &args.relics, args.explain, args.weights, &cache)
+                    .await
+            }
+            Command::Inspect(args) => commands::inspect(args).await,
+            Command::Run(args) => {
+                let scenario = Scenario::load(&args.scenario)?;
+                if args.evaluate {
+                    commands::evaluate(&scenario, &[], args.explain, args.weights, &cache).await
+                } else {
+                    commands::optimize(&scenario, &args.export, &cache).await
+                }
+            }
+            Command::Team(args) => commands::team(args, &cache).await,
+            Command::Upgrades(args) => {
+                let scenario = args.build.scenario(OptimizerSettings::default())?;
+                commands::upgrades(&scenario, args, &cache).await
+            }
+            Command::Cleanup(args) => commands::cleanup(args, &cache).await,
+            Command::Simulate(args) => {
+                let scenario = Scenario::load(&args.scenario)?;
+                commands::simulate(&scenario, &cache).await
+            }
+        }
+    }
+}
+
+/// Cache of the responses of the remote game data APIs, used when the StarRailRes database is
+/// missing.
+#[derive(Args)]
+pub struct CacheArgs {
+    /// Directory the API responses are cached in.
+    #[arg(long, global = true, default_value = ".cache")]
+    pub cache_dir: PathBuf,
+    /// Hours after which a cached API response is fetched again.
+    #[arg(long, global = true, default_value_t = 168)]
+    pub cache_ttl: u64,
+    /// Fetch every API response again instead of using the cached ones.
+    #[arg(long, global = true)]
+    pub refresh: bool,
+}
+
+#[derive(Args)]
+pub struct OptimizeArgs {
+    #[command(flatten)]
+    pub build: BuildArgs,
+    #[command(flatten)]
+    pub optimizer: OptimizerArgs,
+    /// Write the setup of this run to a scenario file so that it can be shared and replayed.
+    #[arg(long)]
+    pub save_scenario: Option<PathBuf>,
+    #[command(flatten)]
+    pub export: ExportArgs,
+}
+
+#[derive(Args)]
+pub struct ExportArgs {
+    /// Write a copy of the scan with the best build equipped on the character.
+    #[arg(long)]
+    pub export: Option<PathBuf>,
+    /// Format of the export: scanner for the format of the scan, or fribbels for a Fribbels
+    /// optimizer save file.
+    #[arg(long, default_value = "scanner", value_parser = parse_value::<ExportFormat>)]
+    pub export_format: ExportFormat,
+}
+
+#[derive(Args)]
+pub struct EvaluateArgs {
+    #[command(flatten)]
+    pub build: BuildArgs,
+    /// UID of a relic to evaluate, once per slot. Defaults to the relics equipped on the character.
+    #[arg(long = "relic")]
+    pub relics: Vec<String>,
+    /// Detail every multiplier of the damage and where each bonus comes from.
+    #[arg(long)]
+    pub explain: bool,
+    /// Rank the substats by how much one more roll of each raises the evaluation target.
+    #[arg(long)]
+    pub weights: bool,
+}
+
+#[derive(Args)]
+pub struct UpgradesArgs {
+    #[command(flatten)]
+    pub build: BuildArgs,
+    /// Number of simulated upgrades of each relic.
+    #[arg(long, default_value_t = 1000)]
+    pub samples: usize,
+    /// Number of relics to list, best expected score first.
+    #[arg(long, default_value_t = 20)]
+    pub top: usize,
+    /// Seed of the simulation, the seed of a run being logged otherwise.
+    #[arg(long)]
+    pub seed: Option<u64>,
+}
+
+#[derive(Args)]
+pub struct CleanupArgs {
+    /// Scenario files, one per character and target to keep relics for. They must share the
+    /// same scan.
+    #[arg(required = true)]
+    pub scenarios: Vec<PathBuf>,
+    /// Number of builds kept per scenario, in place of the scenario's own setting.
+    #[arg(long, default_value_t = 5)]
+    pub top: usize,
+    /// Write a copy of the scan with the relics to discard flagged `discard: true`.
+    #[arg(long)]
+    pub write: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct InspectArgs {
+    /// HSR-Scanner JSON export.
+    #[arg(long, short)]
+    pub scan: PathBuf,
+    /// Show the equipment of a single character instead of the whole roster.
+    #[arg(long, short)]
+    pub character: Option<String>,
+}
+
+#[derive(Args)]
+pub struct RunArgs {
+    /// YAML scenario file.
+    pub scenario: PathBuf,
+    /// Only evaluate the equipped build instead of optimizing.
+    #[arg(long)]
+    pub evaluate: bool,
+    /// With --evaluate, detail every multiplier of the damage and where each bonus comes from.
+    #[arg(long, requires = "evaluate")]
+    pub explain: bool,
+    /// With --evaluate, rank the substats by how much one more roll of each raises the target.
+    #[arg(long, requires = "evaluate")]
+    pub weights: bool,
+    #[command(flatten)]
+    pub export: ExportArgs,
+}
+
+#[derive(Args)]
+pub struct SimulateArgs {
+    /// YAML scenario file with a `timeline` section.
+    pub scenario: PathBuf,
+}
+
+#[derive(Args)]
+pub struct TeamArgs {
+    /// HSR-Scanner JSON export.
+    #[arg(long, short)]
+    pub scan: PathBuf,
+    /// Team member as CHARACTER[:TARGET][=WEIGHT], e.g. 1308:UltimateAoe=2. Members without a
+    /// target only count through their buffs. Repeated for each member, up to four.
+    #[arg(long = "member", required = true, value_parser = parse_member)]
+    pub members: Vec<MemberSpec>,
+    /// How critical hits are accounted for: crit, no-crit or avg.
+    #[arg(long, default_value = "avg", value_parser = parse_value::<CritEnum>)]
+    pub crit: CritEnum,
+    /// Relic set state as KEY=VALUE, e.g. stack_105=3. Every effect is active by default.
+    #[arg(long = "set-config", value_parser = parse_key_value)]
+    pub set_config: Vec<(String, Value)>,
+    /// Passes over the team, each member being optimized once per pass.
+    #[arg(long, default_value_t = 2)]
+    pub rounds: usize,
+    #[command(flatten)]
+    pub enemy: EnemyArgs,
+    #[command(flatten)]
+    pub filter: FilterArgs,
+    #[command(flatten)]
+    pub optimizer: OptimizerArgs,
+}
+
+/// A team member given on the command line.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MemberSpec {
+    pub character: String,
+    pub target: Option<String>,
+    pub weight: f64,
+}
+
+/// Everything needed to evaluate one character.
+#[derive(Args)]
+pub struct BuildArgs {
+    /// HSR-Scanner JSON export.
+    #[arg(long, short)]
+    pub scan: PathBuf,
+    /// Character ID as found in the scan, e.g. 1308.
+    #[arg(long, short)]
+    pub character: String,
+    /// Light cone UID as found in the scan, e.g. light_cone_15. Defaults to the equipped one.
+    #[arg(long)]
+    pub light_cone: Option<String>,
+    /// Evaluation target of the character, e.g. UltimateAoe.
+    #[arg(long, short)]
+    pub target: String,
+    /// How critical hits are accounted for: crit, no-crit or avg.
+    #[arg(long, default_value = "avg", value_parser = parse_value::<CritEnum>)]
+    pub crit: CritEnum,
+    /// Teammate as a path, or a character ID from the scan to apply its buffs. Repeated for
+    /// each teammate.
+    #[arg(long = "teammate", value_parser = parse_teammate)]
+    pub teammates: Vec<TeammateSpec>,
+    /// Floor or ceiling on the character sheet, e.g. SPD>=134 or EHR>=67. Repeated for each
+    /// constraint.
+    #[arg(long = "constraint", value_parser = parse_constraint)]
+    pub constraints: Vec<StatConstraint>,
+    /// Character specific battle state as KEY=VALUE, e.g. crimson_knot=9.
+    #[arg(long = "option", value_parser = parse_key_value)]
+    pub options: Vec<(String, Value)>,
+    /// Light cone passive state as KEY=VALUE, e.g. stack_21001=2.
+    #[arg(long = "light-cone-config", value_parser = parse_key_value)]
+    pub light_cone_config: Vec<(String, Value)>,
+    /// Relic set state as KEY=VALUE, e.g. stack_105=3. Every effect is active by default.
+    #[arg(long = "set-config", value_parser = parse_key_value)]
+    pub set_config: Vec<(String, Value)>,
+    #[command(flatten)]
+    pub enemy: EnemyArgs,
+    #[command(flatten)]
+    pub filter: FilterArgs,
+}
+
+#[derive(Args)]
+pub struct EnemyArgs {
+    /// Built-in enemy preset, e.g. cocolia, replacing the level, RES, DEF bonus and DMG
+    /// mitigation options.
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "enemy_level",
+            "enemy_resistance",
+            "enemy_def_bonus",
+            "enemy_dmg_mitigation",
+        ]
+    )]
+    pub enemy_preset: Option<String>,
+    /// Built-in wave preset, e.g. moc_boss_wave, replacing every enemy option but the battle
+    /// state ones.
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "enemy_preset",
+            "enemy_count",
+            "enemy_level",
+            "enemy_resistance",
+            "enemy_def_bonus",
+            "enemy_dmg_mitigation",
+        ]
+    )]
+    pub wave: Option<String>,
+    #[arg(long, default_value_t = Enemy::default().level)]
+    pub enemy_level: u8,
+    /// Enemy RES in percent.
+    #[arg(long, default_value_t = 0.0)]
+    pub enemy_resistance: f64,
+    /// Enemy DEF bonus as a fraction.
+    #[arg(long, default_value_t = 0.0)]
+    pub enemy_def_bonus: f64,
+    /// Enemy DMG mitigation in percent, repeated for each independent source.
+    #[arg(long = "enemy-dmg-mitigation")]
+    pub enemy_dmg_mitigation: Vec<f64>,
+    /// Enemy vulnerability in percent.
+    #[arg(long, default_value_t = 0.0)]
+    pub enemy_vulnerability: f64,
+    /// Whether the enemy is weakness broken.
+    #[arg(long)]
+    pub toughness_break: bool,
+    /// Weaken applied to the character in percent.
+    #[arg(long, default_value_t = 0.0)]
+    pub weaken: f64,
+    /// Element the enemies are weak to, e.g. LIGHTNING, repeated for each weakness and added to
+    /// those of presets.
+    #[arg(long = "enemy-weakness", value_parser = parse_value::<AttackType>)]
+    pub enemy_weaknesses: Vec<AttackType>,
+    /// Number of identical enemies, the main target being the one in the middle.
+    #[arg(long, default_value_t = 1)]
+    pub enemy_count: usize,
+}
+
+#[derive(Args)]
+pub struct FilterArgs {
+    /// Skip relics worn by other characters.
+    #[arg(long)]
+    pub exclude_equipped: bool,
+    /// Skip relics marked for discard.
+    #[arg(long)]
+    pub exclude_discarded: bool,
+    /// Only use relics locked in game.
+    #[arg(long)]
+    pub locked_only: bool,
+    #[arg(long, default_value_t = 0)]
+    pub min_level: u8,
+    #[arg(long, default_value_t = 0)]
+    pub min_rarity: u8,
+    /// UID of a relic never to use, repeated for each relic.
+    #[arg(long = "exclude")]
+    pub excluded: Vec<String>,
+    /// UID of a relic forced into its slot, repeated for each relic.
+    #[arg(long = "pin")]
+    pub pinned: Vec<String>,
+    /// Keep the relics currently worn by the character.
+    #[arg(long)]
+    pub keep_equipped: bool,
+}
+
+impl From<&FilterArgs> for RelicFilter {
+    fn from(args: &FilterArgs) -> Self {
+        RelicFilter {
+            exclude_equipped: args.exclude_equipped,
+            exclude_discarded: args.exclude_discarded,
+            locked_only: args.locked_only,
+            min_level: args.min_level,
+            min_rarity: args.min_rarity,
+            excluded: args.excluded.clone(),
+            pinned: args.pinned.clone(),
+            keep_equipped: args.keep_equipped,
+        }
+    }
+}
+
+impl BuildArgs {
+    /// Turns the command line into the equivalent scenario.
+    fn scenario(&self, optimizer: OptimizerSettings) -> Result<Scenario> {
+        Ok(Scenario {
+            scan: self.scan.clone(),
+            character: self.character.clone(),
+            light_cone: LightConeSetup {
+                uid: self.light_cone.clone(),
+                config: with_overrides(LightConePassiveConfig::default(), &self.light_cone_config)?,
+            },
+            target: self.target.clone(),
+            crit: self.crit,
+            options: self.options.iter().cloned().collect(),
+            relic_set_config: with_overrides(RelicSetConfig::all_active(), &self.set_config)?,
+            relic_filter: RelicFilter::from(&self.filter),
+            constraints: self.constraints.clone(),
+            encounter: Encounter::try_from(&self.enemy)?,
+            teammates: self.teammates.clone(),
+            optimizer,
+            timeline: None,
+        })
+    }
+}
+
+impl TryFrom<&EnemyArgs> for Encounter {
+    type Error = eyre::Report;
+
+    fn try_from(args: &EnemyArgs) -> Result<Self> {
+        let mut encounter = match (&args.wave, &args.enemy_preset) {
+            (Some(wave), _) => enemy_presets()?.wave(wave)?,
+            (None, Some(preset)) => {
+                Encounter::copies(enemy_presets()?.enemy(preset)?, args.enemy_count)
+            }
+            (None, None) => Encounter::copies(
+                Enemy {
+                    level: args.enemy_level,
+                    resistance: args.enemy_resistance,
+                    def_bonus: args.enemy_def_bonus,
+                    dmg_mitigation: args.enemy_dmg_mitigation.clone(),
+                    ..Default::default()
+                },
+                args.enemy_count,
+            ),
+        };
+        for enemy in &mut encounter.enemies {
+            enemy.add_weaknesses(&args.enemy_weaknesses);
+            enemy.vulnerability = args.enemy_vulnerability;
+            enemy.toughness_break = args.toughness_break;
+            enemy.weaken = args.weaken;
+        }
+        encounter.check()?;
+        Ok(encounter)
+    }
+}
+
+#[derive(Args)]
+pub struct OptimizerArgs {
+    /// Search to run: genetic, or branch-and-bound for an exact but slower one.
+    #[arg(long, default_value = "genetic", value_parser = parse_value::<Solver>)]
+    pub solver: Solver,
+    /// Number of distinct builds to report, best first.
+    #[arg(long, default_value_t = OptimizerSettings::default().top)]
+    pub top: usize,
+    #[arg(long, default_value_t = OptimizerSettings::default().generation)]
+    pub generation: usize,
+    #[arg(long, default_value_t = OptimizerSettings::default().population_size)]
+    pub population_size: usize,
+    #[arg(long, default_value_t = OptimizerSettings::default().mutation_rate)]
+    pub mutation_rate: f64,
+    #[arg(long, default_value_t = OptimizerSettings::default().crossover_rate)]
+    pub crossover_rate: f64,
+    /// Refine the best individual with simulated annealing every 10 generations.
+    #[arg(long)]
+    pub enable_sa: bool,
+    #[arg(long, default_value_t = AnnealingSettings::default().initial_temp)]
+    pub sa_initial_temp: f64,
+    #[arg(long, default_value_t = AnnealingSettings::default().cooling_rate)]
+    pub sa_cooling_rate: f32,
+    #[arg(long, default_value_t = AnnealingSettings::default().min_temp)]
+    pub sa_min_temp: f64,
+    #[arg(long, default_value_t = AnnealingSettings::default().aggresive_factor)]
+    pub sa_aggresive_factor: f32,
+    /// Seed of the random search. Runs with the same seed give the same result, the seed of a
+    /// run being logged otherwise.
+    #[arg(long)]
+    pub seed: Option<u64>,
+    /// Value relics that can still be levelled as they are (current), fully upgraded with their
+    /// expected substats (expected), or with every roll a high roll of their best substat
+    /// (best-case).
+    #[arg(long, default_value = "current", value_parser = parse_value::<Projection>)]
+    pub upgrades: Projection,
+}
+
+impl From<&CacheArgs> for DiskCache {
+    fn from(args: &CacheArgs) -> Self {
+        DiskCache {
+            dir: args.cache_dir.clone(),
+            ttl: Duration::from_secs(args.cache_ttl * 3600),
+            refresh: args.refresh,
+        }
+    }
+}
+
+impl From<&OptimizerArgs> for OptimizerSettings {
+    fn from(args: &OptimizerArgs) -> Self {
+        OptimizerSettings {
+            solver: args.solver,
+            top: args.top,
+            generation: args.generation,
+            population_size: args.population_size,
+            mutation_rate: args.mutation_rate,
+            crossover_rate: args.crossover_rate,
+            enable_sa: args.enable_sa,
+            simulated_annealing: AnnealingSettings {
+                initial_temp: args.sa_initial_temp,
+                cooling_rate: args.sa_cooling_rate,
+                min_temp: args.sa_min_temp,
+                aggresive_factor: args.sa_aggresive_factor,
+            },
+            seed: args.seed,
+            upgrades: args.upgrades,
+        }
+    }
+}
+
+/// Parses a single value through its serde representation, so aliases keep working.
+fn parse_value<T: DeserializeOwned>(s: &str) -> Result<T, String> {
+    serde_json::from_value(Value::String(s.to_string())).map_err(|e| e.to_string())
+}
+
+/// Parses a teammate given as a path, e.g. Nihility, or as a character ID from the scan.
+fn parse_teammate(s: &str) -> Result<TeammateSpec, String> {
+    if !s.is_empty() && s.chars().all(|c| c.is_ascii_digit()) {
+        return Ok(TeammateSpec::Scanned {
+            character: s.to_string(),
+            config: SupportConfig::default(),
+        });
+    }
+    parse_value::<Path>(s).map(TeammateSpec::Path)
+}
+
+/// Parses `CHARACTER[:TARGET][=WEIGHT]`, the weight defaulting to 1.
+fn parse_member(s: &str) -> Result<MemberSpec, String> {
+    let (member, weight) = match s.split_once('=') {
+        Some((member, weight)) => (
+            member,
+            weight
+                .parse::<f64>()
+                .map_err(|e| format!("Invalid weight {weight}: {e}"))?,
+        ),
+        None => (s, 1.0),
+    };
+    let (character, target) = match member.split_once(':') {
+        Some((character, target)) => (character, Some(target.to_string())),
+        None => (member, None),
+    };
+    if character.is_empty() {
+        return Err(format!("Missing character in {s}"));
+    }
+    Ok(MemberSpec {
+        character: character.to_string(),
+        target,
+        weight,
+    })
+}
+
+fn parse_constraint(s: &str) -> Result<StatConstraint, String> {
+    s.parse().map_err(|e: eyre::Report| e.to_string())
+}
+
+/// Parses `KEY=VALUE`, reading the value as JSON and falling back to a plain string.
+fn parse_key_value(s: &str) -> Result<(String, Value), String> {
+    let (key, value) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected KEY=VALUE, got {s}"))?;
+    let value = serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()));
+    Ok((key.trim().to_string(), value))
+}
+
+/// Overwrites fields of a serializable config with `KEY=VALUE` overrides.
+pub fn with_overrides<T: Serialize + DeserializeOwned>(
+    base: T,
+    overrides: &[(String, Value)],
+) -> Result<T> {
+    let mut value = serde_json::to_value(base)?;
+    let fields = value
+        .as_object_mut()
+        .ok_or_else(|| eyre!("Config is not a map of fields"))?;
+    for (key, val) in overrides {
+        fields.insert(key.clone(), val.clone());
+    }
+    Ok(serde_json::from_value(value)?)
+}
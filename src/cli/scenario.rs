@@ -0,0 +1,185 @@
+use crate::{
+    character::{CustomSupport, SupportConfig},
+    domain::{CritEnum, Encounter, LightConePassiveConfig, Path, RelicSetConfig},
+    engine::{
+        constraints::StatConstraint, relic_filter::RelicFilter, timeline::TimelineSettings,
+        upgrade::Projection,
+    },
+};
+use eyre::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::{fs, path::PathBuf};
+
+/// A complete optimization setup that can be checked in and shared.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    /// HSR-Scanner JSON export, relative to the working directory.
+    pub scan: PathBuf,
+    /// Character ID as found in the scan.
+    pub character: String,
+    #[serde(default)]
+    pub light_cone: LightConeSetup,
+    pub target: String,
+    #[serde(default = "default_crit")]
+    pub crit: CritEnum,
+    /// Character specific battle state, e.g. `crimson_knot` for Acheron.
+    #[serde(default)]
+    pub options: Map<String, Value>,
+    #[serde(default = "RelicSetConfig::all_active")]
+    pub relic_set_config: RelicSetConfig,
+    /// Which relics of the scan the optimizer may use.
+    #[serde(default)]
+    pub relic_filter: RelicFilter,
+    /// Floors and ceilings on the character sheet, e.g. `SPD>=134`.
+    #[serde(default)]
+    pub constraints: Vec<StatConstraint>,
+    /// The enemies fought, or a single enemy under the former `enemy` key.
+    #[serde(default, alias = "enemy")]
+    pub encounter: Encounter,
+    #[serde(default)]
+    pub teammates: Vec<TeammateSpec>,
+    #[serde(default)]
+    pub optimizer: OptimizerSettings,
+    /// Rotation played by the `simulate` command.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeline: Option<TimelineSettings>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct LightConeSetup {
+    /// Light cone UID as found in the scan. Defaults to the one equipped on the character.
+    pub uid: Option<String>,
+    pub config: LightConePassiveConfig,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum TeammateSpec {
+    /// Only the path of the teammate is known, e.g. for Acheron's The Abyss.
+    Path(Path),
+    /// A character from the scan, buffing with its equipped light cone and relics.
+    Scanned {
+        character: String,
+        #[serde(default)]
+        config: SupportConfig,
+    },
+    /// Buffs given by hand.
+    Custom(CustomSupport),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct OptimizerSettings {
+    pub solver: Solver,
+    /// Number of distinct builds to report, best first.
+    pub top: usize,
+    pub generation: usize,
+    pub population_size: usize,
+    pub mutation_rate: f64,
+    pub crossover_rate: f64,
+    pub enable_sa: bool,
+    pub simulated_annealing: AnnealingSettings,
+    /// Seed of the random search, to reproduce a run exactly.
+    pub seed: Option<u64>,
+    /// How the relics that can still be levelled are valued.
+    pub upgrades: Projection,
+}
+
+impl Default for OptimizerSettings {
+    fn default() -> Self {
+        Self {
+            solver: Solver::default(),
+            top: 1,
+            generation: 100,
+            population_size: 1000,
+            mutation_rate: 0.1,
+            crossover_rate: 0.7,
+            enable_sa: false,
+            simulated_annealing: AnnealingSettings::default(),
+            seed: None,
+            upgrades: Projection::default(),
+        }
+    }
+}
+
+/// Search used to find the best build.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Solver {
+    /// Genetic algorithm, optionally refined with simulated annealing.
+    #[default]
+    Genetic,
+    /// Exact search, slower but guaranteed to find the best builds.
+    BranchAndBound,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct AnnealingSettings {
+    pub initial_temp: f64,
+    pub cooling_rate: f32,
+    pub min_temp: f64,
+    pub aggresive_factor: f32,
+}
+
+impl Default for AnnealingSettings {
+    fn default() -> Self {
+        Self {
+            initial_temp: 1000.0,
+            cooling_rate: 0.99,
+            min_temp: 0.1,
+            aggresive_factor: 0.9,
+        }
+    }
+}
+
+fn default_crit() -> CritEnum {
+    CritEnum::Avg
+}
+
+impl Scenario {
+    pub fn load(file_path: &std::path::Path) -> Result<Self> {
+        let content = fs::read_to_string(file_path)
+            .wrap_err_with(|| format!("Failed to read scenario {}", file_path.display()))?;
+        serde_yaml::from_str(&content)
+            .wrap_err_with(|| format!("Invalid scenario {}", file_path.display()))
+    }
+
+    pub fn save(&self, file_path: &std::path::Path) -> Result<()> {
+        fs::write(file_path, serde_yaml::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checked_in_scenarios_load() -> Result<()> {
+        for entry in fs::read_dir("scenarios")? {
+            let file_path = entry?.path();
+            let scenario = Scenario::load(&file_path)?;
+            let reloaded: Scenario = serde_yaml::from_str(&serde_yaml::to_string(&scenario)?)?;
+            assert_eq!(reloaded.character, scenario.character);
+            assert_eq!(reloaded.teammates, scenario.teammates);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_teammate_specs() -> Result<()> {
+        let yaml = "- Nihility\n- character: \"1106\"\n- { character: \"1306\", config: { ultimate: false } }\n- { path: Harmony, buffs: { ATK_: 48 } }\n";
+        let teammates: Vec<TeammateSpec> = serde_yaml::from_str(yaml)?;
+        assert_eq!(teammates[0], TeammateSpec::Path(Path::Nihility));
+        assert!(
+            matches!(&teammates[1], TeammateSpec::Scanned { character, .. } if character == "1106")
+        );
+        assert!(matches!(&teammates[2], TeammateSpec::Scanned { config, .. } if !config.ultimate));
+        assert!(matches!(&teammates[3], TeammateSpec::Custom(custom) if custom.buffs.len() == 1));
+        Ok(())
+    }
+}
@@ -0,0 +1,113 @@
+use eyre::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::{fs, future::Future, path::PathBuf, time::Duration};
+use tracing::warn;
+
+/// Responses of the remote APIs saved on disk, one JSON file per endpoint, language and ID.
+#[derive(Clone, Debug)]
+pub struct DiskCache {
+    pub dir: PathBuf,
+    /// Age after which a response is fetched again.
+    pub ttl: Duration,
+    /// Fetch every response again, ignoring the cached ones.
+    pub refresh: bool,
+}
+
+impl Default for DiskCache {
+    fn default() -> Self {
+        Self {
+            dir: PathBuf::from(".cache"),
+            ttl: Duration::from_secs(7 * 24 * 3600),
+            refresh: false,
+        }
+    }
+}
+
+impl DiskCache {
+    fn path(&self, endpoint: &str, id: &str, language: &str) -> PathBuf {
+        self.dir
+            .join(endpoint.replace('/', "_"))
+            .join(language)
+            .join(format!("{id}.json"))
+    }
+
+    /// The cached response and whether it is older than the TTL.
+    fn read(&self, endpoint: &str, id: &str, language: &str) -> Option<(Value, bool)> {
+        let path = self.path(endpoint, id, language);
+        let age = fs::metadata(&path).ok()?.modified().ok()?.elapsed().ok()?;
+        let value = serde_json::from_str(&fs::read_to_string(&path).ok()?).ok()?;
+        Some((value, age >= self.ttl))
+    }
+
+    fn write(&self, endpoint: &str, id: &str, language: &str, value: &Value) -> Result<()> {
+        let path = self.path(endpoint, id, language);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        Ok(fs::write(&path, serde_json::to_string(value)?)?)
+    }
+
+    /// The cached response if it is recent enough, otherwise the response of the request, which
+    /// is then cached. When the request fails, e.g. offline, an outdated response is used anyway.
+    pub async fn fetch<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        id: &str,
+        language: &str,
+        request: impl Future<Output = Result<Value>>,
+    ) -> Result<T> {
+        let cached = self.read(endpoint, id, language);
+        let value = match cached {
+            Some((value, false)) if !self.refresh => value,
+            cached => match request.await {
+                Ok(value) => {
+                    if let Err(e) = self.write(endpoint, id, language, &value) {
+                        warn!("Can't cache {endpoint} {id}: {e}");
+                    }
+                    value
+                }
+                Err(e) => match cached {
+                    Some((value, _)) => {
+                        warn!("Using the outdated cached {endpoint} {id}: {e}");
+                        value
+                    }
+                    None => return Err(e),
+                },
+            },
+        };
+        serde_json::from_value(value).wrap_err_with(|| format!("Invalid {endpoint} {id} response"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eyre::eyre;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_disk_cache() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("disk_cache_{}", std::process::id()));
+        let mut cache = DiskCache {
+            dir: dir.clone(),
+            ..Default::default()
+        };
+        let fetch = |cache: DiskCache, response: Result<Value>| async move {
+            cache
+                .fetch::<Value>("avatar", "1308", "en", async { response })
+                .await
+        };
+
+        assert_eq!(fetch(cache.clone(), Ok(json!(1))).await?, json!(1));
+        assert_eq!(fetch(cache.clone(), Ok(json!(2))).await?, json!(1));
+        cache.refresh = true;
+        assert_eq!(fetch(cache.clone(), Ok(json!(3))).await?, json!(3));
+        cache.refresh = false;
+        cache.ttl = Duration::ZERO;
+        assert_eq!(fetch(cache.clone(), Err(eyre!("offline"))).await?, json!(3));
+        fs::remove_dir_all(&dir)?;
+        assert!(fetch(cache, Err(eyre!("offline"))).await.is_err());
+        Ok(())
+    }
+}
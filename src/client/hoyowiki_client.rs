@@ -1,3 +1,4 @@
+use super::cache::DiskCache;
 use crate::data_fetcher::hoyowiki_data_fetcher::HoyowikiResponse;
 use eyre::{eyre, Result};
 use reqwest::Client;
@@ -7,6 +8,8 @@ pub struct HoyowikiClient {
     pub base_url: String,
     pub language: String,
     pub wiki_app: String,
+    pub http: Client,
+    pub cache: DiskCache,
 }
 
 impl HoyowikiClient {
@@ -15,16 +18,23 @@ impl HoyowikiClient {
         data_name: &str,
         id: &str,
     ) -> Result<Vec<T>> {
-        let client = Client::new();
-        let response: HoyowikiResponse = client
-            .get(format!("{}{}", self.base_url, "/entry_page"))
-            .header("x-rpc-language", self.language.clone())
-            .header("x-rpc-wiki_app", self.wiki_app.clone())
-            .query(&[("entry_page_id", self.id_lookup(id))])
-            .send()
-            .await?
-            .error_for_status()?
-            .json()
+        let entry_page_id = self.id_lookup(id)?;
+        let request = async {
+            Ok(self
+                .http
+                .get(format!("{}{}", self.base_url, "/entry_page"))
+                .header("x-rpc-language", self.language.clone())
+                .header("x-rpc-wiki_app", self.wiki_app.clone())
+                .query(&[("entry_page_id", &entry_page_id)])
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?)
+        };
+        let response: HoyowikiResponse = self
+            .cache
+            .fetch("entry_page", &entry_page_id, &self.language, request)
             .await?;
         let raw_data = &response
             .data
@@ -41,8 +51,8 @@ impl HoyowikiClient {
         Ok(data)
     }
 
-    fn id_lookup(&self, id: &str) -> String {
-        match id {
+    fn id_lookup(&self, id: &str) -> Result<String> {
+        Ok(match id {
             "1001" => "7",
             "1002" => "8",
             "1003" => "9",
@@ -131,8 +141,8 @@ impl HoyowikiClient {
             "23015" => "1398",
             "23016" => "1544",
             "23017" => "1602",
-            _ => todo!(),
+            _ => return Err(eyre!("No Hoyowiki entry for {id}")),
         }
-        .to_string()
+        .to_string())
     }
 }
@@ -1,2 +1,3 @@
+pub mod cache;
 pub mod hoyowiki_client;
 pub mod project_yatta_client;
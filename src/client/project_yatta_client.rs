@@ -1,4 +1,5 @@
-use eyre::{eyre, Result};
+use super::cache::DiskCache;
+use eyre::Result;
 use reqwest::Client;
 use serde::Deserialize;
 use std::collections::HashMap;
@@ -30,7 +31,9 @@ impl Upgrade for LightConeUpgrade {
 
 pub struct ProjectYattaClient {
     pub url: String,
-    pub light_cone_cache: HashMap<String, ProjectYattaLightConeResponse>,
+    pub language: String,
+    pub http: Client,
+    pub cache: DiskCache,
 }
 
 #[derive(Deserialize)]
@@ -40,6 +43,8 @@ pub struct ProjectYattaCharacterResponse {
 
 #[derive(Deserialize)]
 pub struct CharacterData {
+    #[serde(default)]
+    pub name: String,
     pub traces: Traces,
     pub upgrade: Vec<CharacterUpgrade>,
     pub types: Types,
@@ -49,6 +54,12 @@ pub struct CharacterData {
 #[serde(rename_all = "camelCase")]
 pub struct Types {
     pub combat_type: CombatType,
+    pub path_type: Option<PathType>,
+}
+
+#[derive(Deserialize)]
+pub struct PathType {
+    pub id: String,
 }
 
 #[derive(Deserialize)]
@@ -119,36 +130,27 @@ pub struct LightConeUpgrade {
 }
 
 impl ProjectYattaClient {
+    async fn fetch<T: for<'de> Deserialize<'de>>(&self, endpoint: &str, id: &str) -> Result<T> {
+        let request = async {
+            Ok(self
+                .http
+                .get(format!("{}{}/{}/{}", self.url, self.language, endpoint, id))
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?)
+        };
+        self.cache
+            .fetch(endpoint, id, &self.language, request)
+            .await
+    }
+
     pub async fn fetch_character_data(&self, id: &str) -> Result<ProjectYattaCharacterResponse> {
-        Ok(Client::new()
-            .get(format!("{}avatar/{}", self.url, id))
-            .send()
-            .await?
-            .error_for_status()?
-            .json()
-            .await?)
+        self.fetch("avatar", id).await
     }
 
-    pub async fn fetch_light_cone_data(
-        &mut self,
-        id: &str,
-    ) -> Result<ProjectYattaLightConeResponse> {
-        if self.light_cone_cache.contains_key(id) {
-            return self
-                .light_cone_cache
-                .get(id)
-                .cloned()
-                .ok_or_else(|| eyre!("Unexpected error"));
-        }
-        let response: ProjectYattaLightConeResponse = Client::new()
-            .get(format!("{}equipment/{}", self.url, id))
-            .send()
-            .await?
-            .error_for_status()?
-            .json()
-            .await?;
-        self.light_cone_cache
-            .insert(id.to_owned(), response.clone());
-        Ok(response)
+    pub async fn fetch_light_cone_data(&self, id: &str) -> Result<ProjectYattaLightConeResponse> {
+        self.fetch("equipment", id).await
     }
 }
@@ -2,8 +2,8 @@ use super::DataFetcher;
 use crate::{
     client::hoyowiki_client::HoyowikiClient,
     domain::{
-        BaseStats, Character, LightCone, LightConeEntity, LightConePassiveConfig, Path,
-        RawCharacter, Stats,
+        AttackType, BaseStats, Character, DataError, LightCone, LightConeEntity,
+        LightConePassiveConfig, Path, RawCharacter, Stats,
     },
     engine::StatBonusMap,
     utils::trace_title_mapper::title_mapper,
@@ -200,7 +200,7 @@ impl HoyowikiDataFetcherService {
             .points
             .iter()
             .find(|(this_key, _)| *this_key == key)
-            .map(|(_, value)| Ok((title_mapper(&value.title), extract_boost(&value.desc)?)))
+            .map(|(_, value)| Ok((title_mapper(&value.title)?, extract_boost(&value.desc)?)))
             .ok_or_else(|| eyre!("Key {key} not found"))?
     }
 
@@ -275,16 +275,25 @@ impl HoyowikiDataFetcherService {
             stat_bonus: BaseStats::default(),
             id: character.id.clone(),
             name: character.name.clone(),
-            path: character.path.clone(),
-            attack_type: todo!(),
+            path: character
+                .path
+                .clone()
+                .ok_or_else(|| DataError::UnknownPath(character.id.clone()))?,
+            attack_type: self.attack_type(character)?,
             level: character.level,
             ascension: character.ascension,
             eidolon: character.eidolon,
-            skills: character.skills,
-            traces: character.traces,
+            skills: character.skills.clone(),
+            traces: character.traces.clone(),
         })
     }
 
+    /// The ascension and trace modules fetched from Hoyowiki don't give the combat type, so
+    /// characters can't be built from them alone and are skipped when loading a scan.
+    fn attack_type(&self, character: &RawCharacter) -> Result<AttackType> {
+        Err(DataError::UnknownCombatType(format!("of {} in Hoyowiki data", character.name)).into())
+    }
+
     fn calculate_light_cone_base_stats(
         &self,
         lo: &str,
@@ -345,6 +354,7 @@ impl HoyowikiDataFetcherService {
             base_def: calc_base_stat(def_lo, def_hi, lo)?,
             _light_cone: light_cone.clone(),
             config: LightConePassiveConfig::default(),
+            ignore_passive: false,
         })
     }
 
@@ -490,3 +500,62 @@ impl HoyowikiDataFetcherService {
         Ok(stat_bonus)
     }
 }
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod tests {
+    use super::*;
+    use crate::{
+        client::cache::DiskCache,
+        domain::{CharacterSkills, CharacterTraces},
+    };
+
+    #[test]
+    fn test_attack_type_is_a_data_error() {
+        let fetcher = HoyowikiDataFetcherService {
+            client: HoyowikiClient {
+                base_url: String::new(),
+                language: "en-us".to_string(),
+                wiki_app: "hsr".to_string(),
+                http: reqwest::Client::new(),
+                cache: DiskCache::default(),
+            },
+        };
+        let character = RawCharacter {
+            id: "1102".to_string(),
+            name: "Seele".to_string(),
+            path: Some(Path::TheHunt),
+            level: 80,
+            ascension: 6,
+            eidolon: 0,
+            skills: CharacterSkills {
+                basic: 6,
+                skill: 10,
+                ult: 10,
+                talent: 10,
+            },
+            traces: CharacterTraces {
+                ability_1: true,
+                ability_2: true,
+                ability_3: true,
+                stat_1: true,
+                stat_2: true,
+                stat_3: true,
+                stat_4: true,
+                stat_5: true,
+                stat_6: true,
+                stat_7: true,
+                stat_8: true,
+                stat_9: true,
+                stat_10: true,
+            },
+        };
+        let error = fetcher.attack_type(&character).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<DataError>(),
+            Some(&DataError::UnknownCombatType(
+                "of Seele in Hoyowiki data".to_string()
+            ))
+        );
+    }
+}
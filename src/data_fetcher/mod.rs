@@ -1,12 +1,43 @@
-use crate::domain::{RawCharacter, Character, LightCone, LightConeEntity};
+use crate::domain::{
+    AttackType, Character, DataError, LightCone, LightConeEntity, Path, RawCharacter,
+};
 use async_trait::async_trait;
 use eyre::Result;
 
 pub mod hoyowiki_data_fetcher;
 pub mod project_yatta_data_fetcher;
+pub mod star_rail_res_data_fetcher;
 
 #[async_trait]
 pub trait DataFetcher {
     async fn fetch_character_data(&self, character: &RawCharacter) -> Result<Character>;
     async fn fetch_light_cone_data(&mut self, light_cone: &LightCone) -> Result<LightConeEntity>;
 }
+
+/// Path of a character from its id in the game files, e.g. `Rogue` for The Hunt.
+pub fn path_from_id(id: &str) -> Result<Path, DataError> {
+    Ok(match id {
+        "Warrior" => Path::Destruction,
+        "Rogue" => Path::TheHunt,
+        "Mage" => Path::Erudition,
+        "Shaman" => Path::Harmony,
+        "Warlock" => Path::Nihility,
+        "Knight" => Path::Preservation,
+        "Priest" => Path::Abundance,
+        other => return Err(DataError::UnknownPath(other.to_string())),
+    })
+}
+
+/// Combat type of a character from its id in the game files, e.g. `Thunder` for Lightning.
+pub fn attack_type_from_id(id: &str) -> Result<AttackType, DataError> {
+    Ok(match id {
+        "Wind" => AttackType::Wind,
+        "Fire" => AttackType::Fire,
+        "Lightning" | "Thunder" => AttackType::Lightning,
+        "Ice" => AttackType::Ice,
+        "Physical" => AttackType::Physical,
+        "Quantum" => AttackType::Quantum,
+        "Imaginary" => AttackType::Imaginary,
+        other => return Err(DataError::UnknownCombatType(other.to_string())),
+    })
+}
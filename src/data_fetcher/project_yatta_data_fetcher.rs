@@ -1,14 +1,17 @@
-use super::DataFetcher;
+use super::{attack_type_from_id, path_from_id, DataFetcher};
 use crate::{
     client::project_yatta_client::{
-        CharacterUpgrade, LightConeUpgrade, ProjectYattaClient, Traces, Upgrade,
+        CharacterUpgrade, LightConeUpgrade, ProjectYattaClient, Traces, Types, Upgrade,
     },
     domain::{
-        BaseStats, Character, LightCone, LightConeEntity, LightConePassiveConfig, RawCharacter,
+        BaseStats, Character, DataError, LightCone, LightConeEntity, LightConePassiveConfig,
+        RawCharacter,
     },
+    utils::trace_title_mapper::title_mapper,
 };
 use async_trait::async_trait;
 use eyre::{eyre, Result};
+use tracing::warn;
 
 pub struct ProjectYattaDataFetcher {
     pub client: ProjectYattaClient,
@@ -53,36 +56,13 @@ impl ProjectYattaDataFetcher {
             )
         };
 
-        match subskill.name.as_str() {
-            "HP Boost" => base_stats.hp_percentage += extract()?,
-            "ATK Boost" => base_stats.atk_percentage += extract()?,
-            "DEF Boost" => base_stats.def_percentage += extract()?,
-            "SPD Boost" => base_stats.spd_percentage += extract()?,
-            "CRIT Rate Boost" => base_stats.crit_rate += extract()?,
-            "CRIT DMG Boost" => base_stats.crit_damage += extract()?,
-            "Effect RES Boost" => base_stats.effect_resistance += extract()?,
-            "Break Boost" | "Break Enhance" => base_stats.break_effect += extract()?,
-            "Energy Regeneration Boost" => base_stats.energy_regeneration_rate += extract()?,
-            "Effect Hit Rate Boost" => base_stats.effect_hit_rate += extract()?,
-            "DMG Boost" => {
-                base_stats.fire_damage_boost += extract()?;
-                base_stats.ice_damage_boost += extract()?;
-                base_stats.wind_damage_boost += extract()?;
-                base_stats.lightning_damage_boost += extract()?;
-                base_stats.physical_damage_boost += extract()?;
-                base_stats.quantum_damage_boost += extract()?;
-                base_stats.imaginary_damage_boost += extract()?;
-            }
-            "DMG Boost: Ice" => base_stats.ice_damage_boost += extract()?,
-            "DMG Boost: Fire" | "DMG Boost Fire" => base_stats.fire_damage_boost += extract()?,
-            "DMG Boost: Wind" => base_stats.wind_damage_boost += extract()?,
-            "DMG Boost: Lightning" => base_stats.lightning_damage_boost += extract()?,
-            "DMG Boost: Imaginary" => base_stats.imaginary_damage_boost += extract()?,
-            "DMG Boost: Quantum" => base_stats.quantum_damage_boost += extract()?,
-            "DMG Boost: Physical" => base_stats.physical_damage_boost += extract()?,
-            _ => todo!(),
+        let added = match title_mapper(&subskill.name) {
+            Ok(stat) => base_stats.add(&stat, extract()?),
+            Err(e) => Err(e),
         };
-
+        if let Err(e) = added {
+            warn!("Ignoring trace {id} of character {character_id}: {e}");
+        }
         Ok(())
     }
 
@@ -100,12 +80,12 @@ impl ProjectYattaDataFetcher {
         &self,
         character: &RawCharacter,
         upgrades: &[CharacterUpgrade],
-        combat_type: &str,
-    ) -> Character {
+        types: &Types,
+    ) -> Result<Character> {
         let upgrade = &upgrades[character.ascension as usize];
         let (hp, atk, def) = self.calculate_base_stats(upgrade, character.level);
 
-        Character {
+        Ok(Character {
             base_hp: hp,
             base_atk: atk,
             base_def: def,
@@ -116,26 +96,18 @@ impl ProjectYattaDataFetcher {
             stat_bonus: BaseStats::default(),
             id: character.id.clone(),
             name: character.name.clone(),
-            path: character.path.clone(),
-            attack_type: match combat_type {
-                "Wind" => crate::domain::AttackType::Wind,
-                "Fire" => crate::domain::AttackType::Fire,
-                "Lightning" | "Thunder" => crate::domain::AttackType::Lightning,
-                "Ice" => crate::domain::AttackType::Ice,
-                "Physical" => crate::domain::AttackType::Physical,
-                "Quantum" => crate::domain::AttackType::Quantum,
-                "Imaginary" => crate::domain::AttackType::Imaginary,
-                other => {
-                    println!("Missing {}", other);
-                    todo!()
-                }
+            path: match (&character.path, &types.path_type) {
+                (Some(path), _) => path.clone(),
+                (None, Some(path_type)) => path_from_id(&path_type.id)?,
+                (None, None) => return Err(DataError::UnknownPath(String::new()).into()),
             },
+            attack_type: attack_type_from_id(&types.combat_type.id)?,
             level: character.level,
             ascension: character.ascension,
             eidolon: character.eidolon,
             skills: character.skills.clone(),
             traces: character.traces.clone(),
-        }
+        })
     }
 
     fn calculate_light_cone_base_stats(
@@ -152,6 +124,7 @@ impl ProjectYattaDataFetcher {
             base_def: def,
             _light_cone: light_cone.clone(),
             config: LightConePassiveConfig::default(),
+            ignore_passive: false,
         }
     }
 }
@@ -163,8 +136,11 @@ impl DataFetcher for ProjectYattaDataFetcher {
         let mut character_entity = self.calculate_character_base_stats(
             character,
             &response.data.upgrade,
-            &response.data.types.combat_type.id,
-        );
+            &response.data.types,
+        )?;
+        if character_entity.name.is_empty() {
+            character_entity.name = response.data.name.clone();
+        }
         character_entity.stat_bonus =
             self.calculate_trace_bonus(character, &response.data.traces)?;
         Ok(character_entity)
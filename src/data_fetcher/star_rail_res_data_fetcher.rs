@@ -0,0 +1,364 @@
+use super::{attack_type_from_id, path_from_id, DataFetcher};
+use crate::{
+    character::ScalingTable,
+    domain::{
+        BaseStats, Character, LightCone, LightConeEntity, LightConePassiveConfig, RawCharacter,
+        Stats,
+    },
+    utils::trace_title_mapper::property_mapper,
+};
+use async_trait::async_trait;
+use eyre::{eyre, Context, Result};
+use serde::{de::DeserializeOwned, Deserialize};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+};
+use tracing::warn;
+
+/// English index of the StarRailRes database submodule.
+pub const STAR_RAIL_RES_PATH: &str = "database/index_new/en";
+
+#[derive(Deserialize, Debug)]
+struct CharacterEntry {
+    name: String,
+    path: String,
+    element: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct Promotion {
+    values: Vec<HashMap<String, PromotionValue>>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+struct PromotionValue {
+    base: f64,
+    step: f64,
+}
+
+#[derive(Deserialize, Debug)]
+struct SkillTree {
+    levels: Vec<SkillTreeLevel>,
+}
+
+#[derive(Deserialize, Debug)]
+struct SkillTreeLevel {
+    #[serde(default)]
+    properties: Vec<Property>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Property {
+    #[serde(rename = "type")]
+    property: String,
+    value: f64,
+}
+
+#[derive(Deserialize, Debug)]
+struct Skill {
+    name: String,
+    params: Vec<Vec<f64>>,
+}
+
+/// Parameters of an ability as listed in its description, each with a value per ability level.
+#[derive(Clone, Debug)]
+pub struct SkillScaling {
+    pub name: String,
+    pub params: Vec<ScalingTable>,
+}
+
+/// Reads character and light cone data from the StarRailRes database, without any network
+/// access. Ability multipliers stay in the character kits, `skill_scaling` gives the tables of
+/// the database to check them against.
+pub struct StarRailResDataFetcher {
+    path: PathBuf,
+    characters: HashMap<String, CharacterEntry>,
+    character_promotions: HashMap<String, Promotion>,
+    skill_trees: HashMap<String, SkillTree>,
+    light_cone_promotions: HashMap<String, Promotion>,
+}
+
+fn read<T: DeserializeOwned>(path: &Path, file: &str) -> Result<T> {
+    let file_path = path.join(file);
+    let reader = BufReader::new(
+        File::open(&file_path).wrap_err_with(|| format!("Can't open {}", file_path.display()))?,
+    );
+    serde_json::from_reader(reader).wrap_err_with(|| format!("Invalid {}", file_path.display()))
+}
+
+impl StarRailResDataFetcher {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        Ok(Self {
+            path: path.to_path_buf(),
+            characters: read(path, "characters.json")?,
+            character_promotions: read(path, "character_promotions.json")?,
+            skill_trees: read(path, "character_skill_trees.json")?,
+            light_cone_promotions: read(path, "light_cone_promotions.json")?,
+        })
+    }
+
+    /// Whether the database has been checked out at `path`.
+    pub fn available(path: impl AsRef<Path>) -> bool {
+        path.as_ref().join("characters.json").is_file()
+    }
+
+    /// Parameters of an ability, `skill_id` being e.g. `130803` for the ultimate of Acheron.
+    pub fn skill_scaling(&self, skill_id: &str) -> Result<SkillScaling> {
+        let mut skills: HashMap<String, Skill> = read(&self.path, "character_skills.json")?;
+        let skill = skills
+            .remove(skill_id)
+            .ok_or_else(|| eyre!("Skill {skill_id} not found"))?;
+        let count = skill.params.iter().map(Vec::len).max().unwrap_or_default();
+        let params = (0..count)
+            .map(|param| {
+                ScalingTable(
+                    skill
+                        .params
+                        .iter()
+                        .map(|level| level.get(param).cloned().unwrap_or_default())
+                        .collect(),
+                )
+            })
+            .collect();
+        Ok(SkillScaling {
+            name: skill.name,
+            params,
+        })
+    }
+
+    fn promotion<'a>(
+        promotions: &'a HashMap<String, Promotion>,
+        id: &str,
+        ascension: u8,
+    ) -> Result<&'a HashMap<String, PromotionValue>> {
+        promotions
+            .get(id)
+            .ok_or_else(|| eyre!("No promotion data for {id}"))?
+            .values
+            .get(ascension as usize)
+            .ok_or_else(|| eyre!("No ascension {ascension} for {id}"))
+    }
+
+    /// Value of a stat at the level, level 0 counting as level 1.
+    fn stat(values: &HashMap<String, PromotionValue>, stat: &str, level: u8) -> f64 {
+        values
+            .get(stat)
+            .map(|value| value.base + value.step * level.saturating_sub(1) as f64)
+            .unwrap_or_default()
+    }
+
+    fn calculate_trace_bonus(&self, character: &RawCharacter) -> Result<BaseStats> {
+        let mut base_stats = BaseStats::default();
+        for i in 1..=10 {
+            if !character.traces.get_stat(i) {
+                continue;
+            }
+            let id = format!("{}{}", character.id, 200 + i);
+            let properties = self
+                .skill_trees
+                .get(&id)
+                .and_then(|tree| tree.levels.first())
+                .map(|level| &level.properties)
+                .ok_or_else(|| eyre!("Trace {id} not found"))?;
+            for property in properties {
+                let added = property_mapper(&property.property).and_then(|stat| {
+                    let value = match stat {
+                        Stats::Hp | Stats::Atk | Stats::Def | Stats::Spd => property.value,
+                        _ => property.value * 100.0,
+                    };
+                    base_stats.add(&stat, value)
+                });
+                if let Err(e) = added {
+                    warn!("Ignoring trace {id} of character {}: {e}", character.id);
+                }
+            }
+        }
+        Ok(base_stats)
+    }
+}
+
+#[async_trait]
+impl DataFetcher for StarRailResDataFetcher {
+    async fn fetch_character_data(&self, character: &RawCharacter) -> Result<Character> {
+        let entry = self
+            .characters
+            .get(&character.id)
+            .ok_or_else(|| eyre!("Character {} not found", character.id))?;
+        let values = Self::promotion(
+            &self.character_promotions,
+            &character.id,
+            character.ascension,
+        )?;
+        let base = |stat: &str| values.get(stat).map(|v| v.base).unwrap_or_default();
+        Ok(Character {
+            id: character.id.clone(),
+            name: if character.name.is_empty() {
+                entry.name.clone()
+            } else {
+                character.name.clone()
+            },
+            path: match &character.path {
+                Some(path) => path.clone(),
+                None => path_from_id(&entry.path)?,
+            },
+            attack_type: attack_type_from_id(&entry.element)?,
+            level: character.level,
+            ascension: character.ascension,
+            eidolon: character.eidolon,
+            skills: character.skills.clone(),
+            traces: character.traces.clone(),
+            base_hp: Self::stat(values, "hp", character.level),
+            base_atk: Self::stat(values, "atk", character.level),
+            base_def: Self::stat(values, "def", character.level),
+            base_spd: base("spd"),
+            base_aggro: base("taunt") as u64,
+            critical_chance: base("crit_rate") * 100.0,
+            critical_damage: base("crit_dmg") * 100.0,
+            stat_bonus: self.calculate_trace_bonus(character)?,
+        })
+    }
+
+    async fn fetch_light_cone_data(&mut self, light_cone: &LightCone) -> Result<LightConeEntity> {
+        let values = Self::promotion(
+            &self.light_cone_promotions,
+            &light_cone.id,
+            light_cone.ascension,
+        )?;
+        Ok(LightConeEntity {
+            base_hp: Self::stat(values, "hp", light_cone.level),
+            base_atk: Self::stat(values, "atk", light_cone.level),
+            base_def: Self::stat(values, "def", light_cone.level),
+            _light_cone: light_cone.clone(),
+            config: LightConePassiveConfig::default(),
+            ignore_passive: false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{CharacterSkills, CharacterTraces, Path as CharacterPath};
+    use serde_json::json;
+    use std::fs;
+
+    fn write(dir: &Path, file: &str, value: serde_json::Value) -> Result<()> {
+        Ok(fs::write(dir.join(file), value.to_string())?)
+    }
+
+    #[tokio::test]
+    async fn test_star_rail_res_character() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("star_rail_res_{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        write(
+            &dir,
+            "characters.json",
+            json!({ "1308": { "id": "1308", "name": "Acheron", "path": "Warlock", "element": "Thunder" } }),
+        )?;
+        let stat = |base: f64, step: f64| json!({ "base": base, "step": step });
+        write(
+            &dir,
+            "character_promotions.json",
+            json!({ "1308": { "id": "1308", "values": [{}, {}, {}, {}, {}, {}, {
+                "hp": stat(614.592, 8.64), "atk": stat(324.72, 4.752), "def": stat(240.24, 3.96),
+                "spd": stat(101.0, 0.0), "taunt": stat(100.0, 0.0),
+                "crit_rate": stat(0.05, 0.0), "crit_dmg": stat(0.5, 0.0),
+            }] } }),
+        )?;
+        write(
+            &dir,
+            "character_skill_trees.json",
+            json!({
+                "1308201": { "levels": [{ "properties": [{ "type": "AttackAddedRatio", "value": 0.04 }] }] },
+                "1308202": { "levels": [{ "properties": [{ "type": "ThunderAddedRatio", "value": 0.032 }] }] },
+            }),
+        )?;
+        write(
+            &dir,
+            "light_cone_promotions.json",
+            json!({ "23024": { "values": [{}, {}, {}, {}, {}, {}, {
+                "hp": stat(120.96, 18.0), "atk": stat(72.576, 10.8), "def": stat(50.4, 7.5),
+            }] } }),
+        )?;
+        write(
+            &dir,
+            "character_skills.json",
+            json!({ "130803": { "name": "Slashed Dream Cries in Red", "params": [[0.144, 0.9], [0.1536, 0.96]] } }),
+        )?;
+
+        let mut fetcher = StarRailResDataFetcher::load(&dir)?;
+        let character = fetcher
+            .fetch_character_data(&RawCharacter {
+                id: "1308".to_string(),
+                name: String::new(),
+                path: None,
+                level: 80,
+                ascension: 6,
+                eidolon: 0,
+                skills: CharacterSkills {
+                    basic: 6,
+                    skill: 10,
+                    ult: 10,
+                    talent: 10,
+                },
+                traces: CharacterTraces {
+                    ability_1: true,
+                    ability_2: true,
+                    ability_3: true,
+                    stat_1: true,
+                    stat_2: true,
+                    stat_3: false,
+                    stat_4: false,
+                    stat_5: false,
+                    stat_6: false,
+                    stat_7: false,
+                    stat_8: false,
+                    stat_9: false,
+                    stat_10: false,
+                },
+            })
+            .await?;
+        let light_cone = fetcher
+            .fetch_light_cone_data(&LightCone {
+                id: "23024".to_string(),
+                name: String::new(),
+                level: 80,
+                ascension: 6,
+                superimposition: 1,
+                location: None,
+                lock: false,
+                _uid: "light_cone_1".to_string(),
+            })
+            .await?;
+        let ultimate = fetcher.skill_scaling("130803")?;
+        fs::remove_dir_all(&dir)?;
+
+        assert_eq!(character.name, "Acheron");
+        assert_eq!(character.path, CharacterPath::Nihility);
+        assert!((character.base_atk - 700.128).abs() < 1e-9);
+        assert_eq!(character.base_aggro, 100);
+        assert_eq!(character.critical_damage, 50.0);
+        assert_eq!(character.stat_bonus.atk_percentage, 4.0);
+        assert!((character.stat_bonus.lightning_damage_boost - 3.2).abs() < 1e-9);
+        assert!((light_cone.base_atk - 925.776).abs() < 1e-9);
+        assert_eq!(ultimate.params[1].0, vec![0.9, 0.96]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_stat_at_level_0() {
+        let values = HashMap::from([(
+            "hp".to_string(),
+            PromotionValue {
+                base: 100.0,
+                step: 5.0,
+            },
+        )]);
+        assert_eq!(StarRailResDataFetcher::stat(&values, "hp", 0), 100.0);
+        assert_eq!(StarRailResDataFetcher::stat(&values, "hp", 3), 110.0);
+    }
+}
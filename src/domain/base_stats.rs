@@ -1,3 +1,5 @@
+use super::{DataError, Stats};
+
 #[derive(Clone, Debug, Default)]
 pub struct BaseStats {
     pub hp: f64,
@@ -32,3 +34,44 @@ pub struct BaseStats {
     pub quantum_resistance: f64,
     pub imaginary_resistance: f64,
 }
+
+impl BaseStats {
+    /// Adds a bonus granted by a trace, a generic DMG boost applying to every element.
+    pub fn add(&mut self, stat: &Stats, value: f64) -> Result<(), DataError> {
+        match stat {
+            Stats::Hp => self.hp += value,
+            Stats::Hp_ => self.hp_percentage += value,
+            Stats::Atk => self.atk += value,
+            Stats::Atk_ => self.atk_percentage += value,
+            Stats::Def => self.def += value,
+            Stats::Def_ => self.def_percentage += value,
+            Stats::Spd => self.spd += value,
+            Stats::Spd_ => self.spd_percentage += value,
+            Stats::CritRate_ => self.crit_rate += value,
+            Stats::CritDmg_ => self.crit_damage += value,
+            Stats::EnergyRegenerationRate_ => self.energy_regeneration_rate += value,
+            Stats::BreakEffect_ => self.break_effect += value,
+            Stats::EffectRes_ => self.effect_resistance += value,
+            Stats::EffectHitRate_ => self.effect_hit_rate += value,
+            Stats::OutgoingHealingBoost_ => self.ougoing_healing_boost += value,
+            Stats::LightningDmgBoost_ => self.lightning_damage_boost += value,
+            Stats::IceDmgBoost_ => self.ice_damage_boost += value,
+            Stats::FireDmgBoost_ => self.fire_damage_boost += value,
+            Stats::WindDmgBoost_ => self.wind_damage_boost += value,
+            Stats::PhysicalDmgBoost_ => self.physical_damage_boost += value,
+            Stats::QuantumDmgBoost_ => self.quantum_damage_boost += value,
+            Stats::ImaginaryDmgBoost_ => self.imaginary_damage_boost += value,
+            Stats::DmgBoost_ => {
+                self.lightning_damage_boost += value;
+                self.ice_damage_boost += value;
+                self.fire_damage_boost += value;
+                self.wind_damage_boost += value;
+                self.physical_damage_boost += value;
+                self.quantum_damage_boost += value;
+                self.imaginary_damage_boost += value;
+            }
+            other => return Err(DataError::UnsupportedTraceBonus(other.clone())),
+        }
+        Ok(())
+    }
+}
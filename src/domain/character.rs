@@ -26,7 +26,9 @@ pub struct Character {
 pub struct RawCharacter {
     pub id: String,
     pub name: String,
-    pub path: Path,
+    /// Missing from exports that only store the character ID, in which case it is fetched along
+    /// with the character data.
+    pub path: Option<Path>,
     pub level: u8,
     pub ascension: u8,
     pub eidolon: u8,
@@ -95,7 +97,8 @@ impl CharacterTraces {
     }
 }
 
-#[derive(PartialEq, Eq, Clone, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum AttackType {
     Lightning,
     Physical,
@@ -106,7 +109,8 @@ pub enum AttackType {
     Quantum,
 }
 
-#[derive(PartialEq, Eq)]
+#[derive(Deserialize, PartialEq, Eq, Clone, Debug)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum SkillType {
     BasicAttack,
     Skill,
@@ -114,7 +118,8 @@ pub enum SkillType {
     FollowUpAttack,
 }
 
-#[derive(PartialEq, Eq)]
+#[derive(Deserialize, PartialEq, Eq, Clone, Debug)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum DamageType {
     Normal,
     RealDamage,
@@ -122,9 +127,21 @@ pub enum DamageType {
     DamageOnTime,
     BreakDamage,
     SuperBreakDamage,
+    /// HP restored to an ally.
+    Heal,
+    /// DMG a shield given to an ally absorbs.
+    Shield,
+}
+
+impl DamageType {
+    /// Heals and shields, which are scored like damage but never hit the enemy.
+    pub fn is_sustain(&self) -> bool {
+        matches!(self, DamageType::Heal | DamageType::Shield)
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, Copy)]
+#[serde(rename_all = "kebab-case")]
 pub enum CritEnum {
     Crit,
     NoCrit,
@@ -1,10 +1,278 @@
-#[derive(Clone)]
+use super::{enemy_presets, AttackType};
+use eyre::{bail, Report, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default, deny_unknown_fields)]
 pub struct Enemy {
     pub level: u8,
+    /// Maximum HP, capping the DMG of bleed when given.
+    pub hp: f64,
+    /// Elite enemies and bosses, whose bleed is capped lower.
+    pub elite: bool,
+    /// RES in percent to the elements missing from `resistances` the enemy is not weak to.
     pub resistance: f64,
+    /// RES in percent to specific elements.
+    pub resistances: HashMap<AttackType, f64>,
+    /// Elements the enemy is weak to, with no RES unless given in `resistances`.
+    pub weaknesses: Vec<AttackType>,
+    /// Maximum toughness, before the 1/3 scaling of the toughness bar.
+    pub toughness: f64,
     pub def_bonus: f64,
     pub dmg_mitigation: Vec<f64>,
     pub vulnerability: f64,
     pub toughness_break: bool,
     pub weaken: f64,
+    /// Number of debuffs on the enemy, read by kit options named `enemy_debuffs` when given.
+    pub debuffs: Option<u32>,
+}
+
+impl Default for Enemy {
+    fn default() -> Self {
+        Self {
+            level: 80,
+            hp: 0.0,
+            elite: false,
+            resistance: 0.0,
+            resistances: HashMap::new(),
+            weaknesses: vec![],
+            toughness: 0.0,
+            def_bonus: 0.0,
+            dmg_mitigation: vec![],
+            vulnerability: 0.0,
+            toughness_break: false,
+            weaken: 0.0,
+            debuffs: None,
+        }
+    }
+}
+
+impl Enemy {
+    /// RES in percent to DMG of the element.
+    pub fn resistance_to(&self, element: &AttackType) -> f64 {
+        match self.resistances.get(element) {
+            Some(resistance) => *resistance,
+            None if self.weaknesses.contains(element) => 0.0,
+            None => self.resistance,
+        }
+    }
+
+    /// Adds weaknesses the enemy doesn't have yet, e.g. implanted ones.
+    pub fn add_weaknesses(&mut self, weaknesses: &[AttackType]) {
+        for weakness in weaknesses {
+            if !self.weaknesses.contains(weakness) {
+                self.weaknesses.push(weakness.clone());
+            }
+        }
+    }
+}
+
+/// The enemies of a fight, lined up as in game, and the one targeted by single target abilities.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(try_from = "EncounterSpec")]
+pub struct Encounter {
+    pub enemies: Vec<Enemy>,
+    /// Index of the main target in `enemies`.
+    pub target: usize,
+}
+
+/// An encounter as written in a scenario: a wave preset, a list of enemies, or a single enemy
+/// being enough for single target fights.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum EncounterSpec {
+    Wave(WaveSpec),
+    Enemies {
+        enemies: Vec<EnemySpec>,
+        #[serde(default)]
+        target: usize,
+    },
+    Single(EnemySpec),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WaveSpec {
+    wave: String,
+    /// Overrides the main target of the preset.
+    target: Option<usize>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum EnemySpec {
+    Preset(PresetSpec),
+    Enemy(Enemy),
+}
+
+/// An enemy preset with implanted weaknesses and its battle state.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PresetSpec {
+    preset: String,
+    #[serde(default)]
+    weaknesses: Vec<AttackType>,
+    #[serde(default)]
+    vulnerability: f64,
+    #[serde(default)]
+    toughness_break: bool,
+    #[serde(default)]
+    weaken: f64,
+    debuffs: Option<u32>,
+}
+
+impl TryFrom<EnemySpec> for Enemy {
+    type Error = Report;
+
+    fn try_from(spec: EnemySpec) -> Result<Self> {
+        match spec {
+            EnemySpec::Preset(spec) => {
+                let mut enemy = Self {
+                    vulnerability: spec.vulnerability,
+                    toughness_break: spec.toughness_break,
+                    weaken: spec.weaken,
+                    debuffs: spec.debuffs,
+                    ..enemy_presets()?.enemy(&spec.preset)?
+                };
+                enemy.add_weaknesses(&spec.weaknesses);
+                Ok(enemy)
+            }
+            EnemySpec::Enemy(enemy) => Ok(enemy),
+        }
+    }
+}
+
+impl TryFrom<EncounterSpec> for Encounter {
+    type Error = Report;
+
+    fn try_from(spec: EncounterSpec) -> Result<Self> {
+        let encounter = match spec {
+            EncounterSpec::Wave(spec) => {
+                let wave = enemy_presets()?.wave(&spec.wave)?;
+                Self {
+                    target: spec.target.unwrap_or(wave.target),
+                    ..wave
+                }
+            }
+            EncounterSpec::Enemies { enemies, target } => Self {
+                enemies: enemies
+                    .into_iter()
+                    .map(Enemy::try_from)
+                    .collect::<Result<_>>()?,
+                target,
+            },
+            EncounterSpec::Single(enemy) => Self::single(enemy.try_into()?),
+        };
+        encounter.check()?;
+        Ok(encounter)
+    }
+}
+
+impl Default for Encounter {
+    fn default() -> Self {
+        Self::single(Enemy::default())
+    }
+}
+
+impl Encounter {
+    pub fn single(enemy: Enemy) -> Self {
+        Self {
+            enemies: vec![enemy],
+            target: 0,
+        }
+    }
+
+    /// `count` copies of the enemy, the main target being the one in the middle.
+    pub fn copies(enemy: Enemy, count: usize) -> Self {
+        Self {
+            enemies: vec![enemy; count.max(1)],
+            target: count.max(1) / 2,
+        }
+    }
+
+    /// Makes sure there is an enemy to target.
+    pub fn check(&self) -> Result<()> {
+        if self.target >= self.enemies.len() {
+            bail!(
+                "Target {} is out of the {} enemies of the encounter",
+                self.target,
+                self.enemies.len()
+            );
+        }
+        Ok(())
+    }
+
+    pub fn main_target(&self) -> &Enemy {
+        &self.enemies[self.target.min(self.enemies.len() - 1)]
+    }
+
+    /// Indices of the enemies next to the main target, hit by blast abilities.
+    pub fn adjacent_targets(&self) -> Vec<usize> {
+        let target = self.target.min(self.enemies.len() - 1);
+        (0..self.enemies.len())
+            .filter(|i| i.abs_diff(target) == 1)
+            .collect()
+    }
+
+    /// The encounter reduced to its main target.
+    pub fn main_only(&self) -> Self {
+        Self::single(self.main_target().clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encounter() -> Result<()> {
+        let single: Encounter = serde_yaml::from_str("level: 95\nresistance: 20")?;
+        assert_eq!(single.enemies.len(), 1);
+        assert_eq!(single.main_target().level, 95);
+        assert!(single.adjacent_targets().is_empty());
+
+        let encounter: Encounter = serde_yaml::from_str(
+            "enemies:\n- {}\n- weaknesses: [LIGHTNING]\n  resistance: 20\n- resistance: 20\ntarget: 1",
+        )?;
+        encounter.check()?;
+        assert_eq!(encounter.adjacent_targets(), vec![0, 2]);
+        let main = encounter.main_target();
+        assert_eq!(main.resistance_to(&AttackType::Lightning), 0.0);
+        assert_eq!(main.resistance_to(&AttackType::Fire), 20.0);
+        assert_eq!(Encounter::copies(Enemy::default(), 3).target, 1);
+        assert!(Encounter {
+            enemies: vec![],
+            target: 0
+        }
+        .check()
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_encounter_presets() -> Result<()> {
+        let wave: Encounter = serde_yaml::from_str("wave: moc_boss_wave\ntarget: 0")?;
+        assert_eq!(wave.enemies.len(), 3);
+        assert_eq!(wave.main_target().level, 95);
+        assert_eq!(wave.target, 0);
+
+        let encounter: Encounter = serde_yaml::from_str(
+            "enemies:\n- preset: voidranger_reaver\n- preset: voidranger_trampler\n  weaknesses: [QUANTUM, FIRE]\n  toughness_break: true\n- level: 90\ntarget: 1",
+        )?;
+        let main = encounter.main_target();
+        assert_eq!(main.weaknesses.len(), 4);
+        assert_eq!(main.resistance_to(&AttackType::Quantum), 0.0);
+        assert_eq!(main.resistance_to(&AttackType::Fire), 0.0);
+        assert_eq!(main.resistance_to(&AttackType::Ice), 20.0);
+        assert!(main.toughness_break);
+        assert_eq!(encounter.enemies[2].level, 90);
+
+        assert!(serde_yaml::from_str::<Encounter>("enemies: []").is_err());
+        assert!(serde_yaml::from_str::<Encounter>("wave: moc_boss_wave\ntarget: 3").is_err());
+
+        let unknown = serde_yaml::from_str::<Encounter>("preset: cocolya").unwrap_err();
+        assert!(unknown.to_string().contains("Unknown enemy preset cocolya"));
+        Ok(())
+    }
 }
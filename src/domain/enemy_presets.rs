@@ -0,0 +1,105 @@
+use super::{Encounter, Enemy};
+use eyre::{bail, eyre, Context, Result};
+use itertools::Itertools;
+use serde::Deserialize;
+use std::{collections::HashMap, sync::OnceLock};
+
+/// Enemy and wave presets, see the header of the file for the format.
+const ENEMY_PRESETS_YAML: &str = include_str!("../config/enemy_presets.yaml");
+
+static ENEMY_PRESETS: OnceLock<Result<EnemyPresets, String>> = OnceLock::new();
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct EnemyPresets {
+    pub enemies: HashMap<String, Enemy>,
+    pub waves: HashMap<String, Wave>,
+}
+
+/// Enemy presets lined up as in game.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Wave {
+    pub enemies: Vec<String>,
+    #[serde(default)]
+    pub target: usize,
+}
+
+/// All built-in enemy and wave presets.
+pub fn enemy_presets() -> Result<&'static EnemyPresets> {
+    ENEMY_PRESETS
+        .get_or_init(|| parse_enemy_presets(ENEMY_PRESETS_YAML).map_err(|e| format!("{e:#}")))
+        .as_ref()
+        .map_err(|e| eyre!("Invalid enemy preset data: {e}"))
+}
+
+fn parse_enemy_presets(yaml: &str) -> Result<EnemyPresets> {
+    let presets: EnemyPresets = serde_yaml::from_str(yaml)?;
+    for name in presets.waves.keys() {
+        presets
+            .wave(name)?
+            .check()
+            .wrap_err_with(|| format!("Wave {name}"))?;
+    }
+    Ok(presets)
+}
+
+impl EnemyPresets {
+    pub fn enemy(&self, name: &str) -> Result<Enemy> {
+        match self.enemies.get(name) {
+            Some(enemy) => Ok(enemy.clone()),
+            None => bail!(
+                "Unknown enemy preset {name}, expected one of: {}",
+                self.enemies.keys().sorted().join(", ")
+            ),
+        }
+    }
+
+    pub fn wave(&self, name: &str) -> Result<Encounter> {
+        let wave = self.waves.get(name).ok_or_else(|| {
+            eyre!(
+                "Unknown wave preset {name}, expected one of: {}",
+                self.waves.keys().sorted().join(", ")
+            )
+        })?;
+        Ok(Encounter {
+            enemies: wave
+                .enemies
+                .iter()
+                .map(|enemy| self.enemy(enemy))
+                .collect::<Result<_>>()?,
+            target: wave.target,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::AttackType;
+
+    #[test]
+    fn test_enemy_presets_load() -> Result<()> {
+        let presets = enemy_presets()?;
+        let boss = presets.enemy("phantylia")?;
+        assert_eq!(boss.level, 95);
+        assert!(boss.elite);
+        assert_eq!(
+            boss.weaknesses,
+            vec![AttackType::Fire, AttackType::Ice, AttackType::Imaginary]
+        );
+        assert_eq!(boss.resistance_to(&AttackType::Lightning), 40.0);
+        assert_eq!(boss.resistance_to(&AttackType::Ice), 0.0);
+        assert_eq!(boss.resistance_to(&AttackType::Quantum), 20.0);
+        assert_eq!(boss.def_bonus, 0.0);
+        assert_eq!(boss.dmg_mitigation, vec![20.0]);
+
+        let wave = presets.wave("pure_fiction_wave")?;
+        assert_eq!(wave.enemies.len(), 5);
+        assert_eq!(wave.main_target().toughness, 300.0);
+
+        let unknown = presets.enemy("cocolya").unwrap_err().to_string();
+        assert!(unknown.contains("expected one of: cocolia, phantylia"));
+        Ok(())
+    }
+}
@@ -0,0 +1,22 @@
+use super::Stats;
+use thiserror::Error;
+
+/// Game data missing from this version of the calculator, usually introduced by a newer patch.
+/// Callers loading a scan warn about these and skip the item instead of aborting.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum DataError {
+    #[error("Light cone {id} ({name}) has no implemented passive")]
+    UnknownLightCone { id: String, name: String },
+    #[error("Unknown trace {0}")]
+    UnknownTrace(String),
+    #[error("{0:?} can't be granted by a trace")]
+    UnsupportedTraceBonus(Stats),
+    #[error("{0:?} is not a relic substat")]
+    UnknownSubstat(Stats),
+    #[error("Relic {0} has no known slot")]
+    UnknownSlot(String),
+    #[error("Unknown combat type {0}")]
+    UnknownCombatType(String),
+    #[error("Unknown path {0}")]
+    UnknownPath(String),
+}
@@ -0,0 +1,262 @@
+use super::{relic_sets, RawRelic, Relic, ScannerInput, Slot, Stats, FRIBBELS_STATS};
+use eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+/// Format a build is exported in.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExportFormat {
+    /// The HSR-Scanner export the build comes from.
+    #[default]
+    Scanner,
+    /// A Fribbels optimizer save file.
+    Fribbels,
+}
+
+/// Where each relic ends up once the build is equipped on the character, keyed by UID, `None`
+/// meaning unequipped. As in game, the relic a build piece replaces goes to the character that
+/// was wearing the piece. Relics that don't move are left out.
+pub fn reassign<'a>(
+    relics: impl IntoIterator<Item = (&'a str, &'a Slot, Option<&'a str>)>,
+    character_id: &str,
+    build: &[Relic],
+) -> HashMap<String, Option<String>> {
+    let relics: Vec<_> = relics.into_iter().collect();
+    let mut locations = HashMap::new();
+    for piece in build {
+        let Some((_, _, owner)) = relics.iter().find(|(uid, _, _)| *uid == piece.uid) else {
+            continue;
+        };
+        if *owner == Some(character_id) {
+            continue;
+        }
+        locations.insert(piece.uid.clone(), Some(character_id.to_string()));
+        let replaced = relics.iter().find(|(uid, slot, location)| {
+            **slot == piece.slot && *location == Some(character_id) && *uid != piece.uid
+        });
+        if let Some((uid, _, _)) = replaced {
+            locations.insert(uid.to_string(), owner.map(str::to_string));
+        }
+    }
+    locations
+}
+
+/// Equips the build in an HSR-Scanner export, leaving everything else untouched. Returns the
+/// number of relics that moved.
+pub fn equip_in_scan(scan: &mut Value, character_id: &str, build: &[Relic]) -> Result<usize> {
+    let relics = scan
+        .get_mut("relics")
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| eyre!("The scan has no relic list"))?;
+    let locations = {
+        let entries = relics
+            .iter()
+            .map(|relic| {
+                let uid = relic.get("_uid").and_then(Value::as_str);
+                let slot = relic
+                    .get("slot")
+                    .cloned()
+                    .map(serde_json::from_value::<Slot>);
+                let location = relic
+                    .get("location")
+                    .and_then(Value::as_str)
+                    .filter(|location| !location.is_empty());
+                match (uid, slot) {
+                    (Some(uid), Some(Ok(slot))) => Ok((uid, slot, location)),
+                    _ => Err(eyre!("Relic without a valid `_uid` or `slot` in the scan")),
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+        reassign(
+            entries
+                .iter()
+                .map(|(uid, slot, location)| (*uid, slot, *location)),
+            character_id,
+            build,
+        )
+    };
+    for relic in relics.iter_mut() {
+        let uid = relic
+            .get("_uid")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        if let Some(location) = locations.get(uid) {
+            relic["location"] = Value::String(location.clone().unwrap_or_default());
+        }
+    }
+    Ok(locations.len())
+}
+
+/// Location of a relic in a scan, HSR-Scanner writing unequipped relics with an empty one.
+fn location(relic: &RawRelic) -> Option<&str> {
+    relic
+        .location
+        .as_deref()
+        .filter(|location| !location.is_empty())
+}
+
+fn fribbels_stat_name(stat: &Stats) -> Result<&'static str> {
+    FRIBBELS_STATS
+        .iter()
+        .find(|(_, s)| s == stat)
+        .map(|(name, _)| *name)
+        .ok_or_else(|| eyre!("{stat:?} has no Fribbels optimizer name"))
+}
+
+/// The scan as a Fribbels optimizer save file, with the build equipped on the character.
+pub fn to_fribbels(input: &ScannerInput, character_id: &str, build: &[Relic]) -> Result<Value> {
+    let sets = relic_sets()?;
+    let locations = reassign(
+        input
+            .relics
+            .iter()
+            .map(|relic| (relic._uid.as_str(), &relic.slot, location(relic))),
+        character_id,
+        build,
+    );
+    let mut equipped: HashMap<String, Map<String, Value>> = HashMap::new();
+    let relics = input
+        .relics
+        .iter()
+        .map(|relic| {
+            let location = match locations.get(&relic._uid) {
+                Some(location) => location.clone(),
+                None => location(relic).map(str::to_string),
+            };
+            if let Some(location) = &location {
+                equipped.entry(location.clone()).or_default().insert(
+                    format!("{:?}", relic.slot),
+                    Value::String(relic._uid.clone()),
+                );
+            }
+            let substats = relic
+                .substats
+                .iter()
+                .map(|substat| {
+                    Ok(json!({
+                        "stat": fribbels_stat_name(&substat.key)?,
+                        "value": substat.value,
+                    }))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Ok(json!({
+                "id": relic._uid,
+                "part": format!("{:?}", relic.slot),
+                "set": sets.get(&relic.set_id).map(|set| &set.name).unwrap_or(&relic.name),
+                "grade": relic.rarity,
+                "enhance": relic.level,
+                "main": {
+                    "stat": fribbels_stat_name(&relic.mainstat)?,
+                    "value": relic.get_mainstat()?,
+                },
+                "substats": substats,
+                "equippedBy": location,
+            }))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let characters: Vec<_> = input
+        .characters
+        .iter()
+        .map(|character| {
+            let light_cone = input
+                .light_cones
+                .iter()
+                .find(|lc| lc.location.as_deref() == Some(character.id.as_str()));
+            let mut form = json!({
+                "characterId": character.id,
+                "characterLevel": character.level,
+                "characterEidolon": character.eidolon,
+            });
+            if let Some(light_cone) = light_cone {
+                form["lightCone"] = json!(light_cone.id);
+                form["lightConeLevel"] = json!(light_cone.level);
+                form["lightConeSuperimposition"] = json!(light_cone.superimposition);
+            }
+            json!({
+                "id": character.id,
+                "form": form,
+                "equipped": equipped.remove(&character.id).unwrap_or_default(),
+            })
+        })
+        .collect();
+    Ok(json!({ "relics": relics, "characters": characters }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::import_scan;
+
+    fn piece(uid: &str, slot: Slot) -> Relic {
+        Relic {
+            uid: uid.to_string(),
+            slot,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_reassign_swaps_with_previous_owner() {
+        let relics = [
+            ("a", &Slot::Head, Some("1308")),
+            ("b", &Slot::Head, Some("1306")),
+            ("c", &Slot::Feet, None),
+            ("d", &Slot::Feet, Some("1308")),
+            ("e", &Slot::Body, Some("1308")),
+        ];
+        let build = [
+            piece("b", Slot::Head),
+            piece("c", Slot::Feet),
+            piece("e", Slot::Body),
+        ];
+        let locations = reassign(relics, "1308", &build);
+        assert_eq!(
+            locations,
+            HashMap::from([
+                ("b".to_string(), Some("1308".to_string())),
+                ("a".to_string(), Some("1306".to_string())),
+                ("c".to_string(), Some("1308".to_string())),
+                ("d".to_string(), None),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_exports_round_trip() -> Result<()> {
+        let mut scan = json!({
+            "source": "HSR-Scanner",
+            "version": 4,
+            "light_cones": [],
+            "characters": [],
+            "relics": [
+                {
+                    "set_id": "102", "name": "Musketeer's Wild Wheat Felt Hat", "slot": "Head",
+                    "rarity": 5, "level": 15, "mainstat": "HP",
+                    "substats": [{ "key": "CRIT Rate_", "value": 3.24 }],
+                    "location": "1308", "lock": true, "discard": false, "_uid": "relic_1",
+                },
+                {
+                    "set_id": "102", "name": "Musketeer's Wild Wheat Felt Hat", "slot": "Head",
+                    "rarity": 5, "level": 15, "mainstat": "HP",
+                    "substats": [{ "key": "CRIT DMG_", "value": 6.48 }],
+                    "location": "", "lock": false, "discard": false, "_uid": "relic_2",
+                },
+            ],
+        });
+        let build = [piece("relic_2", Slot::Head)];
+        let input = import_scan(scan.clone())?;
+        assert_eq!(equip_in_scan(&mut scan, "1308", &build)?, 2);
+        assert_eq!(scan["relics"][0]["location"], json!(""));
+        assert_eq!(scan["relics"][1]["location"], json!("1308"));
+        assert_eq!(scan["relics"][1]["lock"], json!(false));
+
+        let fribbels = import_scan(to_fribbels(&input, "1308", &build)?)?;
+        assert_eq!(fribbels.relics[0].location, None);
+        assert_eq!(fribbels.relics[1].location.as_deref(), Some("1308"));
+        assert_eq!(fribbels.relics[1].set_id, "102");
+        assert_eq!(fribbels.relics[1].substats[0].value, 6.48);
+        Ok(())
+    }
+}
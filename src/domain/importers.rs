@@ -0,0 +1,375 @@
+use super::{
+    relic_sets, CharacterSkills, CharacterTraces, LightCone, RawCharacter, RawRelic, ScannerInput,
+    Slot, Stats, SubStats,
+};
+use eyre::{bail, eyre, Context, Result};
+use itertools::Itertools;
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use tracing::warn;
+
+/// A scan export format that can be turned into a `ScannerInput`.
+pub trait Importer {
+    fn name(&self) -> &'static str;
+
+    /// Whether the export looks like it was written in this format.
+    fn detect(&self, json: &Value) -> bool;
+
+    fn import(&self, json: Value) -> Result<ScannerInput>;
+}
+
+/// Every supported format, tried in order.
+pub fn importers() -> Vec<Box<dyn Importer>> {
+    vec![Box::new(HsrScanner), Box::new(Fribbels)]
+}
+
+/// Imports an export in any supported format.
+pub fn import_scan(json: Value) -> Result<ScannerInput> {
+    let importers = importers();
+    let importer = importers
+        .iter()
+        .find(|importer| importer.detect(&json))
+        .ok_or_else(|| {
+            eyre!(
+                "Unrecognized scan format, expected one of: {}",
+                importers.iter().map(|importer| importer.name()).join(", ")
+            )
+        })?;
+    importer
+        .import(json)
+        .wrap_err_with(|| format!("Invalid {} export", importer.name()))
+}
+
+/// Deserializes the items of an array one by one, so that errors tell which item is invalid.
+fn items<T: DeserializeOwned>(json: &mut Value, key: &str, item: &str) -> Result<Vec<T>> {
+    let items = match json.get_mut(key).map(Value::take) {
+        Some(Value::Array(items)) => items,
+        Some(_) => bail!("`{key}` is not a list"),
+        None => bail!("Missing `{key}`"),
+    };
+    items
+        .into_iter()
+        .enumerate()
+        .map(|(i, value)| {
+            serde_json::from_value(value).wrap_err_with(|| format!("Invalid {item} #{i}"))
+        })
+        .collect()
+}
+
+/// Ascension of a character or light cone at the given level, assuming it has been ascended as
+/// soon as possible.
+fn ascension(level: u8) -> u8 {
+    (level.max(11) - 11) / 10
+}
+
+/// Exports of HSR-Scanner (kel-z), the native format of the calculator.
+pub struct HsrScanner;
+
+impl Importer for HsrScanner {
+    fn name(&self) -> &'static str {
+        "HSR-Scanner"
+    }
+
+    fn detect(&self, json: &Value) -> bool {
+        json.get("source").is_some() && json.get("version").is_some()
+    }
+
+    fn import(&self, json: Value) -> Result<ScannerInput> {
+        match json.get("version").and_then(Value::as_u64) {
+            Some(4) => {}
+            Some(version) if version < 4 => {
+                bail!("Version {version} exports are not supported, rescan to get a version 4 one")
+            }
+            Some(version) => {
+                warn!("Version {version} exports are untested, reading it as version 4")
+            }
+            None => bail!("`version` is not a number"),
+        }
+        Ok(serde_json::from_value(json)?)
+    }
+}
+
+/// Save files of the Fribbels HSR optimizer. They have neither light cone inventory, nor trace
+/// and skill levels: each character gets the light cone of its optimizer form, every trace
+/// unlocked and its skills at their maximum level.
+pub struct Fribbels;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FribbelsRelic {
+    id: String,
+    part: Slot,
+    set: String,
+    grade: u8,
+    enhance: u8,
+    main: FribbelsStat,
+    substats: Vec<FribbelsStat>,
+    equipped_by: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct FribbelsStat {
+    stat: String,
+    value: f64,
+}
+
+#[derive(Deserialize)]
+struct FribbelsCharacter {
+    id: String,
+    #[serde(default)]
+    form: FribbelsForm,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct FribbelsForm {
+    character_level: u8,
+    character_eidolon: u8,
+    light_cone: Option<String>,
+    light_cone_level: u8,
+    light_cone_superimposition: u8,
+}
+
+impl Default for FribbelsForm {
+    fn default() -> Self {
+        Self {
+            character_level: 80,
+            character_eidolon: 0,
+            light_cone: None,
+            light_cone_level: 80,
+            light_cone_superimposition: 1,
+        }
+    }
+}
+
+/// Stat names of the Fribbels optimizer.
+pub const FRIBBELS_STATS: [(&str, Stats); 22] = [
+    ("HP", Stats::Hp),
+    ("ATK", Stats::Atk),
+    ("DEF", Stats::Def),
+    ("HP%", Stats::Hp_),
+    ("ATK%", Stats::Atk_),
+    ("DEF%", Stats::Def_),
+    ("SPD", Stats::Spd),
+    ("SPD%", Stats::Spd_),
+    ("CRIT Rate", Stats::CritRate_),
+    ("CRIT DMG", Stats::CritDmg_),
+    ("Effect Hit Rate", Stats::EffectHitRate_),
+    ("Effect RES", Stats::EffectRes_),
+    ("Break Effect", Stats::BreakEffect_),
+    ("Energy Regeneration Rate", Stats::EnergyRegenerationRate_),
+    ("Outgoing Healing Boost", Stats::OutgoingHealingBoost_),
+    ("Physical DMG Boost", Stats::PhysicalDmgBoost_),
+    ("Fire DMG Boost", Stats::FireDmgBoost_),
+    ("Ice DMG Boost", Stats::IceDmgBoost_),
+    ("Lightning DMG Boost", Stats::LightningDmgBoost_),
+    ("Wind DMG Boost", Stats::WindDmgBoost_),
+    ("Quantum DMG Boost", Stats::QuantumDmgBoost_),
+    ("Imaginary DMG Boost", Stats::ImaginaryDmgBoost_),
+];
+
+fn fribbels_stat(name: &str) -> Result<Stats> {
+    FRIBBELS_STATS
+        .iter()
+        .find(|(stat_name, _)| *stat_name == name)
+        .map(|(_, stat)| stat.clone())
+        .ok_or_else(|| eyre!("Unknown stat {name}"))
+}
+
+impl Importer for Fribbels {
+    fn name(&self) -> &'static str {
+        "Fribbels optimizer"
+    }
+
+    fn detect(&self, json: &Value) -> bool {
+        json.get("source").is_none()
+            && json
+                .get("relics")
+                .and_then(Value::as_array)
+                .is_some_and(|relics| relics.iter().all(|relic| relic.get("part").is_some()))
+    }
+
+    fn import(&self, mut json: Value) -> Result<ScannerInput> {
+        let set_ids = relic_sets()?
+            .iter()
+            .map(|(id, set)| (set.name.as_str(), id.as_str()))
+            .collect::<HashMap<_, _>>();
+        let relics = items::<FribbelsRelic>(&mut json, "relics", "relic")?
+            .into_iter()
+            .map(|relic| {
+                let set_id = match set_ids.get(relic.set.as_str()) {
+                    Some(id) => id.to_string(),
+                    None => {
+                        warn!(
+                            "Relic {}: Unknown set {}, its set bonus is ignored",
+                            relic.id, relic.set
+                        );
+                        String::new()
+                    }
+                };
+                Ok(RawRelic {
+                    set_id,
+                    name: relic.set.clone(),
+                    slot: relic.part,
+                    rarity: relic.grade,
+                    level: relic.enhance,
+                    mainstat: fribbels_stat(&relic.main.stat)
+                        .wrap_err_with(|| format!("Relic {}", relic.id))?,
+                    substats: relic
+                        .substats
+                        .into_iter()
+                        .map(|substat| {
+                            Ok(SubStats {
+                                key: fribbels_stat(&substat.stat)?,
+                                value: substat.value,
+                            })
+                        })
+                        .collect::<Result<_>>()
+                        .wrap_err_with(|| format!("Relic {}", relic.id))?,
+                    location: relic.equipped_by,
+                    lock: false,
+                    discard: false,
+                    _uid: relic.id,
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        let characters = match json.get("characters") {
+            Some(_) => items::<FribbelsCharacter>(&mut json, "characters", "character")?,
+            None => vec![],
+        };
+        let light_cones = characters
+            .iter()
+            .filter_map(|character| {
+                let form = &character.form;
+                Some(LightCone {
+                    id: form.light_cone.clone()?,
+                    name: String::new(),
+                    level: form.light_cone_level,
+                    ascension: ascension(form.light_cone_level),
+                    superimposition: form.light_cone_superimposition,
+                    location: Some(character.id.clone()),
+                    lock: false,
+                    _uid: format!("light_cone_{}", character.id),
+                })
+            })
+            .collect();
+        let characters = characters
+            .into_iter()
+            .map(|character| RawCharacter {
+                id: character.id,
+                name: String::new(),
+                path: None,
+                level: character.form.character_level,
+                ascension: ascension(character.form.character_level),
+                eidolon: character.form.character_eidolon,
+                skills: CharacterSkills {
+                    basic: 6,
+                    skill: 10,
+                    ult: 10,
+                    talent: 10,
+                },
+                traces: CharacterTraces {
+                    ability_1: true,
+                    ability_2: true,
+                    ability_3: true,
+                    stat_1: true,
+                    stat_2: true,
+                    stat_3: true,
+                    stat_4: true,
+                    stat_5: true,
+                    stat_6: true,
+                    stat_7: true,
+                    stat_8: true,
+                    stat_9: true,
+                    stat_10: true,
+                },
+            })
+            .collect();
+        Ok(ScannerInput {
+            source: self.name().to_string(),
+            version: 4,
+            light_cones,
+            relics,
+            characters,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_import_fribbels() -> Result<()> {
+        let input = import_scan(json!({
+            "relics": [{
+                "id": "5f1c",
+                "part": "Body",
+                "set": "Musketeer of Wild Wheat",
+                "grade": 5,
+                "enhance": 15,
+                "main": { "stat": "CRIT DMG", "value": 64.8 },
+                "substats": [
+                    { "stat": "CRIT Rate", "value": 3.24 },
+                    { "stat": "ATK%", "value": 8.64 },
+                ],
+                "equippedBy": "1308",
+                "verified": true,
+            }],
+            "characters": [{
+                "id": "1308",
+                "form": {
+                    "characterLevel": 80,
+                    "characterEidolon": 2,
+                    "lightCone": "23024",
+                    "lightConeSuperimposition": 1,
+                },
+            }],
+        }))?;
+        let relic = &input.relics[0];
+        assert_eq!(relic.set_id, "102");
+        assert_eq!(relic.slot, Slot::Body);
+        assert_eq!(relic.mainstat, Stats::CritDmg_);
+        assert_eq!(relic.substats[1].key, Stats::Atk_);
+        assert_eq!(relic.location.as_deref(), Some("1308"));
+        assert_eq!(input.characters[0].ascension, 6);
+        assert_eq!(input.characters[0].eidolon, 2);
+        assert_eq!(input.light_cones[0].id, "23024");
+        assert_eq!(input.light_cones[0].location.as_deref(), Some("1308"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_import_errors() {
+        let unknown = import_scan(json!({ "items": [] })).unwrap_err();
+        assert!(unknown.to_string().starts_with("Unrecognized scan format"));
+
+        let old = import_scan(json!({ "source": "HSR-Scanner", "version": 3 })).unwrap_err();
+        assert!(format!("{old:#}").contains("Version 3 exports are not supported"));
+
+        let invalid = import_scan(json!({
+            "relics": [{ "id": "a", "part": "Head", "set": "Musketeer of Wild Wheat" }],
+        }))
+        .unwrap_err();
+        assert!(format!("{invalid:#}").contains("Invalid relic #0"));
+
+        let unknown_set = import_scan(json!({
+            "relics": [{
+                "id": "5f1c",
+                "part": "Head",
+                "set": "Musketeer of Wild Wheats",
+                "grade": 5,
+                "enhance": 15,
+                "main": { "stat": "HP", "value": 705.6 },
+                "substats": [],
+            }],
+        }))
+        .unwrap();
+        assert_eq!(unknown_set.relics.len(), 1);
+        assert_eq!(unknown_set.relics[0].set_id, "");
+        assert_eq!(unknown_set.relics[0].name, "Musketeer of Wild Wheats");
+    }
+}
@@ -2,9 +2,9 @@ use eyre::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use crate::domain::{DamageType, SkillType, Stats};
+use crate::domain::{AttackType, DamageType, DataError, SkillType, Stats};
 
-#[derive(Debug, Clone, PartialEq, Default)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
 pub enum CarveTheMoonWeaveTheCloudEffect {
     #[default]
     Atk,
@@ -19,6 +19,8 @@ pub struct LightConeEntity {
     pub base_def: f64,
     pub _light_cone: LightCone,
     pub config: LightConePassiveConfig,
+    /// Set for light cones whose passive is not implemented, so that only their base stats count.
+    pub ignore_passive: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -40,7 +42,8 @@ pub struct LightConeStats {
     pub def: f64,
 }
 
-#[derive(Clone, Debug, Default)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
 pub struct LightConePassiveConfig {
     pub activate_20000: bool,
     pub activate_20003: bool,
@@ -87,6 +90,9 @@ pub struct LightConePassiveConfig {
 impl LightConeEntity {
     pub fn get_bonus_before_battle(&self) -> Result<HashMap<Stats, f64>> {
         let mut bonus = HashMap::new();
+        if self.ignore_passive {
+            return Ok(bonus);
+        }
         match self._light_cone.id.as_str() {
             "20000" => {}
             "20001" => {}
@@ -299,7 +305,7 @@ impl LightConeEntity {
                 *bonus.entry(Stats::Hp_).or_default() += [0.0, 12.00, 15.00, 18.00, 21.00, 24.00]
                     [self._light_cone.superimposition as usize];
             }
-            _ => todo!(),
+            _ => return Err(self.unknown().into()),
         }
         Ok(bonus)
     }
@@ -311,6 +317,9 @@ impl LightConeEntity {
         base_stats: &HashMap<Stats, f64>,
     ) -> Result<HashMap<Stats, f64>> {
         let mut bonus = HashMap::new();
+        if self.ignore_passive {
+            return Ok(bonus);
+        }
         match self._light_cone.id.as_str() {
             "20000" => {
                 if self.config.activate_20000 {
@@ -741,8 +750,58 @@ impl LightConeEntity {
                             [self._light_cone.superimposition as usize];
                 }
             }
-            _ => todo!(),
+            _ => return Err(self.unknown().into()),
         }
         Ok(bonus)
     }
+
+    /// Buffs and debuffs the wearer grants to the rest of the team, assuming it keeps them up.
+    pub fn get_team_buffs(
+        &self,
+        wearer_attack_type: &AttackType,
+        carry_attack_type: &AttackType,
+    ) -> HashMap<Stats, f64> {
+        let mut bonus = HashMap::new();
+        if self.ignore_passive {
+            return bonus;
+        }
+        let superimposition = self._light_cone.superimposition as usize;
+        match self._light_cone.id.as_str() {
+            "21011" if wearer_attack_type == carry_attack_type => {
+                *bonus.entry(Stats::DmgBoost_).or_default() +=
+                    [0.0, 12.00, 15.00, 18.00, 21.00, 24.00][superimposition];
+            }
+            "21015" => {
+                *bonus.entry(Stats::DefReduction_).or_default() +=
+                    [0.0, 12.00, 13.00, 14.00, 15.00, 16.00][superimposition];
+            }
+            "21025" => {
+                *bonus.entry(Stats::DmgBoost_).or_default() +=
+                    [0.0, 16.00, 20.00, 24.00, 28.00, 32.00][superimposition];
+            }
+            "23003" => {
+                *bonus.entry(Stats::DmgBoost_).or_default() +=
+                    [0.0, 30.00, 35.00, 40.00, 45.00, 50.00][superimposition];
+            }
+            "23019" => {
+                *bonus.entry(Stats::DmgBoost_).or_default() +=
+                    [0.0, 24.00, 28.00, 32.00, 36.00, 40.00][superimposition];
+            }
+            "23021" => {
+                *bonus.entry(Stats::CritRate_).or_default() +=
+                    [0.0, 10.00, 11.00, 12.00, 13.00, 14.00][superimposition];
+                *bonus.entry(Stats::CritDmg_).or_default() +=
+                    [0.0, 28.00, 35.00, 42.00, 49.00, 56.00][superimposition];
+            }
+            _ => {}
+        }
+        bonus
+    }
+
+    fn unknown(&self) -> DataError {
+        DataError::UnknownLightCone {
+            id: self._light_cone.id.clone(),
+            name: self._light_cone.name.clone(),
+        }
+    }
 }
@@ -1,15 +1,25 @@
 mod base_stats;
 pub(crate) mod character;
 mod enemy;
+mod enemy_presets;
+mod error;
+mod exporters;
+mod importers;
 mod light_cone;
 mod relic;
 mod relics;
 mod scanner_input;
+mod set_bonus;
 
 pub use character::*;
 pub use enemy::*;
+pub use enemy_presets::*;
+pub use error::DataError;
+pub use exporters::*;
+pub use importers::*;
 pub use light_cone::*;
 pub use relic::*;
 pub use relics::*;
 pub use scanner_input::*;
+pub use set_bonus::*;
 pub use base_stats::BaseStats;
@@ -4,6 +4,8 @@ use eyre::{bail, eyre, Result};
 use serde::{Deserialize, Serialize};
 use strum_macros::EnumIter;
 
+use super::DataError;
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default, EnumIter, Hash)]
 pub enum Slot {
     Head,
@@ -202,6 +204,44 @@ pub struct SubStats {
     pub value: f64,
 }
 
+/// Stats a relic can roll as a substat.
+pub const SUBSTATS: [Stats; 12] = [
+    Stats::Hp,
+    Stats::Atk,
+    Stats::Def,
+    Stats::Hp_,
+    Stats::Atk_,
+    Stats::Def_,
+    Stats::Spd,
+    Stats::CritRate_,
+    Stats::CritDmg_,
+    Stats::EffectHitRate_,
+    Stats::EffectRes_,
+    Stats::BreakEffect_,
+];
+
+impl Stats {
+    /// Roll of the substat on a 5-star relic as `(base, step)`: each roll adds `base` plus 0, 1
+    /// or 2 times `step` with the same chance. `None` for stats that are never substats.
+    pub fn substat_roll(&self) -> Option<(f64, f64)> {
+        Some(match self {
+            Stats::Hp => (33.870039, 4.233754),
+            Stats::Atk | Stats::Def => (16.935019, 2.116877),
+            Stats::Hp_ | Stats::Atk_ | Stats::EffectHitRate_ | Stats::EffectRes_ => (3.456, 0.432),
+            Stats::Def_ => (4.32, 0.54),
+            Stats::Spd => (2.0, 0.3),
+            Stats::CritRate_ => (2.592, 0.324),
+            Stats::CritDmg_ | Stats::BreakEffect_ => (5.184, 0.648),
+            _ => return None,
+        })
+    }
+
+    /// Value of an average substat roll on a 5-star relic.
+    pub fn average_roll(&self) -> Option<f64> {
+        self.substat_roll().map(|(base, step)| base + step)
+    }
+}
+
 impl RawRelic {
     pub fn get_mainstat(&self) -> Result<f64> {
         let stat = match &self.mainstat {
@@ -209,88 +249,104 @@ impl RawRelic {
             &Stats::Hp if self.slot != Slot::Head => Stats::Hp_,
             other => other.clone(),
         };
-        Ok(match (self.rarity, &stat) {
-            (5, Stats::Spd) => 4.032 + 1.4 * self.level as f64,
-            (5, Stats::Hp) => 112.896 + 39.5136 * self.level as f64,
-            (5, Stats::Atk) => 56.448 + 19.7568 * self.level as f64,
-            (5, Stats::Hp_) | (5, Stats::Atk_) | (5, Stats::EffectHitRate_) => {
-                6.9120 + 2.4192 * self.level as f64
-            }
-            (5, Stats::Def_) => 8.64 + 3.024 * self.level as f64, // Special case
-            (5, Stats::BreakEffect_) => 10.3680 + 3.6277 * self.level as f64,
-            (5, Stats::EnergyRegenerationRate_) => 3.1104 + 1.0886 * self.level as f64,
-            (5, Stats::OutgoingHealingBoost_) => 5.5296 + 1.9354 * self.level as f64,
-            (5, Stats::PhysicalDmgBoost_)
-            | (5, Stats::FireDmgBoost_)
-            | (5, Stats::IceDmgBoost_)
-            | (5, Stats::WindDmgBoost_)
-            | (5, Stats::LightningDmgBoost_)
-            | (5, Stats::QuantumDmgBoost_)
-            | (5, Stats::ImaginaryDmgBoost_) => 6.2208 + 2.1773 * self.level as f64,
-            (5, Stats::CritRate_) => 5.184 + 1.8144 * self.level as f64,
-            (5, Stats::CritDmg_) => 10.368 + 3.6288 * self.level as f64,
-            (4, Stats::Spd) => 3.2256 + 1.1 * self.level as f64,
-            (4, Stats::Hp) => 90.3168 + 31.61088 * self.level as f64,
-            (4, Stats::Atk) => 45.1584 + 15.80544 * self.level as f64,
-            (4, Stats::Hp_) | (4, Stats::Atk_) | (4, Stats::EffectHitRate_) => {
-                5.5296 + 1.9354 * self.level as f64
-            }
-            (4, Stats::Def_) => 6.912 + 2.4192 * self.level as f64, // Special case
-            (4, Stats::BreakEffect_) => 8.2944 + 2.9030 * self.level as f64,
-            (4, Stats::EnergyRegenerationRate_) => 2.4883 + 0.8709 * self.level as f64,
-            (4, Stats::OutgoingHealingBoost_) => 4.4237 + 1.5483 * self.level as f64,
-            (4, Stats::PhysicalDmgBoost_)
-            | (4, Stats::FireDmgBoost_)
-            | (4, Stats::IceDmgBoost_)
-            | (4, Stats::WindDmgBoost_)
-            | (4, Stats::LightningDmgBoost_)
-            | (4, Stats::QuantumDmgBoost_)
-            | (4, Stats::ImaginaryDmgBoost_) => 4.9766 + 1.7418 * self.level as f64,
-            (4, Stats::CritRate_) => 4.1472 + 1.4515 * self.level as f64,
-            (4, Stats::CritDmg_) => 8.2944 + 2.9030 * self.level as f64,
-            (3, Stats::Spd) => 2.4192 + 1.0 * self.level as f64,
-            (3, Stats::Hp) => 67.7376 + 23.70816 * self.level as f64,
-            (3, Stats::Atk) => 33.8688 + 11.85408 * self.level as f64,
-            (3, Stats::Hp_) | (3, Stats::Atk_) | (3, Stats::EffectHitRate_) => {
-                4.1472 + 1.4515 * self.level as f64
-            }
-            (3, Stats::Def_) => 5.184 + 1.8144 * self.level as f64, // Special case
-            (3, Stats::BreakEffect_) => 6.2208 + 2.1773 * self.level as f64,
-            (3, Stats::EnergyRegenerationRate_) => 1.8662 + 0.6532 * self.level as f64,
-            (3, Stats::OutgoingHealingBoost_) => 3.3178 + 1.1612 * self.level as f64,
-            (3, Stats::PhysicalDmgBoost_)
-            | (3, Stats::FireDmgBoost_)
-            | (3, Stats::IceDmgBoost_)
-            | (3, Stats::WindDmgBoost_)
-            | (3, Stats::LightningDmgBoost_)
-            | (3, Stats::QuantumDmgBoost_)
-            | (3, Stats::ImaginaryDmgBoost_) => 3.7325 + 1.3064 * self.level as f64,
-            (3, Stats::CritRate_) => 3.1104 + 1.0886 * self.level as f64,
-            (3, Stats::CritDmg_) => 6.2208 + 2.1773 * self.level as f64,
-            (2, Stats::Spd) => 1.6128 + 1.0 * self.level as f64,
-            (2, Stats::Hp) => 45.1584 + 15.80544 * self.level as f64,
-            (2, Stats::Atk) => 22.5792 + 7.90272 * self.level as f64,
-            (2, Stats::Hp_) | (2, Stats::Atk_) | (2, Stats::EffectHitRate_) => {
-                2.7648 + 0.9677 * self.level as f64
-            }
-            (2, Stats::Def_) => 3.456 + 1.2096 * self.level as f64, // Special case
-            (2, Stats::BreakEffect_) => 4.1472 + 1.4515 * self.level as f64,
-            (2, Stats::EnergyRegenerationRate_) => 1.2442 + 0.4355 * self.level as f64,
-            (2, Stats::OutgoingHealingBoost_) => 2.2118 + 0.7741 * self.level as f64,
-            (2, Stats::PhysicalDmgBoost_)
-            | (2, Stats::FireDmgBoost_)
-            | (2, Stats::IceDmgBoost_)
-            | (2, Stats::WindDmgBoost_)
-            | (2, Stats::LightningDmgBoost_)
-            | (2, Stats::QuantumDmgBoost_)
-            | (2, Stats::ImaginaryDmgBoost_) => 2.4883 + 0.8709 * self.level as f64,
-            (2, Stats::CritRate_) => 2.0736 + 0.7258 * self.level as f64,
-            (2, Stats::CritDmg_) => 4.1472 + 1.4515 * self.level as f64,
-            other => bail!("Invalid rarity or stats: {other:?}"),
-        })
+        let (base, step) = main_affix(self.rarity, &stat)?;
+        Ok(base + step * self.level as f64)
+    }
+}
+
+impl Relic {
+    /// Value of the main stat of the relic once levelled to `level`.
+    pub fn mainstat_at(&self, level: u8) -> Result<f64> {
+        let raw = RawRelic {
+            slot: self.slot.clone(),
+            rarity: self.rarity,
+            level,
+            mainstat: self.mainstat.clone(),
+            ..Default::default()
+        };
+        let current = RawRelic {
+            level: self.level,
+            ..raw.clone()
+        };
+        Ok(self.mainstat_value + raw.get_mainstat()? - current.get_mainstat()?)
     }
 }
 
+/// Main stat of a relic as `(base, step)`, its value being `base + step * level`.
+pub fn main_affix(rarity: u8, stat: &Stats) -> Result<(f64, f64)> {
+    Ok(match (rarity, stat) {
+        (5, Stats::Spd) => (4.032, 1.4),
+        (5, Stats::Hp) => (112.896, 39.5136),
+        (5, Stats::Atk) => (56.448, 19.7568),
+        (5, Stats::Hp_) | (5, Stats::Atk_) | (5, Stats::EffectHitRate_) => (6.9120, 2.4192),
+        (5, Stats::Def_) => (8.64, 3.024), // Special case
+        (5, Stats::BreakEffect_) => (10.3680, 3.6277),
+        (5, Stats::EnergyRegenerationRate_) => (3.1104, 1.0886),
+        (5, Stats::OutgoingHealingBoost_) => (5.5296, 1.9354),
+        (5, Stats::PhysicalDmgBoost_)
+        | (5, Stats::FireDmgBoost_)
+        | (5, Stats::IceDmgBoost_)
+        | (5, Stats::WindDmgBoost_)
+        | (5, Stats::LightningDmgBoost_)
+        | (5, Stats::QuantumDmgBoost_)
+        | (5, Stats::ImaginaryDmgBoost_) => (6.2208, 2.1773),
+        (5, Stats::CritRate_) => (5.184, 1.8144),
+        (5, Stats::CritDmg_) => (10.368, 3.6288),
+        (4, Stats::Spd) => (3.2256, 1.1),
+        (4, Stats::Hp) => (90.3168, 31.61088),
+        (4, Stats::Atk) => (45.1584, 15.80544),
+        (4, Stats::Hp_) | (4, Stats::Atk_) | (4, Stats::EffectHitRate_) => (5.5296, 1.9354),
+        (4, Stats::Def_) => (6.912, 2.4192), // Special case
+        (4, Stats::BreakEffect_) => (8.2944, 2.9030),
+        (4, Stats::EnergyRegenerationRate_) => (2.4883, 0.8709),
+        (4, Stats::OutgoingHealingBoost_) => (4.4237, 1.5483),
+        (4, Stats::PhysicalDmgBoost_)
+        | (4, Stats::FireDmgBoost_)
+        | (4, Stats::IceDmgBoost_)
+        | (4, Stats::WindDmgBoost_)
+        | (4, Stats::LightningDmgBoost_)
+        | (4, Stats::QuantumDmgBoost_)
+        | (4, Stats::ImaginaryDmgBoost_) => (4.9766, 1.7418),
+        (4, Stats::CritRate_) => (4.1472, 1.4515),
+        (4, Stats::CritDmg_) => (8.2944, 2.9030),
+        (3, Stats::Spd) => (2.4192, 1.0),
+        (3, Stats::Hp) => (67.7376, 23.70816),
+        (3, Stats::Atk) => (33.8688, 11.85408),
+        (3, Stats::Hp_) | (3, Stats::Atk_) | (3, Stats::EffectHitRate_) => (4.1472, 1.4515),
+        (3, Stats::Def_) => (5.184, 1.8144), // Special case
+        (3, Stats::BreakEffect_) => (6.2208, 2.1773),
+        (3, Stats::EnergyRegenerationRate_) => (1.8662, 0.6532),
+        (3, Stats::OutgoingHealingBoost_) => (3.3178, 1.1612),
+        (3, Stats::PhysicalDmgBoost_)
+        | (3, Stats::FireDmgBoost_)
+        | (3, Stats::IceDmgBoost_)
+        | (3, Stats::WindDmgBoost_)
+        | (3, Stats::LightningDmgBoost_)
+        | (3, Stats::QuantumDmgBoost_)
+        | (3, Stats::ImaginaryDmgBoost_) => (3.7325, 1.3064),
+        (3, Stats::CritRate_) => (3.1104, 1.0886),
+        (3, Stats::CritDmg_) => (6.2208, 2.1773),
+        (2, Stats::Spd) => (1.6128, 1.0),
+        (2, Stats::Hp) => (45.1584, 15.80544),
+        (2, Stats::Atk) => (22.5792, 7.90272),
+        (2, Stats::Hp_) | (2, Stats::Atk_) | (2, Stats::EffectHitRate_) => (2.7648, 0.9677),
+        (2, Stats::Def_) => (3.456, 1.2096), // Special case
+        (2, Stats::BreakEffect_) => (4.1472, 1.4515),
+        (2, Stats::EnergyRegenerationRate_) => (1.2442, 0.4355),
+        (2, Stats::OutgoingHealingBoost_) => (2.2118, 0.7741),
+        (2, Stats::PhysicalDmgBoost_)
+        | (2, Stats::FireDmgBoost_)
+        | (2, Stats::IceDmgBoost_)
+        | (2, Stats::WindDmgBoost_)
+        | (2, Stats::LightningDmgBoost_)
+        | (2, Stats::QuantumDmgBoost_)
+        | (2, Stats::ImaginaryDmgBoost_) => (2.4883, 0.8709),
+        (2, Stats::CritRate_) => (2.0736, 0.7258),
+        (2, Stats::CritDmg_) => (4.1472, 1.4515),
+        other => bail!("Invalid rarity or stats: {other:?}"),
+    })
+}
+
 pub type RelicMainAffixesData = HashMap<String, MainAffixes>;
 pub type RelicSubAffixesData = HashMap<String, SubAffixes>;
 
@@ -336,7 +392,7 @@ impl TryFrom<RawRelic> for Relic {
                 Slot::Hands => "2",
                 Slot::LinkRope => "6",
                 Slot::PlanarSphere => "5",
-                Slot::Dummy => todo!(),
+                Slot::Dummy => return Err(DataError::UnknownSlot(value._uid).into()),
             };
         let affixes = parsed_data
             .get(&key)
@@ -410,7 +466,7 @@ impl TryFrom<RawRelic> for Relic {
                 Stats::EffectHitRate_ => "10",
                 Stats::EffectRes_ => "11",
                 Stats::BreakEffect_ => "12",
-                _ => todo!(),
+                _ => return Err(DataError::UnknownSubstat(substat.key.clone()).into()),
             };
             let affix = affixes
                 .affixes
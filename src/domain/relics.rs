@@ -1,13 +1,14 @@
-use eyre::{eyre, Result};
+use eyre::{bail, Result};
 use itertools::Itertools;
-use std::{cmp::min, collections::HashMap};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 use crate::{
     character::Support,
     domain::{AttackType, DamageType, Path, SkillType},
 };
 
-use super::{Relic, Stats};
+use super::{config_keys, relic_sets, BattleContext, Relic, RelicSet, Stats};
 
 #[derive(Clone, Debug)]
 pub struct Relics {
@@ -15,7 +16,8 @@ pub struct Relics {
     pub config: RelicSetConfig,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
 pub struct RelicSetConfig {
     pub activate_102: bool,
     pub activate_104: bool,
@@ -43,6 +45,111 @@ pub struct RelicSetConfig {
     pub activate_316: bool,
     pub activate_318: bool,
     pub stack_321: u8,
+    /// Entries used by set data without a dedicated field.
+    #[serde(flatten)]
+    pub extra: HashMap<String, SetConfigValue>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(untagged)]
+pub enum SetConfigValue {
+    Flag(bool),
+    Number(f64),
+}
+
+impl From<SetConfigValue> for f64 {
+    fn from(value: SetConfigValue) -> Self {
+        match value {
+            SetConfigValue::Flag(flag) => flag as u8 as f64,
+            SetConfigValue::Number(number) => number,
+        }
+    }
+}
+
+impl RelicSetConfig {
+    /// Every conditional effect activated and every stack maxed out, the usual assumption
+    /// when comparing sets for a damage dealer.
+    pub fn all_active() -> Self {
+        Self {
+            activate_102: true,
+            activate_104: true,
+            stack_105: 5,
+            activate_107: true,
+            activate_108: true,
+            activate_109: true,
+            activate_112_1: true,
+            activate_112_2: true,
+            stack_113: 5,
+            stack_115: 5,
+            stack_116: 5,
+            activate_117_2pcs: true,
+            stack_117: 5,
+            activate_117_4pcs_extra: true,
+            activate_120: true,
+            activate_122: true,
+            activate_123_1: true,
+            activate_123_2: true,
+            activate_125: true,
+            activate_126: true,
+            activate_305: true,
+            stack_313: 5,
+            stack_315: 5,
+            activate_316: true,
+            activate_318: true,
+            stack_321: 4,
+            extra: HashMap::new(),
+        }
+    }
+
+    /// Value of a config entry as read by the set data, `true` being 1.
+    pub fn get(&self, key: &str) -> f64 {
+        let flag = |flag: bool| flag as u8 as f64;
+        match key {
+            "activate_102" => flag(self.activate_102),
+            "activate_104" => flag(self.activate_104),
+            "stack_105" => self.stack_105 as f64,
+            "activate_107" => flag(self.activate_107),
+            "activate_108" => flag(self.activate_108),
+            "activate_109" => flag(self.activate_109),
+            "activate_112_1" => flag(self.activate_112_1),
+            "activate_112_2" => flag(self.activate_112_2),
+            "stack_113" => self.stack_113 as f64,
+            "stack_115" => self.stack_115 as f64,
+            "stack_116" => self.stack_116 as f64,
+            "activate_117_2pcs" => flag(self.activate_117_2pcs),
+            "stack_117" => self.stack_117 as f64,
+            "activate_117_4pcs_extra" => flag(self.activate_117_4pcs_extra),
+            "activate_120" => flag(self.activate_120),
+            "activate_122" => flag(self.activate_122),
+            "activate_123_1" => flag(self.activate_123_1),
+            "activate_123_2" => flag(self.activate_123_2),
+            "activate_125" => flag(self.activate_125),
+            "activate_126" => flag(self.activate_126),
+            "activate_305" => flag(self.activate_305),
+            "stack_313" => self.stack_313 as f64,
+            "stack_315" => self.stack_315 as f64,
+            "activate_316" => flag(self.activate_316),
+            "activate_318" => flag(self.activate_318),
+            "stack_321" => self.stack_321 as f64,
+            other => self
+                .extra
+                .get(other)
+                .copied()
+                .map(f64::from)
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Rejects extra entries that no set effect reads, which are most likely typos.
+    pub fn check_keys(&self) -> Result<()> {
+        let known = config_keys()?;
+        for key in self.extra.keys() {
+            if !known.contains(key.as_str()) {
+                bail!("Unknown relic set config entry {key}");
+            }
+        }
+        Ok(())
+    }
 }
 
 impl Relics {
@@ -65,282 +172,62 @@ impl Relics {
             for substat in &relic.substats {
                 *bonus.entry(substat.key.clone()).or_default() += substat.value;
             }
-            if *attack_type == AttackType::Lightning && relic.mainstat == Stats::LightningDmgBoost_
-            {
-                *bonus.entry(Stats::DmgBoost_).or_default() += relic.mainstat_value;
-            } else if *attack_type == AttackType::Wind && relic.mainstat == Stats::WindDmgBoost_ {
-                *bonus.entry(Stats::DmgBoost_).or_default() += relic.mainstat_value;
-            } else if *attack_type == AttackType::Fire && relic.mainstat == Stats::FireDmgBoost_ {
-                *bonus.entry(Stats::DmgBoost_).or_default() += relic.mainstat_value;
-            } else if *attack_type == AttackType::Ice && relic.mainstat == Stats::IceDmgBoost_ {
-                *bonus.entry(Stats::DmgBoost_).or_default() += relic.mainstat_value;
-            } else if *attack_type == AttackType::Quantum
-                && relic.mainstat == Stats::QuantumDmgBoost_
-            {
-                *bonus.entry(Stats::DmgBoost_).or_default() += relic.mainstat_value;
-            } else if *attack_type == AttackType::Imaginary
-                && relic.mainstat == Stats::ImaginaryDmgBoost_
-            {
-                *bonus.entry(Stats::DmgBoost_).or_default() += relic.mainstat_value;
-            } else if *attack_type == AttackType::Physical
-                && relic.mainstat == Stats::PhysicalDmgBoost_
-            {
-                *bonus.entry(Stats::DmgBoost_).or_default() += relic.mainstat_value;
-            } else {
-                *bonus.entry(relic.mainstat.clone()).or_default() += relic.mainstat_value;
-            }
+            *bonus.entry(main_stat_key(relic, attack_type)).or_default() += relic.mainstat_value;
         }
         Ok(())
     }
 
+    /// Bonus of the main stats alone, a DMG boost of the character's element counting as
+    /// `DmgBoost_`.
+    pub fn main_stat_bonus(&self, attack_type: &AttackType) -> HashMap<Stats, f64> {
+        let mut bonus = HashMap::new();
+        for relic in &self.relics {
+            *bonus.entry(main_stat_key(relic, attack_type)).or_default() += relic.mainstat_value;
+        }
+        bonus
+    }
+
+    /// Bonus of the substats alone.
+    pub fn substat_bonus(&self) -> HashMap<Stats, f64> {
+        let mut bonus = HashMap::new();
+        for substat in self.relics.iter().flat_map(|r| &r.substats) {
+            *bonus.entry(substat.key.clone()).or_default() += substat.value;
+        }
+        bonus
+    }
+
+    /// Every set effect in use as `(source, stat, bonus)`, e.g. `("4pc Pioneer Diver of Dead
+    /// Waters", CritDmg_, 12.0)`. Without `battle`, only the effects applying outside of battle.
+    pub fn set_effects(
+        &self,
+        attack_type: &AttackType,
+        battle: Option<&BattleContext>,
+    ) -> Result<Vec<(String, Stats, f64)>> {
+        let mut effects = vec![];
+        for (set, num_relics) in self.relic_sets()? {
+            let pieces = [(2, &set.two_pieces), (4, &set.four_pieces)];
+            for (pieces, set_effects) in pieces.into_iter().filter(|(p, _)| num_relics >= *p) {
+                for effect in set_effects.iter().filter(|e| e.battle == battle.is_some()) {
+                    effects.push((
+                        format!("{pieces}pc {}", set.name),
+                        effect.stat.clone(),
+                        effect.bonus(attack_type, &self.config, battle)?,
+                    ));
+                }
+            }
+        }
+        Ok(effects)
+    }
+
     pub fn calculate_set_bonus(
         &self,
         attack_type: &AttackType,
         bonus: &mut HashMap<Stats, f64>,
     ) -> Result<()> {
-        let count = self.relics.iter().counts_by(|r| r.set_id.clone());
-        for (set_id, num_relics) in count {
-            match set_id.as_str() {
-                "101" => {
-                    if num_relics >= 2 {
-                        *bonus.entry(Stats::OutgoingHealingBoost_).or_default() += 10.0;
-                    }
-                }
-                "102" => {
-                    if num_relics >= 2 {
-                        *bonus.entry(Stats::Atk_).or_default() += 12.0;
-                    }
-                    if num_relics >= 4 {
-                        *bonus.entry(Stats::Spd_).or_default() += 6.0;
-                    }
-                }
-                "103" => {
-                    if num_relics >= 2 {
-                        *bonus.entry(Stats::Def_).or_default() += 15.0;
-                    }
-                }
-                "104" => {
-                    if num_relics >= 2 && *attack_type == AttackType::Ice {
-                        *bonus.entry(Stats::DmgBoost_).or_default() += 10.0;
-                    }
-                }
-                "105" => {
-                    if num_relics >= 2 && *attack_type == AttackType::Physical {
-                        *bonus.entry(Stats::DmgBoost_).or_default() += 10.0;
-                    }
-                }
-                "106" => {}
-                "107" => {
-                    if num_relics >= 2 && *attack_type == AttackType::Fire {
-                        *bonus.entry(Stats::DmgBoost_).or_default() += 10.0;
-                    }
-                }
-                "108" => {
-                    if num_relics >= 2 && *attack_type == AttackType::Quantum {
-                        *bonus.entry(Stats::DmgBoost_).or_default() += 10.0;
-                    }
-                    if num_relics >= 4 {
-                        *bonus.entry(Stats::DefIgnore_).or_default() += 10.0;
-                    }
-                }
-                "109" => {
-                    if num_relics >= 2 && *attack_type == AttackType::Lightning {
-                        *bonus.entry(Stats::DmgBoost_).or_default() += 10.0;
-                    }
-                }
-                "110" => {
-                    if num_relics >= 2 && *attack_type == AttackType::Wind {
-                        *bonus.entry(Stats::DmgBoost_).or_default() += 10.0;
-                    }
-                }
-                "111" => {
-                    if num_relics >= 2 {
-                        *bonus.entry(Stats::BreakEffect_).or_default() += 16.0;
-                    }
-                    if num_relics >= 4 {
-                        *bonus.entry(Stats::BreakEffect_).or_default() += 16.0;
-                    }
-                }
-                "112" => {
-                    if num_relics >= 2 && *attack_type == AttackType::Imaginary {
-                        *bonus.entry(Stats::DmgBoost_).or_default() += 10.0;
-                    }
-                }
-                "113" => {
-                    if num_relics >= 2 {
-                        *bonus.entry(Stats::Hp_).or_default() += 12.0;
-                    }
-                }
-                "114" => {
-                    if num_relics >= 2 {
-                        *bonus.entry(Stats::Spd_).or_default() += 6.0;
-                    }
-                }
-                "115" => {}
-                "116" => {
-                    if num_relics >= 2 {
-                        *bonus.entry(Stats::Atk_).or_default() += 12.0;
-                    }
-                }
-                "117" => {
-                    if num_relics >= 4 {
-                        *bonus.entry(Stats::CritRate_).or_default() += 4.0;
-                    }
-                }
-                "118" => {
-                    if num_relics >= 2 {
-                        *bonus.entry(Stats::BreakEffect_).or_default() += 16.0;
-                    }
-                }
-                "119" => {
-                    if num_relics >= 2 {
-                        *bonus.entry(Stats::BreakEffect_).or_default() += 16.0;
-                    }
-                }
-                "120" => {
-                    if num_relics >= 2 {
-                        *bonus.entry(Stats::Atk_).or_default() += 12.0;
-                    }
-                    if num_relics >= 4 {
-                        *bonus.entry(Stats::CritRate_).or_default() += 6.0;
-                    }
-                }
-                "121" => {
-                    if num_relics >= 2 {
-                        *bonus.entry(Stats::Spd_).or_default() += 6.0;
-                    }
-                }
-                "122" => {
-                    if num_relics >= 2 {
-                        *bonus.entry(Stats::CritRate_).or_default() += 8.0;
-                    }
-                }
-                "123" => {
-                    if num_relics >= 2 {
-                        *bonus.entry(Stats::Atk_).or_default() += 12.0;
-                    }
-                }
-                "124" => {
-                    if num_relics >= 2 && *attack_type == AttackType::Quantum {
-                        *bonus.entry(Stats::DmgBoost_).or_default() += 10.0;
-                    }
-                    if num_relics >= 4 {
-                        *bonus.entry(Stats::Spd_).or_default() -= 8.0;
-                    }
-                }
-                "125" => {
-                    if num_relics >= 2 {
-                        *bonus.entry(Stats::Spd_).or_default() += 6.0;
-                    }
-                }
-                "126" => {
-                    if num_relics >= 2 {
-                        *bonus.entry(Stats::CritDmg_).or_default() += 16.0;
-                    }
-                }
-                "301" => {
-                    if num_relics >= 2 {
-                        *bonus.entry(Stats::Atk_).or_default() += 12.0;
-                    }
-                }
-                "302" => {
-                    if num_relics >= 2 {
-                        *bonus.entry(Stats::Hp_).or_default() += 12.0;
-                    }
-                }
-                "303" => {
-                    if num_relics >= 2 {
-                        *bonus.entry(Stats::EffectHitRate_).or_default() += 10.0;
-                    }
-                }
-                "304" => {
-                    if num_relics >= 2 {
-                        *bonus.entry(Stats::Def_).or_default() += 15.0;
-                    }
-                }
-                "305" => {
-                    if num_relics >= 2 {
-                        *bonus.entry(Stats::CritDmg_).or_default() += 16.0;
-                    }
-                }
-                "306" => {
-                    if num_relics >= 2 {
-                        *bonus.entry(Stats::CritRate_).or_default() += 8.0;
-                    }
-                }
-                "307" => {
-                    if num_relics >= 2 {
-                        *bonus.entry(Stats::BreakEffect_).or_default() += 16.0;
-                    }
-                }
-                "308" => {
-                    if num_relics >= 2 {
-                        *bonus.entry(Stats::EnergyRegenerationRate_).or_default() += 5.0;
-                    }
-                }
-                "309" => {
-                    if num_relics >= 2 {
-                        *bonus.entry(Stats::CritRate_).or_default() += 8.0;
-                    }
-                }
-                "310" => {
-                    if num_relics >= 2 {
-                        *bonus.entry(Stats::EffectRes_).or_default() += 10.0;
-                    }
-                }
-                "311" => {
-                    if num_relics >= 2 {
-                        *bonus.entry(Stats::Atk_).or_default() += 12.0;
-                    }
-                }
-                "312" => {
-                    if num_relics >= 2 {
-                        *bonus.entry(Stats::EnergyRegenerationRate_).or_default() += 5.0;
-                    }
-                }
-                "313" => {
-                    if num_relics >= 2 {
-                        *bonus.entry(Stats::CritRate_).or_default() += 4.0;
-                    }
-                }
-                "314" => {
-                    if num_relics >= 2 {
-                        *bonus.entry(Stats::Atk_).or_default() += 12.0;
-                    }
-                }
-                "315" => {}
-                "316" => {
-                    if num_relics >= 2 {
-                        *bonus.entry(Stats::Spd_).or_default() += 6.0;
-                    }
-                }
-                "317" => {
-                    if num_relics >= 2 {
-                        *bonus.entry(Stats::EnergyRegenerationRate_).or_default() += 5.0;
-                    }
-                }
-                "318" => {
-                    if num_relics >= 2 {
-                        *bonus.entry(Stats::CritDmg_).or_default() += 16.0;
-                    }
-                }
-                "319" => {
-                    if num_relics >= 2 {
-                        *bonus.entry(Stats::Hp_).or_default() += 12.0;
-                    }
-                }
-                "320" => {
-                    if num_relics >= 2 {
-                        *bonus.entry(Stats::Spd_).or_default() += 6.0;
-                    }
-                }
-                "321" => {}
-                "322" => {
-                    if num_relics >= 2 {
-                        *bonus.entry(Stats::Atk_).or_default() += 12.0;
-                    }
-                }
-                _ => todo!(),
+        for (set, num_relics) in self.relic_sets()? {
+            for effect in set.effects(num_relics).filter(|e| !e.battle) {
+                *bonus.entry(effect.stat.clone()).or_default() +=
+                    effect.bonus(attack_type, &self.config, None)?;
             }
         }
         Ok(())
@@ -356,346 +243,69 @@ impl Relics {
         teammates: &[Box<dyn Support>],
     ) -> Result<HashMap<Stats, f64>> {
         let mut bonus = HashMap::new();
-        let count = self.relics.iter().counts_by(|r| r.set_id.clone());
-        for (set_id, num_relics) in count {
-            match set_id.as_str() {
-                "101" => {}
-                "102" => {
-                    if num_relics >= 4
-                        && self.config.activate_102
-                        && *skill_type == SkillType::BasicAttack
-                    {
-                        *bonus.entry(Stats::DmgBoost_).or_default() += 10.0;
-                    }
-                }
-                "103" => {}
-                "104" => {
-                    if num_relics >= 4 && self.config.activate_104 {
-                        *bonus.entry(Stats::CritDmg_).or_default() += 25.0;
-                    }
-                }
-                "105" => {
-                    if num_relics >= 4 {
-                        let stack = min(5, self.config.stack_105);
-                        *bonus.entry(Stats::Atk_).or_default() += 5.0 * stack as f64;
-                    }
-                }
-                "107" => {
-                    if num_relics >= 4 {
-                        if *skill_type == SkillType::Skill {
-                            *bonus.entry(Stats::DmgBoost_).or_default() += 12.0;
-                        }
-                        if *attack_type == AttackType::Fire && self.config.activate_107 {
-                            *bonus.entry(Stats::DmgBoost_).or_default() += 12.0;
-                        }
-                    }
-                }
-                "108" => {
-                    if num_relics >= 4 && self.config.activate_108 {
-                        *bonus.entry(Stats::DefIgnore_).or_default() += 10.0;
-                    }
-                }
-                "109" => {
-                    if num_relics >= 4 && self.config.activate_109 {
-                        *bonus.entry(Stats::Atk_).or_default() += 20.0;
-                    }
-                }
-                "110" => {}
-                "111" => {}
-                "112" => {
-                    if num_relics >= 4 {
-                        if self.config.activate_112_1 {
-                            *bonus.entry(Stats::CritRate_).or_default() += 10.0;
-                        }
-                        if self.config.activate_112_2 {
-                            *bonus.entry(Stats::CritDmg_).or_default() += 20.0;
-                        }
-                    }
-                }
-                "113" => {
-                    if num_relics >= 4 {
-                        let stack = min(2, self.config.stack_113);
-                        *bonus.entry(Stats::CritRate_).or_default() += 8.0 * stack as f64;
-                    }
-                }
-                "114" => {}
-                "115" => {
-                    if num_relics >= 2 && *skill_type == SkillType::FollowUpAttack {
-                        *bonus.entry(Stats::DmgBoost_).or_default() += 20.0;
-                    }
-                    if num_relics >= 4 {
-                        let stack = min(8, self.config.stack_115);
-                        *bonus.entry(Stats::Atk_).or_default() += 6.0 * stack as f64;
-                    }
-                }
-                "116" => {
-                    if num_relics >= 4 {
-                        let stack = min(3, self.config.stack_116);
-                        *bonus.entry(Stats::DefIgnore_).or_default() += 6.0 * stack as f64;
-                    }
-                }
-                "117" => {
-                    if num_relics >= 2 && self.config.activate_117_2pcs {
-                        *bonus.entry(Stats::DmgBoost_).or_default() += 12.0;
-                    }
-                    if num_relics >= 4 {
-                        if self.config.stack_117 == 2 {
-                            if self.config.activate_117_4pcs_extra {
-                                *bonus.entry(Stats::CritDmg_).or_default() += 16.0;
-                            } else {
-                                *bonus.entry(Stats::CritDmg_).or_default() += 8.0;
-                            }
-                        } else if self.config.stack_117 >= 3 {
-                            if self.config.activate_117_4pcs_extra {
-                                *bonus.entry(Stats::CritDmg_).or_default() += 24.0;
-                            } else {
-                                *bonus.entry(Stats::CritDmg_).or_default() += 12.0;
-                            }
-                        }
-                    }
-                }
-                "118" => {}
-                "119" => {
-                    if num_relics >= 4 {
-                        if *base_stats
-                            .get(&Stats::BreakEffect_)
-                            .ok_or(eyre!("Missing break effect"))?
-                            >= 150.0
-                            && *damage_type == DamageType::BreakDamage
-                        {
-                            *bonus.entry(Stats::DefIgnore_).or_default() += 10.0;
-                        } else if *base_stats
-                            .get(&Stats::BreakEffect_)
-                            .ok_or(eyre!("Missing break effect"))?
-                            >= 250.0
-                            && *damage_type == DamageType::SuperBreakDamage
-                        {
-                            *bonus.entry(Stats::DefIgnore_).or_default() += 25.0;
-                        }
-                    }
-                }
-                "120" => {
-                    if num_relics >= 4
-                        && self.config.activate_120
-                        && *skill_type == SkillType::Ultimate
-                    {
-                        *bonus.entry(Stats::DmgBoost_).or_default() += 36.0;
-                    }
-                }
-                "121" => {}
-                "122" => {
-                    if num_relics >= 4 {
-                        if *skill_type == SkillType::Skill || *skill_type == SkillType::Ultimate {
-                            *bonus.entry(Stats::DmgBoost_).or_default() += 20.0;
-                        }
-                        if *skill_type == SkillType::Skill && self.config.activate_122 {
-                            *bonus.entry(Stats::DmgBoost_).or_default() += 25.0;
-                        }
-                    }
-                }
-                "123" => {
-                    if num_relics >= 4 {
-                        if self.config.activate_123_1 {
-                            *bonus.entry(Stats::Spd_).or_default() += 6.0;
-                        }
-                        if self.config.activate_123_2 {
-                            *bonus.entry(Stats::CritDmg_).or_default() += 30.0;
-                        }
-                    }
-                }
-                "124" => {
-                    if num_relics >= 4 {
-                        if *base_stats.get(&Stats::Spd).ok_or(eyre!("Missing SPD"))? < 95.0 {
-                            *bonus.entry(Stats::CritRate_).or_default() += 32.0;
-                        } else if *base_stats.get(&Stats::Spd).ok_or(eyre!("Missing SPD"))? < 110.0
-                        {
-                            *bonus.entry(Stats::CritRate_).or_default() += 20.0;
-                        }
-                    }
-                }
-                "125" => {
-                    if num_relics >= 4 && self.config.activate_125 {
-                        *bonus.entry(Stats::Spd_).or_default() += 6.0;
-                        *bonus.entry(Stats::CritDmg_).or_default() += 15.0;
-                    }
-                }
-                "126" => {
-                    if num_relics >= 4
-                        && self.config.activate_126
-                        && *skill_type == SkillType::Ultimate
-                    {
-                        *bonus.entry(Stats::Atk_).or_default() += 48.0;
-                    }
-                }
-                "301" => {
-                    if num_relics >= 2 {
-                        if *base_stats.get(&Stats::Spd).ok_or(eyre!("Missing SPD"))? >= 120.0 {
-                            *bonus.entry(Stats::Atk_).or_default() += 12.0;
-                        }
-                    }
-                }
-                "302" => {}
-                "303" => {
-                    if num_relics >= 2 {
-                        let curr_effect_hit_rate = base_stats
-                            .get(&Stats::EffectHitRate_)
-                            .ok_or(eyre!("Missing effect hit rate"))?;
-                        let atk_bonus = curr_effect_hit_rate * 0.25;
-                        if atk_bonus <= 25.0 {
-                            *bonus.entry(Stats::Atk_).or_default() += atk_bonus;
-                        } else {
-                            *bonus.entry(Stats::Atk_).or_default() += 25.0;
-                        }
-                    }
-                }
-                "304" => {
-                    if num_relics >= 2 {
-                        if *base_stats
-                            .get(&Stats::EffectHitRate_)
-                            .ok_or(eyre!("Missing effect hit rate"))?
-                            >= 50.0
-                        {
-                            *bonus.entry(Stats::Def_).or_default() += 15.0;
-                        }
-                    }
-                }
-                "305" => {
-                    if num_relics >= 2 {
-                        if self.config.activate_305
-                            && *base_stats
-                                .get(&Stats::CritDmg_)
-                                .ok_or(eyre!("Missing CRIT DMG"))?
-                                >= 120.0
-                        {
-                            *bonus.entry(Stats::CritRate_).or_default() += 60.0;
-                        }
-                    }
-                }
-                "306" => {
-                    if num_relics >= 2 {
-                        if *base_stats
-                            .get(&Stats::CritRate_)
-                            .ok_or(eyre!("Missing CRIT Rate"))?
-                            >= 50.0
-                            && *skill_type == SkillType::Ultimate
-                            || *skill_type == SkillType::FollowUpAttack
-                        {
-                            *bonus.entry(Stats::DmgBoost_).or_default() += 15.0;
-                        }
-                    }
-                }
-                "307" => {
-                    if num_relics >= 2 {
-                        if *base_stats
-                            .get(&Stats::Spd)
-                            .ok_or(eyre!("Missing CRIT Rate"))?
-                            >= 145.0
-                        {
-                            *bonus.entry(Stats::BreakEffect_).or_default() += 20.0;
-                        }
-                    }
-                }
-                "308" => {}
-                "309" => {
-                    if num_relics >= 2 {
-                        if *base_stats
-                            .get(&Stats::CritRate_)
-                            .ok_or(eyre!("Missing CRIT Rate"))?
-                            >= 70.0
-                            && *skill_type == SkillType::BasicAttack
-                            || *skill_type == SkillType::Skill
-                        {
-                            *bonus.entry(Stats::DmgBoost_).or_default() += 20.0;
-                        }
-                    }
-                }
-                "310" => {}
-                "311" => {
-                    if num_relics >= 2 {
-                        if *base_stats.get(&Stats::Spd).ok_or(eyre!("Missing SPD"))? >= 160.0 {
-                            *bonus.entry(Stats::DmgBoost_).or_default() += 18.0;
-                        } else if *base_stats.get(&Stats::Spd).ok_or(eyre!("Missing SPD"))? >= 135.0
-                        {
-                            *bonus.entry(Stats::DmgBoost_).or_default() += 12.0;
-                        }
-                    }
-                }
-                "312" => {}
-                "313" => {
-                    if num_relics >= 2 {
-                        *bonus.entry(Stats::CritDmg_).or_default() +=
-                            4.0 * self.config.stack_313 as f64;
-                    }
-                }
-                "314" => {
-                    if num_relics >= 2 {
-                        if teammates.iter().any(|t| t.get_path() == path) {
-                            *bonus.entry(Stats::CritRate_).or_default() += 12.0;
-                        }
-                    }
-                }
-                "315" => {
-                    if num_relics >= 2 && *skill_type == SkillType::FollowUpAttack {
-                        let num_stack = min(5, self.config.stack_315);
-                        *bonus.entry(Stats::DmgBoost_).or_default() += 5.0 * num_stack as f64;
-                        if num_stack == 5 {
-                            *bonus.entry(Stats::CritDmg_).or_default() += 25.0
-                        }
-                    }
-                }
-                "316" => {
-                    if num_relics >= 2 && self.config.activate_316 {
-                        *bonus.entry(Stats::BreakEffect_).or_default() += 40.0;
-                    }
-                }
-                "317" => {}
-                "318" => {
-                    if num_relics >= 2 && self.config.activate_318 {
-                        *bonus.entry(Stats::CritDmg_).or_default() += 32.0;
-                    }
-                }
-                "319" => {
-                    if num_relics >= 2
-                        && *base_stats.get(&Stats::Hp).ok_or(eyre!("Missing HP"))? >= 5000.0
-                    {
-                        *bonus.entry(Stats::CritDmg_).or_default() += 28.0;
-                    }
-                }
-                "320" => {
-                    if num_relics >= 2 {
-                        if *base_stats.get(&Stats::Spd).ok_or(eyre!("Missing SPD"))? >= 180.0 {
-                            *bonus.entry(Stats::OutgoingHealingBoost_).or_default() += 20.0;
-                        } else if *base_stats.get(&Stats::Spd).ok_or(eyre!("Missing SPD"))? >= 135.0
-                        {
-                            *bonus.entry(Stats::OutgoingHealingBoost_).or_default() += 12.0;
-                        }
-                    }
-                }
-                "321" => {
-                    if num_relics >= 2 {
-                        if self.config.stack_321 > 4 {
-                            *bonus.entry(Stats::DmgBoost_).or_default() +=
-                                9.0 * std::cmp::min(4, self.config.stack_321) as f64;
-                        } else if self.config.stack_321 < 4 {
-                            *bonus.entry(Stats::DmgBoost_).or_default() +=
-                                12.0 * std::cmp::min(3, 4 - self.config.stack_321) as f64;
-                        }
-                    }
-                }
-                "322" => {
-                    if num_relics >= 2 && *damage_type == DamageType::DamageOnTime {
-                        if *base_stats.get(&Stats::Atk).ok_or(eyre!("Missing ATK"))? >= 3600.0 {
-                            *bonus.entry(Stats::DmgBoost_).or_default() += 24.0
-                        } else if *base_stats.get(&Stats::Atk).ok_or(eyre!("Missing ATK"))?
-                            >= 2400.0
-                        {
-                            *bonus.entry(Stats::DmgBoost_).or_default() += 12.0
-                        }
-                    }
-                }
-                _ => todo!(),
+        let battle = BattleContext {
+            path: &path,
+            skill_type,
+            damage_type,
+            base_stats,
+            teammates,
+        };
+        for (set, num_relics) in self.relic_sets()? {
+            for effect in set.effects(num_relics).filter(|e| e.battle) {
+                *bonus.entry(effect.stat.clone()).or_default() +=
+                    effect.bonus(attack_type, &self.config, Some(&battle))?;
             }
         }
         Ok(bonus)
     }
+
+    /// Sets of which at least 2 pieces are worn, by name with their number of pieces, most
+    /// pieces first.
+    pub fn set_combination(&self) -> Result<Vec<(String, usize)>> {
+        let sets = relic_sets()?;
+        Ok(self
+            .relics
+            .iter()
+            .counts_by(|r| r.set_id.as_str())
+            .into_iter()
+            .filter(|(_, num_relics)| *num_relics >= 2)
+            .map(|(set_id, num_relics)| {
+                let name = sets.get(set_id).map_or(set_id, |set| set.name.as_str());
+                (name.to_string(), num_relics)
+            })
+            .sorted_by(|(a_name, a), (b_name, b)| b.cmp(a).then_with(|| a_name.cmp(b_name)))
+            .collect())
+    }
+
+    /// The known sets among the relics with their number of pieces. Sets missing from the
+    /// set data grant no bonus.
+    fn relic_sets(&self) -> Result<Vec<(&'static RelicSet, usize)>> {
+        let sets = relic_sets()?;
+        Ok(self
+            .relics
+            .iter()
+            .counts_by(|r| r.set_id.as_str())
+            .into_iter()
+            .filter_map(|(set_id, num_relics)| Some((sets.get(set_id)?, num_relics)))
+            .collect())
+    }
+}
+
+/// The stat a main stat adds to, a DMG boost of the character's element counting as `DmgBoost_`.
+fn main_stat_key(relic: &Relic, attack_type: &AttackType) -> Stats {
+    let element = match relic.mainstat {
+        Stats::LightningDmgBoost_ => AttackType::Lightning,
+        Stats::WindDmgBoost_ => AttackType::Wind,
+        Stats::FireDmgBoost_ => AttackType::Fire,
+        Stats::IceDmgBoost_ => AttackType::Ice,
+        Stats::QuantumDmgBoost_ => AttackType::Quantum,
+        Stats::ImaginaryDmgBoost_ => AttackType::Imaginary,
+        Stats::PhysicalDmgBoost_ => AttackType::Physical,
+        _ => return relic.mainstat.clone(),
+    };
+    if element == *attack_type {
+        Stats::DmgBoost_
+    } else {
+        relic.mainstat.clone()
+    }
 }
@@ -0,0 +1,289 @@
+use super::{AttackType, DamageType, Path, RelicSetConfig, SkillType, Stats};
+use crate::character::Support;
+use eyre::{bail, eyre, Result};
+use serde::Deserialize;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::OnceLock,
+};
+
+/// Relic set effects, see the header of the file for the format.
+const SET_BONUS_YAML: &str = include_str!("../config/set_bonus.yaml");
+
+static RELIC_SETS: OnceLock<Result<HashMap<String, RelicSet>, String>> = OnceLock::new();
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct RelicSet {
+    pub name: String,
+    #[serde(default, rename = "2pc")]
+    pub two_pieces: Vec<SetEffect>,
+    #[serde(default, rename = "4pc")]
+    pub four_pieces: Vec<SetEffect>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct SetEffect {
+    pub stat: Stats,
+    #[serde(default)]
+    pub value: f64,
+    #[serde(default)]
+    pub battle: bool,
+    #[serde(default)]
+    pub attack_types: Vec<AttackType>,
+    #[serde(default)]
+    pub skill_types: Vec<SkillType>,
+    #[serde(default)]
+    pub damage_types: Vec<DamageType>,
+    #[serde(default)]
+    pub when: Vec<Condition>,
+    pub stacks: Option<Stacks>,
+    pub scale: Option<Scale>,
+}
+
+/// Holds when the quantity it reads is within `[min, below)`, or is non-zero without bounds.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Condition {
+    pub config: Option<String>,
+    pub stat: Option<Stats>,
+    pub same_path_teammates: bool,
+    pub min: Option<f64>,
+    pub below: Option<f64>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Stacks {
+    pub config: String,
+    pub max: u8,
+    pub above: Option<f64>,
+    pub below: Option<f64>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Scale {
+    pub stat: Stats,
+    pub ratio: f64,
+    pub cap: Option<f64>,
+}
+
+/// The hit being evaluated, only known during battle.
+pub struct BattleContext<'a> {
+    pub path: &'a Path,
+    pub skill_type: &'a SkillType,
+    pub damage_type: &'a DamageType,
+    pub base_stats: &'a HashMap<Stats, f64>,
+    pub teammates: &'a [Box<dyn Support>],
+}
+
+/// All known relic sets keyed by set ID.
+pub fn relic_sets() -> Result<&'static HashMap<String, RelicSet>> {
+    RELIC_SETS
+        .get_or_init(|| parse_relic_sets(SET_BONUS_YAML).map_err(|e| format!("{e:#}")))
+        .as_ref()
+        .map_err(|e| eyre!("Invalid relic set data: {e}"))
+}
+
+/// Config entries read by at least one set effect.
+pub fn config_keys() -> Result<HashSet<&'static str>> {
+    Ok(relic_sets()?
+        .values()
+        .flat_map(|set| set.two_pieces.iter().chain(&set.four_pieces))
+        .flat_map(|effect| {
+            effect
+                .when
+                .iter()
+                .filter_map(|c| c.config.as_deref())
+                .chain(effect.stacks.as_ref().map(|s| s.config.as_str()))
+        })
+        .collect())
+}
+
+fn parse_relic_sets(yaml: &str) -> Result<HashMap<String, RelicSet>> {
+    let sets: HashMap<String, RelicSet> = serde_yaml::from_str(yaml)?;
+    for (set_id, set) in &sets {
+        for effect in set.two_pieces.iter().chain(&set.four_pieces) {
+            effect
+                .validate()
+                .map_err(|e| eyre!("Set {set_id} ({}): {e}", set.name))?;
+        }
+    }
+    Ok(sets)
+}
+
+impl RelicSet {
+    /// Effects unlocked by wearing `num_relics` pieces of the set.
+    pub fn effects(&self, num_relics: usize) -> impl Iterator<Item = &SetEffect> {
+        let two_pieces: &[SetEffect] = if num_relics >= 2 {
+            &self.two_pieces
+        } else {
+            &[]
+        };
+        let four_pieces: &[SetEffect] = if num_relics >= 4 {
+            &self.four_pieces
+        } else {
+            &[]
+        };
+        two_pieces.iter().chain(four_pieces)
+    }
+}
+
+impl SetEffect {
+    fn validate(&self) -> Result<()> {
+        for condition in &self.when {
+            let sources = condition.config.is_some() as u8
+                + condition.stat.is_some() as u8
+                + condition.same_path_teammates as u8;
+            if sources != 1 {
+                bail!("a condition must read exactly one of config, stat or same_path_teammates");
+            }
+        }
+        let needs_battle = !self.skill_types.is_empty()
+            || !self.damage_types.is_empty()
+            || self.scale.is_some()
+            || self
+                .when
+                .iter()
+                .any(|c| c.stat.is_some() || c.same_path_teammates);
+        if needs_battle && !self.battle {
+            bail!(
+                "{:?} depends on the battle state and must be marked `battle: true`",
+                self.stat
+            );
+        }
+        Ok(())
+    }
+
+    /// Value added to `self.stat`, zero when the effect does not apply. Type filters and stat
+    /// conditions are only checked with a battle context.
+    pub fn bonus(
+        &self,
+        attack_type: &AttackType,
+        config: &RelicSetConfig,
+        battle: Option<&BattleContext>,
+    ) -> Result<f64> {
+        if !self.attack_types.is_empty() && !self.attack_types.contains(attack_type) {
+            return Ok(0.0);
+        }
+        if let Some(battle) = battle {
+            if !self.skill_types.is_empty() && !self.skill_types.contains(battle.skill_type) {
+                return Ok(0.0);
+            }
+            if !self.damage_types.is_empty() && !self.damage_types.contains(battle.damage_type) {
+                return Ok(0.0);
+            }
+        }
+        for condition in &self.when {
+            if !condition.holds(config, battle)? {
+                return Ok(0.0);
+            }
+        }
+        let mut value = self.value;
+        if let Some(stacks) = &self.stacks {
+            value *= stacks.count(config);
+        }
+        if let Some(scale) = &self.scale {
+            let battle = battle.ok_or(eyre!("Scaling effects are only known in battle"))?;
+            value += scale.bonus(battle.base_stats)?;
+        }
+        Ok(value)
+    }
+}
+
+impl Condition {
+    fn holds(&self, config: &RelicSetConfig, battle: Option<&BattleContext>) -> Result<bool> {
+        let quantity = if let Some(key) = &self.config {
+            config.get(key)
+        } else if let Some(stat) = &self.stat {
+            let battle = battle.ok_or(eyre!("{stat:?} is only known in battle"))?;
+            base_stat(battle.base_stats, stat)?
+        } else {
+            let battle = battle.ok_or(eyre!("Teammates are only known in battle"))?;
+            battle
+                .teammates
+                .iter()
+                .filter(|t| t.get_path() == *battle.path)
+                .count() as f64
+        };
+        Ok(match (self.min, self.below) {
+            (None, None) => quantity != 0.0,
+            (min, below) => {
+                min.is_none_or(|min| quantity >= min) && below.is_none_or(|b| quantity < b)
+            }
+        })
+    }
+}
+
+impl Stacks {
+    fn count(&self, config: &RelicSetConfig) -> f64 {
+        let value = config.get(&self.config);
+        let count = match (self.above, self.below) {
+            (Some(above), _) => value - above,
+            (None, Some(below)) => below - value,
+            (None, None) => value,
+        };
+        count.clamp(0.0, self.max as f64)
+    }
+}
+
+impl Scale {
+    fn bonus(&self, base_stats: &HashMap<Stats, f64>) -> Result<f64> {
+        let bonus = base_stat(base_stats, &self.stat)? * self.ratio;
+        Ok(self.cap.map_or(bonus, |cap| bonus.min(cap)))
+    }
+}
+
+fn base_stat(base_stats: &HashMap<Stats, f64>, stat: &Stats) -> Result<f64> {
+    base_stats
+        .get(stat)
+        .cloned()
+        .ok_or(eyre!("Missing {stat:?}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relic_sets_load() -> Result<()> {
+        let sets = relic_sets()?;
+        assert_eq!(sets["321"].name, "Arcadia of Woven Dreams");
+        assert!(config_keys()?.contains("stack_105"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_arcadia_stacks() -> Result<()> {
+        let arcadia = &relic_sets()?["321"];
+        let dmg_boost = |allies: u8| -> Result<f64> {
+            let config = RelicSetConfig {
+                stack_321: allies,
+                ..Default::default()
+            };
+            let battle = BattleContext {
+                path: &Path::Nihility,
+                skill_type: &SkillType::Ultimate,
+                damage_type: &DamageType::Normal,
+                base_stats: &HashMap::new(),
+                teammates: &[],
+            };
+            arcadia
+                .effects(2)
+                .map(|e| e.bonus(&AttackType::Lightning, &config, Some(&battle)))
+                .sum()
+        };
+        assert_eq!(dmg_boost(2)?, 24.0);
+        assert_eq!(dmg_boost(4)?, 0.0);
+        assert_eq!(dmg_boost(5)?, 9.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_effect_needs_battle() {
+        let yaml = "999:\n  name: Test\n  2pc:\n    - { stat: ATK_, value: 12, when: [{ stat: SPD, min: 120 }] }\n";
+        assert!(parse_relic_sets(yaml).is_err());
+    }
+}
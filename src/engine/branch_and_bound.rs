@@ -0,0 +1,668 @@
+use super::{
+    constraints::{Bound, Constrained},
+    ranking::{rank, RankedBuild},
+};
+use crate::{
+    character::{Evaluator, Support},
+    domain::{Encounter, Relic, RelicSetConfig, Relics, Slot, Stats, SubStats},
+};
+use eyre::{bail, Result};
+use itertools::Itertools;
+use rayon::prelude::*;
+use std::{collections::HashMap, fmt::Display, sync::Arc};
+use tracing::info;
+
+const CAVERN_SLOTS: [Slot; 4] = [Slot::Head, Slot::Hands, Slot::Body, Slot::Feet];
+const PLANAR_SLOTS: [Slot; 2] = [Slot::PlanarSphere, Slot::LinkRope];
+
+/// Exact search over the relic pool, an alternative to the genetic `Optimizer`.
+///
+/// Builds are grouped by the set bonuses they unlock, e.g. 4 pieces of a set or 2+2, and the
+/// groups are searched most promising first. Within a group relics are picked slot by slot, and
+/// a partial build is dropped as soon as the best stats still available in the remaining slots
+/// can't beat the builds already found.
+///
+/// The result is exact as long as the score never decreases when a stat increases, which holds
+/// for damage. Stat constraints are checked on complete builds only, so that a ceiling such as
+/// `SPD<=134` never prunes the search.
+pub struct BranchAndBound<T> {
+    pub relic_pool: HashMap<Slot, Vec<Relic>>,
+    pub evaluator: Arc<dyn Evaluator<Target = T> + Sync + Send>,
+    pub target: T,
+    pub encounter: Encounter,
+    pub teammates: Vec<Box<dyn Support>>,
+    pub relic_set_config: RelicSetConfig,
+    /// Number of builds to return.
+    pub top: usize,
+    /// Stat constraints every build returned meets, `evaluator` being left unconstrained.
+    pub constraints: Option<Constrained>,
+}
+
+/// Set bonuses a build must unlock: pieces required per set, the other slots of each group,
+/// cavern then planar, being free to hold any relic.
+#[derive(Clone, Debug)]
+struct SetPlan {
+    required: [Vec<(String, usize)>; 2],
+    free: [usize; 2],
+}
+
+/// Stats a slot can bring at best, over every relic and per set.
+struct SlotBounds {
+    any: HashMap<Stats, f64>,
+    per_set: HashMap<String, HashMap<Stats, f64>>,
+}
+
+/// Builds found so far, best first.
+struct Ranking {
+    top: usize,
+    builds: Vec<(f64, Relics)>,
+    evaluated: usize,
+}
+
+impl Ranking {
+    /// Score a build must beat to enter the ranking.
+    fn threshold(&self) -> f64 {
+        if self.builds.len() < self.top {
+            f64::NEG_INFINITY
+        } else {
+            self.builds[self.builds.len() - 1].0
+        }
+    }
+
+    fn offer(&mut self, score: f64, relics: Relics) {
+        if score <= self.threshold() {
+            return;
+        }
+        let uids = |relics: &Relics| {
+            relics
+                .relics
+                .iter()
+                .map(|r| r.uid.clone())
+                .sorted()
+                .collect_vec()
+        };
+        let key = uids(&relics);
+        if self.builds.iter().any(|(_, build)| uids(build) == key) {
+            return;
+        }
+        let position = self.builds.partition_point(|(other, _)| *other >= score);
+        self.builds.insert(position, (score, relics));
+        self.builds.truncate(self.top);
+    }
+}
+
+impl<T: Sync + Display> BranchAndBound<T> {
+    /// Best builds of the pool, best first.
+    pub fn solve(&self) -> Result<Vec<RankedBuild>> {
+        if self.top == 0 {
+            bail!("The number of builds to return must be at least 1");
+        }
+        let slots: Vec<Slot> = CAVERN_SLOTS
+            .iter()
+            .chain(&PLANAR_SLOTS)
+            .filter(|slot| self.relic_pool.get(slot).is_some_and(|r| !r.is_empty()))
+            .cloned()
+            .collect();
+        let ceilings = self.ceiling_stats();
+        let pool: HashMap<Slot, Vec<Relic>> = slots
+            .iter()
+            .map(|slot| {
+                let relics = undominated(&self.relic_pool[slot], self.top, &ceilings);
+                (slot.clone(), relics)
+            })
+            .collect();
+        let bounds: HashMap<Slot, SlotBounds> = pool
+            .iter()
+            .map(|(slot, relics)| (slot.clone(), SlotBounds::new(relics)))
+            .collect();
+        info!(
+            "Exact search over {} relics, {} after removing dominated ones",
+            self.relic_pool.values().map(Vec::len).sum::<usize>(),
+            pool.values().map(Vec::len).sum::<usize>()
+        );
+
+        let search = Search {
+            solver: self,
+            slots: &slots,
+            pool: &pool,
+            bounds: &bounds,
+        };
+        let mut plans = set_plans(&pool, &slots)
+            .into_par_iter()
+            .map(|plan| Ok((search.bound(&[], &plan)?, plan)))
+            .collect::<Result<Vec<_>>>()?;
+        plans.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+
+        let mut ranking = Ranking {
+            top: self.top,
+            builds: vec![],
+            evaluated: plans.len(),
+        };
+        for (bound, plan) in &plans {
+            if *bound <= ranking.threshold() {
+                break;
+            }
+            search.branch(&mut vec![], plan, &mut ranking)?;
+        }
+        info!(
+            "Exact search done, {} set plans and {} builds evaluated, best {}: {}",
+            plans.len(),
+            ranking.evaluated,
+            self.target,
+            ranking.builds.first().map_or(f64::NAN, |(score, _)| *score)
+        );
+        let candidates = ranking
+            .builds
+            .into_iter()
+            .map(|(score, relics)| (relics, score))
+            .collect();
+        rank(candidates, self.top)
+    }
+
+    /// Relic stats raising a stat with a ceiling, which a relic can't be better in.
+    fn ceiling_stats(&self) -> Vec<Stats> {
+        self.constraints
+            .iter()
+            .flat_map(|constrained| &constrained.constraints)
+            .filter(|constraint| constraint.bound == Bound::Max)
+            .flat_map(|constraint| match constraint.stat {
+                Stats::Hp => vec![Stats::Hp, Stats::Hp_],
+                Stats::Atk => vec![Stats::Atk, Stats::Atk_],
+                Stats::Def => vec![Stats::Def, Stats::Def_],
+                Stats::Spd => vec![Stats::Spd, Stats::Spd_],
+                ref stat => vec![stat.clone()],
+            })
+            .collect()
+    }
+
+    /// Whether the build meets every stat constraint.
+    fn feasible(&self, relics: &Relics) -> Result<bool> {
+        let Some(constrained) = &self.constraints else {
+            return Ok(true);
+        };
+        Ok(constrained.check(relics)?.iter().all(|c| c.satisfied()))
+    }
+
+    fn evaluate(&self, relics: Vec<Relic>) -> Result<f64> {
+        let relics = Relics {
+            relics,
+            config: self.relic_set_config.clone(),
+        };
+        self.evaluator
+            .evaluate(&relics, &self.encounter, &self.target, &self.teammates)
+    }
+}
+
+struct Search<'a, T> {
+    solver: &'a BranchAndBound<T>,
+    slots: &'a [Slot],
+    pool: &'a HashMap<Slot, Vec<Relic>>,
+    bounds: &'a HashMap<Slot, SlotBounds>,
+}
+
+impl<T: Sync + Display> Search<'_, T> {
+    /// Tries every relic of the next slot allowed by the plan, best bound first.
+    fn branch(&self, chosen: &mut Vec<Relic>, plan: &SetPlan, ranking: &mut Ranking) -> Result<()> {
+        let slot = &self.slots[chosen.len()];
+        let last = chosen.len() + 1 == self.slots.len();
+        let mut children = self.pool[slot]
+            .par_iter()
+            .filter_map(|relic| Some((relic, plan.pick(relic, slot)?)))
+            .map(|(relic, plan)| {
+                let mut build = chosen.clone();
+                build.push(relic.clone());
+                Ok((self.bound(&build, &plan)?, relic, plan))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        ranking.evaluated += children.len();
+        children.sort_by(|(a, ..), (b, ..)| b.total_cmp(a));
+
+        for (bound, relic, plan) in children {
+            if bound <= ranking.threshold() {
+                break;
+            }
+            chosen.push(relic.clone());
+            if last {
+                let relics = Relics {
+                    relics: chosen.clone(),
+                    config: self.solver.relic_set_config.clone(),
+                };
+                if self.solver.feasible(&relics)? {
+                    ranking.offer(bound, relics);
+                }
+            } else {
+                self.branch(chosen, &plan, ranking)?;
+            }
+            chosen.pop();
+        }
+        Ok(())
+    }
+
+    /// Score of the chosen relics completed by the best stats the remaining slots can bring
+    /// under the plan, the exact score once every slot is chosen.
+    fn bound(&self, chosen: &[Relic], plan: &SetPlan) -> Result<f64> {
+        let mut relics = chosen.to_vec();
+        let mut labels = plan.required.clone().map(|required| {
+            required
+                .into_iter()
+                .flat_map(|(set_id, pieces)| std::iter::repeat_n(set_id, pieces))
+        });
+        for slot in &self.slots[chosen.len()..] {
+            let group = group(slot);
+            let set_id = labels[group].next().unwrap_or_default();
+            relics.push(phantom(
+                slot,
+                set_id,
+                plan.best_stats(&self.bounds[slot], group),
+            ));
+        }
+        self.solver.evaluate(relics)
+    }
+}
+
+impl SetPlan {
+    /// The plan left once `relic` is worn in `slot`, `None` if the plan doesn't allow it.
+    fn pick(&self, relic: &Relic, slot: &Slot) -> Option<SetPlan> {
+        let mut plan = self.clone();
+        if let Some((_, pieces)) = plan.required[group(slot)]
+            .iter_mut()
+            .find(|(set_id, pieces)| *set_id == relic.set_id && *pieces > 0)
+        {
+            *pieces -= 1;
+        } else {
+            let free = &mut plan.free[group(slot)];
+            *free = free.checked_sub(1)?;
+        }
+        Some(plan)
+    }
+
+    /// Best stats of a slot among the relics the plan still allows there.
+    fn best_stats(&self, bounds: &SlotBounds, group: usize) -> HashMap<Stats, f64> {
+        if self.free[group] > 0 {
+            return bounds.any.clone();
+        }
+        let mut best = HashMap::new();
+        for (set_id, _) in self.required[group]
+            .iter()
+            .filter(|(_, pieces)| *pieces > 0)
+        {
+            for (stat, value) in bounds.per_set.get(set_id).into_iter().flatten() {
+                let entry = best.entry(stat.clone()).or_insert(*value);
+                *entry = entry.max(*value);
+            }
+        }
+        best
+    }
+}
+
+impl SlotBounds {
+    fn new(relics: &[Relic]) -> Self {
+        let mut bounds = SlotBounds {
+            any: HashMap::new(),
+            per_set: HashMap::new(),
+        };
+        for relic in relics {
+            for (stat, value) in contribution(relic) {
+                for best in [
+                    &mut bounds.any,
+                    bounds.per_set.entry(relic.set_id.clone()).or_default(),
+                ] {
+                    let entry = best.entry(stat.clone()).or_insert(value);
+                    *entry = entry.max(value);
+                }
+            }
+        }
+        bounds
+    }
+}
+
+/// 0 for cavern relics, 1 for planar ornaments.
+fn group(slot: &Slot) -> usize {
+    PLANAR_SLOTS.contains(slot) as usize
+}
+
+/// Every combination of set bonuses the pool can unlock: 4 pieces, 2+2, 2 pieces or none for
+/// the cavern relics, crossed with 2 pieces or none for the planar ornaments.
+fn set_plans(pool: &HashMap<Slot, Vec<Relic>>, slots: &[Slot]) -> Vec<SetPlan> {
+    let group_plans = |group_slots: &[Slot]| -> Vec<(Vec<(String, usize)>, usize)> {
+        let size = slots.iter().filter(|s| group_slots.contains(s)).count();
+        let sets = group_slots
+            .iter()
+            .filter_map(|slot| pool.get(slot))
+            .flatten()
+            .map(|relic| relic.set_id.clone())
+            .sorted()
+            .dedup()
+            .collect_vec();
+        // A set can only unlock its bonus when it has a relic in enough slots.
+        let slots_of = |set_id: &String| {
+            group_slots
+                .iter()
+                .filter(|slot| {
+                    pool.get(slot)
+                        .is_some_and(|r| r.iter().any(|r| &r.set_id == set_id))
+                })
+                .count()
+        };
+        let mut plans = vec![(vec![], size)];
+        for set_id in sets.iter().filter(|set_id| slots_of(set_id) >= 2) {
+            plans.push((vec![(set_id.clone(), 2)], size - 2));
+            if slots_of(set_id) >= 4 {
+                plans.push((vec![(set_id.clone(), 4)], size - 4));
+            }
+        }
+        if size >= 4 {
+            for (x, y) in sets
+                .iter()
+                .filter(|set_id| slots_of(set_id) >= 2)
+                .tuple_combinations()
+            {
+                plans.push((vec![(x.clone(), 2), (y.clone(), 2)], size - 4));
+            }
+        }
+        plans
+    };
+    group_plans(&CAVERN_SLOTS)
+        .into_iter()
+        .cartesian_product(group_plans(&PLANAR_SLOTS))
+        .map(|((cavern_sets, cavern), (planar_sets, planar))| SetPlan {
+            required: [cavern_sets, planar_sets],
+            free: [cavern, planar],
+        })
+        .collect()
+}
+
+/// Stats a relic adds to the bonus, its main stat counting as DMG boost when elemental since
+/// the search doesn't know the element of the character.
+fn contribution(relic: &Relic) -> HashMap<Stats, f64> {
+    let mut stats: HashMap<Stats, f64> = HashMap::new();
+    for substat in &relic.substats {
+        *stats.entry(substat.key.clone()).or_default() += substat.value;
+    }
+    let mainstat = match relic.mainstat {
+        Stats::FireDmgBoost_
+        | Stats::IceDmgBoost_
+        | Stats::WindDmgBoost_
+        | Stats::LightningDmgBoost_
+        | Stats::QuantumDmgBoost_
+        | Stats::ImaginaryDmgBoost_
+        | Stats::PhysicalDmgBoost_ => Stats::DmgBoost_,
+        ref stat => stat.clone(),
+    };
+    *stats.entry(mainstat).or_default() += relic.mainstat_value;
+    stats
+}
+
+/// A relic that doesn't exist, holding the given stats.
+fn phantom(slot: &Slot, set_id: String, stats: HashMap<Stats, f64>) -> Relic {
+    Relic {
+        set_id,
+        slot: slot.clone(),
+        substats: stats
+            .into_iter()
+            .map(|(key, value)| SubStats { key, value })
+            .collect(),
+        uid: format!("bound_{slot:?}"),
+        ..Default::default()
+    }
+}
+
+/// Whether `a` is at least as good as `b` in every stat while being of the same set and main
+/// stat, an elemental DMG boost only counting for a character of that element. More of a stat
+/// with a ceiling isn't better, so both must have the same of those.
+fn dominates(
+    a: &Relic,
+    b: &Relic,
+    a_stats: &HashMap<Stats, f64>,
+    b_stats: &HashMap<Stats, f64>,
+    ceilings: &[Stats],
+) -> bool {
+    a.set_id == b.set_id
+        && a.mainstat == b.mainstat
+        && ceilings
+            .iter()
+            .all(|stat| a_stats.get(stat) == b_stats.get(stat))
+        && b_stats
+            .iter()
+            .all(|(stat, value)| a_stats.get(stat).is_some_and(|a| a >= value))
+        && (a_stats != b_stats || a.uid < b.uid)
+}
+
+/// Relics of a slot without those at least `top` others are better than in every stat, which
+/// can't make it into the best `top` builds.
+fn undominated(relics: &[Relic], top: usize, ceilings: &[Stats]) -> Vec<Relic> {
+    let stats = relics.iter().map(contribution).collect_vec();
+    relics
+        .iter()
+        .enumerate()
+        .filter(|(i, relic)| {
+            relics
+                .iter()
+                .enumerate()
+                .filter(|(j, other)| {
+                    i != j && dominates(other, relic, &stats[*j], &stats[*i], ceilings)
+                })
+                .count()
+                < top
+        })
+        .map(|(_, relic)| relic.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{AttackType, BaseStats, Character, CharacterSkills, CharacterTraces, Path};
+
+    /// ATK% of the build and DMG boost of a Fire character, plus 10 for each set of which at
+    /// least 2 pieces are worn.
+    struct AtkEvaluator;
+
+    impl Evaluator for AtkEvaluator {
+        type Target = String;
+
+        fn evaluate(
+            &self,
+            relics: &Relics,
+            _encounter: &Encounter,
+            _target: &Self::Target,
+            _teammates: &[Box<dyn Support>],
+        ) -> Result<f64> {
+            let atk: f64 = relics
+                .relics
+                .iter()
+                .map(|r| {
+                    let fire = match r.mainstat {
+                        Stats::FireDmgBoost_ => r.mainstat_value,
+                        _ => 0.0,
+                    };
+                    r.substats
+                        .iter()
+                        .filter(|s| matches!(s.key, Stats::Atk_ | Stats::DmgBoost_))
+                        .map(|s| s.value)
+                        .sum::<f64>()
+                        + fire
+                })
+                .sum();
+            let sets = relics
+                .relics
+                .iter()
+                .filter(|r| !r.set_id.is_empty())
+                .counts_by(|r| r.set_id.clone())
+                .into_values()
+                .filter(|pieces| *pieces >= 2)
+                .count();
+            Ok(atk + 10.0 * sets as f64)
+        }
+
+        fn targets(&self) -> Vec<String> {
+            vec![]
+        }
+    }
+
+    fn relic(uid: &str, slot: &Slot, set_id: &str, atk: f64) -> Relic {
+        Relic {
+            uid: uid.to_string(),
+            slot: slot.clone(),
+            set_id: set_id.to_string(),
+            substats: vec![SubStats {
+                key: Stats::Atk_,
+                value: atk,
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_matches_exhaustive_search() -> Result<()> {
+        let mut relic_pool = HashMap::new();
+        for (i, slot) in CAVERN_SLOTS.iter().chain(&PLANAR_SLOTS).enumerate() {
+            let (a, b) = if group(slot) == 0 {
+                ("101", "102")
+            } else {
+                ("301", "302")
+            };
+            let relics = (0..4)
+                .map(|j| {
+                    let set_id = if (i + j) % 2 == 0 { a } else { b };
+                    relic(
+                        &format!("{i}{j}"),
+                        slot,
+                        set_id,
+                        ((i * 7 + j * 5) % 11) as f64,
+                    )
+                })
+                .collect();
+            relic_pool.insert(slot.clone(), relics);
+        }
+        let solver = BranchAndBound {
+            relic_pool: relic_pool.clone(),
+            evaluator: Arc::new(AtkEvaluator),
+            target: "Atk".to_string(),
+            encounter: Encounter::default(),
+            teammates: vec![],
+            relic_set_config: RelicSetConfig::default(),
+            top: 3,
+            constraints: None,
+        };
+        let best = solver.solve()?;
+
+        let mut exhaustive = CAVERN_SLOTS
+            .iter()
+            .chain(&PLANAR_SLOTS)
+            .map(|slot| relic_pool[slot].clone())
+            .multi_cartesian_product()
+            .map(|relics| solver.evaluate(relics))
+            .collect::<Result<Vec<_>>>()?;
+        exhaustive.sort_by(|a, b| b.total_cmp(a));
+        let scores = best.iter().map(|build| build.score).collect_vec();
+        assert_eq!(scores, exhaustive[..3]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_keeps_spheres_of_other_elements() -> Result<()> {
+        let sphere = |uid: &str, mainstat: Stats, atk: f64| Relic {
+            mainstat,
+            mainstat_value: 38.88,
+            ..relic(uid, &Slot::PlanarSphere, "301", atk)
+        };
+        let solver = BranchAndBound {
+            relic_pool: HashMap::from([(
+                Slot::PlanarSphere,
+                vec![
+                    sphere("fire", Stats::FireDmgBoost_, 0.0),
+                    sphere("ice", Stats::IceDmgBoost_, 5.0),
+                ],
+            )]),
+            evaluator: Arc::new(AtkEvaluator),
+            target: "Atk".to_string(),
+            encounter: Encounter::default(),
+            teammates: vec![],
+            relic_set_config: RelicSetConfig::default(),
+            top: 1,
+            constraints: None,
+        };
+        let best = solver.solve()?;
+        assert_eq!(best[0].score, 38.88);
+        Ok(())
+    }
+
+    #[test]
+    fn test_keeps_builds_under_ceilings() -> Result<()> {
+        let character = Character {
+            id: "1".to_string(),
+            name: "Test".to_string(),
+            path: Path::TheHunt,
+            attack_type: AttackType::Fire,
+            level: 80,
+            ascension: 6,
+            eidolon: 0,
+            skills: CharacterSkills {
+                basic: 6,
+                skill: 10,
+                ult: 10,
+                talent: 10,
+            },
+            traces: CharacterTraces {
+                ability_1: true,
+                ability_2: true,
+                ability_3: true,
+                stat_1: true,
+                stat_2: true,
+                stat_3: true,
+                stat_4: true,
+                stat_5: true,
+                stat_6: true,
+                stat_7: true,
+                stat_8: true,
+                stat_9: true,
+                stat_10: true,
+            },
+            base_hp: 1000.0,
+            base_atk: 600.0,
+            base_def: 500.0,
+            base_spd: 100.0,
+            base_aggro: 100,
+            critical_chance: 5.0,
+            critical_damage: 50.0,
+            stat_bonus: BaseStats::default(),
+        };
+        let fast = Relic {
+            substats: vec![
+                SubStats {
+                    key: Stats::Atk_,
+                    value: 10.0,
+                },
+                SubStats {
+                    key: Stats::Spd,
+                    value: 10.0,
+                },
+            ],
+            ..relic("fast", &Slot::Feet, "", 0.0)
+        };
+        let evaluator = Arc::new(AtkEvaluator);
+        let solver = BranchAndBound {
+            relic_pool: HashMap::from([(
+                Slot::Feet,
+                vec![fast, relic("slow", &Slot::Feet, "", 5.0)],
+            )]),
+            evaluator: evaluator.clone(),
+            target: "Atk".to_string(),
+            encounter: Encounter::default(),
+            teammates: vec![],
+            relic_set_config: RelicSetConfig::default(),
+            top: 1,
+            constraints: Some(Constrained {
+                evaluator,
+                character,
+                light_cone: None,
+                constraints: vec!["SPD<=105".parse()?],
+            }),
+        };
+        let best = solver.solve()?;
+        assert_eq!(best[0].relics.relics[0].uid, "slow");
+        assert_eq!(best[0].score, 5.0);
+        Ok(())
+    }
+}
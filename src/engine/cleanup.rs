@@ -0,0 +1,88 @@
+use crate::domain::{Relic, Slot};
+use eyre::{eyre, Result};
+use itertools::Itertools;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+/// Relics no build uses, split between the ones to discard and the locked ones that are kept
+/// whatever happens.
+#[derive(Clone, Debug, Default)]
+pub struct Cleanup {
+    pub discard: Vec<Relic>,
+    pub locked: Vec<Relic>,
+}
+
+/// Sorts the relics of the pool that are missing from every kept build, by slot then by UID.
+pub fn unused_relics(relic_pool: &HashMap<Slot, Vec<Relic>>, kept: &HashSet<String>) -> Cleanup {
+    let (locked, discard) = relic_pool
+        .values()
+        .flatten()
+        .filter(|relic| !kept.contains(&relic.uid))
+        .sorted_by(|a, b| {
+            format!("{:?}", a.slot)
+                .cmp(&format!("{:?}", b.slot))
+                .then_with(|| a.uid.cmp(&b.uid))
+        })
+        .cloned()
+        .partition(|relic| relic.lock);
+    Cleanup { discard, locked }
+}
+
+/// Sets `discard: true` on the relics of a scanner export whose UID is given, leaving the rest of
+/// the export untouched. Returns the number of relics flagged.
+pub fn mark_discarded(scan: &mut Value, uids: &HashSet<String>) -> Result<usize> {
+    let relics = scan
+        .get_mut("relics")
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| eyre!("The scan has no relic list"))?;
+    let mut marked = 0;
+    for relic in relics {
+        let uid = relic.get("_uid").and_then(Value::as_str);
+        if uid.is_some_and(|uid| uids.contains(uid)) {
+            relic["discard"] = Value::Bool(true);
+            marked += 1;
+        }
+    }
+    Ok(marked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_cleanup() -> Result<()> {
+        let relic = |uid: &str, lock: bool| Relic {
+            uid: uid.to_string(),
+            slot: Slot::Head,
+            lock,
+            ..Default::default()
+        };
+        let relic_pool = HashMap::from([(
+            Slot::Head,
+            vec![relic("c", false), relic("a", false), relic("b", true)],
+        )]);
+        let cleanup = unused_relics(&relic_pool, &HashSet::from(["c".to_string()]));
+        assert_eq!(cleanup.discard.len(), 1);
+        assert_eq!(cleanup.discard[0].uid, "a");
+        assert_eq!(cleanup.locked[0].uid, "b");
+
+        let mut scan = json!({
+            "source": "HSR-Scanner",
+            "relics": [
+                { "_uid": "a", "discard": false, "level": 15 },
+                { "_uid": "c", "discard": false },
+            ],
+        });
+        let uids = cleanup.discard.iter().map(|r| r.uid.clone()).collect();
+        assert_eq!(mark_discarded(&mut scan, &uids)?, 1);
+        assert_eq!(
+            scan["relics"][0],
+            json!({ "_uid": "a", "discard": true, "level": 15 })
+        );
+        assert_eq!(scan["relics"][1]["discard"], json!(false));
+        assert_eq!(scan["source"], json!("HSR-Scanner"));
+        Ok(())
+    }
+}
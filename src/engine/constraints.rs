@@ -0,0 +1,246 @@
+use crate::{
+    character::{DamageHit, Evaluator, NamedEvaluator, Support},
+    domain::{Character, Encounter, LightConeEntity, Relics, Stats},
+    utils::calculator::{bonus_before_battle, calculate_stats},
+};
+use eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{collections::HashMap, fmt, str::FromStr};
+use strum::IntoEnumIterator;
+
+/// A floor or a ceiling on a stat of the character sheet, e.g. `SPD>=134` or `EHR>=67%`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(try_from = "String", into = "String")]
+pub struct StatConstraint {
+    pub stat: Stats,
+    pub bound: Bound,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    Min,
+    Max,
+}
+
+/// How a build fares against a constraint.
+#[derive(Debug, Clone)]
+pub struct ConstraintCheck {
+    pub constraint: StatConstraint,
+    /// Value of the stat on the character sheet.
+    pub value: f64,
+    /// Margin to the bound, negative when the constraint is violated.
+    pub slack: f64,
+}
+
+impl ConstraintCheck {
+    pub fn satisfied(&self) -> bool {
+        self.slack >= 0.0
+    }
+
+    /// Whether the build sits on the bound, i.e. within 1% of it.
+    pub fn binding(&self) -> bool {
+        self.satisfied() && self.slack <= 0.01 * self.constraint.value.abs().max(1.0)
+    }
+}
+
+/// Stats found on the character sheet, which constraints can refer to.
+pub const SHEET_STATS: [Stats; 11] = [
+    Stats::Hp,
+    Stats::Atk,
+    Stats::Def,
+    Stats::Spd,
+    Stats::CritRate_,
+    Stats::CritDmg_,
+    Stats::EnergyRegenerationRate_,
+    Stats::EffectHitRate_,
+    Stats::BreakEffect_,
+    Stats::EffectRes_,
+    Stats::OutgoingHealingBoost_,
+];
+
+impl StatConstraint {
+    pub fn check(&self, stats: &HashMap<Stats, f64>) -> ConstraintCheck {
+        let value = stats.get(&self.stat).cloned().unwrap_or_default();
+        let slack = match self.bound {
+            Bound::Min => value - self.value,
+            Bound::Max => self.value - value,
+        };
+        ConstraintCheck {
+            constraint: self.clone(),
+            value,
+            slack,
+        }
+    }
+}
+
+impl FromStr for StatConstraint {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (stat, bound, value) = if let Some((stat, value)) = s.split_once(">=") {
+            (stat, Bound::Min, value)
+        } else if let Some((stat, value)) = s.split_once("<=") {
+            (stat, Bound::Max, value)
+        } else {
+            return Err(eyre!(
+                "Invalid constraint {s}, expected STAT>=VALUE or STAT<=VALUE"
+            ));
+        };
+        let stat = parse_stat(stat.trim())?;
+        let value = value
+            .trim()
+            .trim_end_matches('%')
+            .parse::<f64>()
+            .map_err(|e| eyre!("Invalid value in constraint {s}: {e}"))?;
+        Ok(Self { stat, bound, value })
+    }
+}
+
+impl TryFrom<String> for StatConstraint {
+    type Error = eyre::Report;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<StatConstraint> for String {
+    fn from(constraint: StatConstraint) -> Self {
+        constraint.to_string()
+    }
+}
+
+impl fmt::Display for StatConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bound = match self.bound {
+            Bound::Min => ">=",
+            Bound::Max => "<=",
+        };
+        write!(f, "{:?}{bound}{}", self.stat, self.value)
+    }
+}
+
+/// Reads a sheet stat by its scanner name, its variant name or a usual abbreviation.
+fn parse_stat(name: &str) -> Result<Stats> {
+    let name = match name.to_ascii_uppercase().as_str() {
+        "CR" | "CRIT RATE" => "CritRate_",
+        "CD" | "CRIT DMG" => "CritDmg_",
+        "EHR" => "EffectHitRate_",
+        "RES" | "EFFECT RES" => "EffectRes_",
+        "BE" => "BreakEffect_",
+        "ERR" | "ER" => "EnergyRegenerationRate_",
+        "OHB" => "OutgoingHealingBoost_",
+        _ => name,
+    };
+    let stat = match Stats::iter().find(|stat| format!("{stat:?}").eq_ignore_ascii_case(name)) {
+        Some(stat) => stat,
+        None => serde_json::from_value(Value::String(name.to_string()))
+            .map_err(|_| eyre!("Unknown stat {name}"))?,
+    };
+    if !SHEET_STATS.contains(&stat) {
+        return Err(eyre!(
+            "{stat:?} is not on the character sheet, constraints apply to {}",
+            SHEET_STATS.map(|s| format!("{s:?}")).join(", ")
+        ));
+    }
+    Ok(stat)
+}
+
+/// Wraps an evaluator so that builds violating a constraint score below every feasible build.
+///
+/// Constraints apply to the character sheet, i.e. the stats outside of battle. An infeasible
+/// build scores minus its total relative violation, so that the search can still climb towards
+/// feasibility.
+pub struct Constrained {
+    pub evaluator: NamedEvaluator,
+    pub character: Character,
+    pub light_cone: Option<LightConeEntity>,
+    pub constraints: Vec<StatConstraint>,
+}
+
+/// Stats of the character sheet, i.e. outside of battle, for a build.
+pub fn sheet_stats(
+    character: &Character,
+    light_cone: &Option<LightConeEntity>,
+    relics: &Relics,
+) -> Result<HashMap<Stats, f64>> {
+    let bonus = bonus_before_battle(character, light_cone, relics, &character.attack_type)?;
+    Ok(calculate_stats(&bonus, character, light_cone))
+}
+
+impl Constrained {
+    pub fn check(&self, relics: &Relics) -> Result<Vec<ConstraintCheck>> {
+        let stats = sheet_stats(&self.character, &self.light_cone, relics)?;
+        Ok(self.constraints.iter().map(|c| c.check(&stats)).collect())
+    }
+}
+
+impl Evaluator for Constrained {
+    type Target = String;
+
+    fn evaluate(
+        &self,
+        relics: &Relics,
+        encounter: &Encounter,
+        target: &Self::Target,
+        teammates: &[Box<dyn Support>],
+    ) -> Result<f64> {
+        let violation: f64 = self
+            .check(relics)?
+            .iter()
+            .filter(|check| !check.satisfied())
+            .map(|check| -check.slack / check.constraint.value.abs().max(1.0))
+            .sum();
+        if violation > 0.0 {
+            return Ok(-violation);
+        }
+        self.evaluator
+            .evaluate(relics, encounter, target, teammates)
+    }
+
+    fn targets(&self) -> Vec<String> {
+        self.evaluator.targets()
+    }
+
+    fn explain(
+        &self,
+        relics: &Relics,
+        encounter: &Encounter,
+        target: &Self::Target,
+        teammates: &[Box<dyn Support>],
+    ) -> Result<Vec<DamageHit>> {
+        self.evaluator.explain(relics, encounter, target, teammates)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_constraints() -> Result<()> {
+        let spd: StatConstraint = "SPD>=134".parse()?;
+        assert_eq!(spd.stat, Stats::Spd);
+        assert_eq!(spd.bound, Bound::Min);
+        let ehr: StatConstraint = "EHR >= 67%".parse()?;
+        assert_eq!((ehr.stat, ehr.value), (Stats::EffectHitRate_, 67.0));
+        let crit: StatConstraint = "CritRate_<=100".parse()?;
+        assert_eq!(crit.bound, Bound::Max);
+        assert_eq!(crit.to_string().parse::<StatConstraint>()?, crit);
+        assert!("DmgBoost_>=10".parse::<StatConstraint>().is_err());
+        assert!("SPD=134".parse::<StatConstraint>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_binding_constraints() -> Result<()> {
+        let stats = HashMap::from([(Stats::Spd, 134.5), (Stats::EffectHitRate_, 40.0)]);
+        let spd = "SPD>=134".parse::<StatConstraint>()?.check(&stats);
+        assert!(spd.satisfied() && spd.binding());
+        let ehr = "EHR>=67".parse::<StatConstraint>()?.check(&stats);
+        assert!(!ehr.satisfied() && !ehr.binding());
+        Ok(())
+    }
+}
@@ -370,7 +370,7 @@ impl Evaluator {
 mod tests {
     use super::*;
     use crate::{
-        client::project_yatta_client::ProjectYattaClient,
+        client::{cache::DiskCache, project_yatta_client::ProjectYattaClient},
         data_fetcher::{project_yatta_data_fetcher::ProjectYattaDataFetcher, DataFetcher},
         domain::{Character, CharacterSkills, CharacterTraces, LightCone, Relic, Slot, SubStats},
     };
@@ -386,8 +386,10 @@ mod tests {
         // };
         let mut fetcher = ProjectYattaDataFetcher {
             client: ProjectYattaClient {
-                url: "https://sr.yatta.moe/api/v2/en/".to_string(),
-                light_cone_cache: HashMap::new(),
+                url: "https://sr.yatta.moe/api/v2/".to_string(),
+                language: "en".to_string(),
+                http: reqwest::Client::new(),
+                cache: DiskCache::default(),
             },
         };
         // Create a new character instance with specific attributes.
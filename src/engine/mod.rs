@@ -2,8 +2,17 @@ use std::collections::HashMap;
 
 use crate::domain::Stats;
 
+pub mod branch_and_bound;
+pub mod cleanup;
+pub mod constraints;
 // pub mod evaluator;
 pub mod optimizer;
+pub mod ranking;
+pub mod relic_filter;
+pub mod sensitivity;
 pub mod simulated_annealing;
+pub mod team_optimizer;
+pub mod timeline;
+pub mod upgrade;
 
 pub type StatBonusMap = HashMap<Stats, f64>;
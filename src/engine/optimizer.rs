@@ -1,15 +1,18 @@
-use super::simulated_annealing::SimulatedAnnealing;
+use super::{
+    ranking::{rank, RankedBuild},
+    simulated_annealing::SimulatedAnnealing,
+};
 use crate::{
     character::{Evaluator, Support},
-    domain::{Enemy, Relic, RelicSetConfig, Relics, Slot},
+    domain::{Encounter, Relic, RelicSetConfig, Relics, Slot},
 };
 use core::f64;
-use eyre::{OptionExt, Result};
+use eyre::{bail, OptionExt, Result};
 use rand::{
-    rngs::ThreadRng,
     seq::{IteratorRandom, SliceRandom},
-    thread_rng, Rng,
+    thread_rng, Rng, SeedableRng,
 };
+use rand_chacha::ChaCha8Rng;
 use rayon::prelude::*;
 use std::{
     cmp::{min, Ordering},
@@ -39,9 +42,33 @@ pub struct Optimizer<T> {
     pub simulated_annealing: SimulatedAnnealing<T>,
 
     pub target: T,
-    pub enemy: Enemy,
+    pub encounter: Encounter,
     pub teammates: Vec<Box<dyn Support>>,
     pub relic_set_config: RelicSetConfig,
+    /// Seed of the random number generators, a random one when `None`. The same seed gives the
+    /// same result whatever the number of threads.
+    pub seed: Option<u64>,
+}
+
+/// Streams of random numbers within a generation, one per kind of task.
+const SELECTION: u64 = 0;
+const BREEDING: u64 = 1 << 24;
+const ANNEALING: u64 = 2 << 24;
+
+/// The seed of a run, drawn at random when none is given. It is logged so that any run can be
+/// replayed.
+pub fn run_seed(seed: Option<u64>) -> u64 {
+    let seed = seed.unwrap_or_else(|| thread_rng().gen());
+    info!("Seed {seed}");
+    seed
+}
+
+/// Random number generator of a single task of a run. Tasks get their own stream rather than
+/// sharing one per thread, so that the result doesn't depend on how rayon schedules them.
+pub fn task_rng(seed: u64, generation: usize, task: u64) -> ChaCha8Rng {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    rng.set_stream(((generation as u64) << 32) | task);
+    rng
 }
 
 impl<T: Sync + Display> Optimizer<T> {
@@ -50,9 +77,12 @@ impl<T: Sync + Display> Optimizer<T> {
     fn total_fitness(&self, population: &[Relics]) -> Result<f64> {
         let mut total = 0.0;
         for individual in population {
-            let fitness =
-                self.evaluator
-                    .evaluate(individual, &self.enemy, &self.target, &self.teammates)?;
+            let fitness = self.evaluator.evaluate(
+                individual,
+                &self.encounter,
+                &self.target,
+                &self.teammates,
+            )?;
             total += fitness;
         }
         Ok(total)
@@ -63,7 +93,7 @@ impl<T: Sync + Display> Optimizer<T> {
     fn roulette_wheel_selection(
         &self,
         population: &[Relics],
-        rng: &mut ThreadRng,
+        rng: &mut impl Rng,
     ) -> Result<Vec<Relics>> {
         let total_fitness = self.total_fitness(population)?;
         let mut cumulative_probabilities = Vec::with_capacity(population.len());
@@ -71,9 +101,12 @@ impl<T: Sync + Display> Optimizer<T> {
 
         // Calculate cumulative probabilities
         for individual in population {
-            let fitness =
-                self.evaluator
-                    .evaluate(individual, &self.enemy, &self.target, &self.teammates)?;
+            let fitness = self.evaluator.evaluate(
+                individual,
+                &self.encounter,
+                &self.target,
+                &self.teammates,
+            )?;
             cumulative_sum += fitness / total_fitness;
             cumulative_probabilities.push(cumulative_sum);
         }
@@ -95,9 +128,9 @@ impl<T: Sync + Display> Optimizer<T> {
     fn evaluation(&self, x: &Relics, y: &Relics) -> Ordering {
         match (
             self.evaluator
-                .evaluate(x, &self.enemy, &self.target, &self.teammates),
+                .evaluate(x, &self.encounter, &self.target, &self.teammates),
             self.evaluator
-                .evaluate(y, &self.enemy, &self.target, &self.teammates),
+                .evaluate(y, &self.encounter, &self.target, &self.teammates),
         ) {
             (Ok(x_val), Ok(y_val)) => x_val.partial_cmp(&y_val).unwrap(),
             _ => f64::MIN.partial_cmp(&f64::MIN).unwrap(),
@@ -108,11 +141,13 @@ impl<T: Sync + Display> Optimizer<T> {
         &self,
         population: &[Relics],
         tournament_size: usize,
+        seed: u64,
+        generation: usize,
     ) -> Result<Vec<Relics>> {
         let selected = (0..self.population_size / 2)
             .into_par_iter()
-            .map(|_| {
-                let mut rng = thread_rng();
+            .map(|i| {
+                let mut rng = task_rng(seed, generation, SELECTION + i as u64);
                 let tournament: Vec<&Relics> = (0..tournament_size)
                     .map(|_| population.choose(&mut rng).unwrap())
                     .collect();
@@ -133,35 +168,51 @@ impl<T: Sync + Display> Optimizer<T> {
     /// - `Ok(Vec<Relic>)` - The best relic set found after all generations.
     /// - `Err(e)` - An error if something goes wrong during the optimization process.
     pub fn optimize(&self) -> Result<Relics> {
+        let mut best = self.optimize_top(1)?;
+        Ok(best.remove(0).relics)
+    }
+
+    /// Runs the optimization and returns the `top` best distinct builds found, best first.
+    ///
+    /// Candidates are the final population and the best build of every generation, so a good
+    /// build the population drifted away from is not lost.
+    pub fn optimize_top(&self, top: usize) -> Result<Vec<RankedBuild>> {
+        let seed = run_seed(self.seed);
         // Initialize the population with random relic sets.
+        let mut rng = task_rng(seed, 0, 0);
         let mut population: Vec<Relics> = (0..self.population_size)
-            .map(|_| self.generate_random_relic_set())
+            .map(|_| self.generate_random_relic_set(&mut rng))
             .collect();
 
+        let mut hall_of_fame = vec![];
+
         // Run the optimization process over a number of generations.
         for generation in 0..self.generation {
+            // Streams of generation 0 are used by the initial population.
+            let stream = generation + 1;
             // Use Roulette Wheel Selection to select parents
-            let mut selected_population = self.tournament_selection(&population, 5)?; // 5 is the tournament size
+            let mut selected_population =
+                self.tournament_selection(&population, 5, seed, stream)?; // 5 is the tournament size
 
             let difference = (self.population_size - selected_population.len()) / 2;
 
             // Generate new individuals through crossover and mutation in parallel.
             let mut new_gen: Vec<Relics> = (0..difference)
                 .into_par_iter()
-                .map(|_| {
-                    let mut rng = thread_rng();
+                .map(|i| {
+                    let mut rng = task_rng(seed, stream, BREEDING + i as u64);
                     // Randomly select two parents from the selected population
                     let parents = selected_population
                         .clone()
                         .into_iter()
                         .choose_multiple(&mut rng, 2);
 
-                    let children = self.crossover(parents)?;
+                    let children = self.crossover(parents, &mut rng)?;
 
                     // Apply mutation to the children and add them to the next generation.
                     let mutated_children: Vec<_> = children
                         .into_iter()
-                        .map(|child| self.mutate(child))
+                        .map(|child| self.mutate(child, &mut rng))
                         .collect::<Result<_>>()?;
 
                     Ok::<Vec<_>, eyre::Report>(mutated_children)
@@ -187,7 +238,7 @@ impl<T: Sync + Display> Optimizer<T> {
                         .clone();
                     let best_fit = self.evaluator.evaluate(
                         &best_individual,
-                        &self.enemy,
+                        &self.encounter,
                         &self.target,
                         &self.teammates,
                     )?;
@@ -197,12 +248,13 @@ impl<T: Sync + Display> Optimizer<T> {
                     );
 
                     // Apply aggresive SA
+                    let mut rng = task_rng(seed, stream, ANNEALING);
                     best_individual = self
                         .simulated_annealing
-                        .simulated_annealing(&best_individual)?;
+                        .simulated_annealing(&best_individual, &mut rng)?;
                     let best_fit = self.evaluator.evaluate(
                         &best_individual,
-                        &self.enemy,
+                        &self.encounter,
                         &self.target,
                         &self.teammates,
                     )?;
@@ -210,7 +262,7 @@ impl<T: Sync + Display> Optimizer<T> {
                         "Generation {generation}, after SA, Highest {}: {}",
                         self.target, best_fit
                     );
-                    let random_index = thread_rng().gen_range(0..population.len() - 1);
+                    let random_index = rng.gen_range(0..population.len() - 1);
                     population[random_index] = best_individual;
                 }
             }
@@ -223,7 +275,7 @@ impl<T: Sync + Display> Optimizer<T> {
 
             let result = self.evaluator.evaluate(
                 best_combination,
-                &self.enemy,
+                &self.encounter,
                 &self.target,
                 &self.teammates,
             )?;
@@ -233,12 +285,28 @@ impl<T: Sync + Display> Optimizer<T> {
                 self.target,
                 result
             );
+            hall_of_fame.push((best_combination.clone(), result));
         }
 
-        // Sort the final population and return the best relic set.
-        population.par_sort_by(|x, y| self.evaluation(x, y));
-        let best_relic_set = population.last().unwrap().clone();
-        Ok(best_relic_set)
+        // Rank the final population along with the best builds of each generation.
+        let mut candidates = population
+            .into_par_iter()
+            .map(|relics| {
+                let score = self.evaluator.evaluate(
+                    &relics,
+                    &self.encounter,
+                    &self.target,
+                    &self.teammates,
+                )?;
+                Ok((relics, score))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        candidates.append(&mut hall_of_fame);
+        let best = rank(candidates, top.max(1))?;
+        if best.is_empty() {
+            bail!("Best combination not found");
+        }
+        Ok(best)
     }
 
     /// Generates a random relic set by selecting one relic for each slot from the available relic pool.
@@ -246,7 +314,7 @@ impl<T: Sync + Display> Optimizer<T> {
     /// # Returns
     ///
     /// - `Vec<Relic>` - A vector of randomly selected relics for each slot.
-    fn generate_random_relic_set(&self) -> Relics {
+    fn generate_random_relic_set(&self, rng: &mut impl Rng) -> Relics {
         // Collect all the slots
         let slots: Vec<Slot> = Slot::iter().collect();
 
@@ -254,9 +322,8 @@ impl<T: Sync + Display> Optimizer<T> {
         let relics: Vec<Relic> = slots
             .iter()
             .filter_map(|slot| {
-                let mut rng = thread_rng(); // Create a new RNG instance for each thread
                 if let Some(relics_for_slot) = self.relic_pool.get(slot) {
-                    relics_for_slot.iter().choose(&mut rng).cloned()
+                    relics_for_slot.iter().choose(rng).cloned()
                 } else {
                     None
                 }
@@ -279,7 +346,7 @@ impl<T: Sync + Display> Optimizer<T> {
     ///
     /// - `Ok(Vec<Vec<Relic>>)` - A vector containing two child relic sets resulting from the crossover.
     /// - `Err(e)` - An error if there are not exactly two parents provided.
-    fn crossover(&self, parents: Vec<Relics>) -> Result<Vec<Relics>> {
+    fn crossover(&self, parents: Vec<Relics>, rng: &mut impl Rng) -> Result<Vec<Relics>> {
         let mut parents = parents.iter();
         let parent1 = parents.next().ok_or(eyre::eyre!("Missing parent 1"))?;
         let parent2 = parents.next().ok_or(eyre::eyre!("Missing parent 2"))?;
@@ -289,7 +356,6 @@ impl<T: Sync + Display> Optimizer<T> {
         let mut child1 = vec![];
         let mut child2 = vec![];
 
-        let mut rng = thread_rng();
         for i in 0..min_length {
             if rng.gen::<f64>() > self.crossover_rate {
                 child1.push(parent1.relics[i].clone());
@@ -331,16 +397,14 @@ impl<T: Sync + Display> Optimizer<T> {
     ///
     /// - `Ok(Vec<Relic>)` - The mutated relic set.
     /// - `Err(e)` - An error if a relic's slot is not found in the relic pool.
-    fn mutate(&self, child: Relics) -> Result<Relics> {
+    fn mutate(&self, child: Relics, rng: &mut impl Rng) -> Result<Relics> {
         let mut mutated_child = child;
 
-        // Parallelize the mutation of relics
         mutated_child.relics.iter_mut().for_each(|relic| {
-            let mut rng = rand::thread_rng(); // Create a new RNG for each thread
             if rng.gen::<f64>() < self.mutation_rate {
                 let slot = &relic.slot;
                 if let Some(candidates) = self.relic_pool.get(slot) {
-                    if let Some(new_relic) = candidates.choose(&mut rng) {
+                    if let Some(new_relic) = candidates.choose(rng) {
                         *relic = new_relic.clone();
                     }
                 }
@@ -350,3 +414,93 @@ impl<T: Sync + Display> Optimizer<T> {
         Ok(mutated_child)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Stats, SubStats};
+
+    struct AtkEvaluator;
+
+    impl Evaluator for AtkEvaluator {
+        type Target = String;
+
+        fn evaluate(
+            &self,
+            relics: &Relics,
+            _encounter: &Encounter,
+            _target: &Self::Target,
+            _teammates: &[Box<dyn Support>],
+        ) -> Result<f64> {
+            Ok(relics
+                .relics
+                .iter()
+                .flat_map(|r| &r.substats)
+                .map(|s| s.value)
+                .sum())
+        }
+
+        fn targets(&self) -> Vec<String> {
+            vec![]
+        }
+    }
+
+    fn optimizer(seed: u64) -> Optimizer<String> {
+        let relic_pool: HashMap<Slot, Vec<Relic>> = Slot::iter()
+            .filter(|slot| *slot != Slot::Dummy)
+            .map(|slot| {
+                let relics = (0..20)
+                    .map(|i| Relic {
+                        uid: format!("{slot:?}{i}"),
+                        slot: slot.clone(),
+                        substats: vec![SubStats {
+                            key: Stats::Atk_,
+                            value: ((i * 7) % 13) as f64,
+                        }],
+                        ..Default::default()
+                    })
+                    .collect();
+                (slot, relics)
+            })
+            .collect();
+        let evaluator: Arc<dyn Evaluator<Target = String> + Sync + Send> = Arc::new(AtkEvaluator);
+        Optimizer {
+            relic_pool: relic_pool.clone(),
+            generation: 5,
+            population_size: 40,
+            mutation_rate: 0.1,
+            crossover_rate: 0.7,
+            evaluator: evaluator.clone(),
+            enable_sa: true,
+            simulated_annealing: SimulatedAnnealing {
+                initial_temp: 10.0,
+                cooling_rate: 0.9,
+                min_temp: 0.1,
+                aggresive_factor: 0.9,
+                relic_pool,
+                evaluator,
+                target: String::new(),
+                encounter: Encounter::default(),
+                teammates: vec![],
+            },
+            target: String::new(),
+            encounter: Encounter::default(),
+            teammates: vec![],
+            relic_set_config: RelicSetConfig::default(),
+            seed: Some(seed),
+        }
+    }
+
+    #[test]
+    fn test_seed_is_reproducible() -> Result<()> {
+        let run = |threads: usize| -> Result<Vec<String>> {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()?;
+            let best = pool.install(|| optimizer(42).optimize())?;
+            Ok(best.relics.into_iter().map(|r| r.uid).collect())
+        };
+        assert_eq!(run(1)?, run(4)?);
+        Ok(())
+    }
+}
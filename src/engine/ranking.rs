@@ -0,0 +1,95 @@
+use crate::domain::Relics;
+use eyre::Result;
+use itertools::Itertools;
+
+/// One of the best builds found, compared to the very best.
+#[derive(Clone, Debug)]
+pub struct RankedBuild {
+    pub relics: Relics,
+    pub score: f64,
+    /// How much lower the score is than the best build's, 0 for the best build.
+    pub delta: f64,
+    /// Sets worn with at least 2 pieces, see `Relics::set_combination`.
+    pub sets: Vec<(String, usize)>,
+}
+
+impl RankedBuild {
+    /// Sets of the build as e.g. `4pc Pioneer Diver of Dead Waters + 2pc Izumo Gensei`.
+    pub fn sets_summary(&self) -> String {
+        if self.sets.is_empty() {
+            return "no set bonus".to_string();
+        }
+        self.sets
+            .iter()
+            .map(|(name, pieces)| format!("{pieces}pc {name}"))
+            .join(" + ")
+    }
+}
+
+/// The `top` best distinct builds among the candidates, best first. Builds made of the same
+/// relics count once whatever the order of the relics.
+pub fn rank(candidates: Vec<(Relics, f64)>, top: usize) -> Result<Vec<RankedBuild>> {
+    let best = candidates
+        .iter()
+        .map(|(_, score)| *score)
+        .fold(f64::NEG_INFINITY, f64::max);
+    candidates
+        .into_iter()
+        .sorted_by(|(_, a), (_, b)| b.total_cmp(a))
+        .unique_by(|(relics, _)| {
+            relics
+                .relics
+                .iter()
+                .map(|r| r.uid.clone())
+                .sorted()
+                .collect_vec()
+        })
+        .take(top)
+        .map(|(relics, score)| {
+            Ok(RankedBuild {
+                sets: relics.set_combination()?,
+                relics,
+                score,
+                delta: best - score,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Relic, RelicSetConfig};
+
+    fn build(uids: &[&str]) -> Relics {
+        Relics {
+            relics: uids
+                .iter()
+                .map(|uid| Relic {
+                    uid: uid.to_string(),
+                    set_id: "101".to_string(),
+                    ..Default::default()
+                })
+                .collect(),
+            config: RelicSetConfig::default(),
+        }
+    }
+
+    #[test]
+    fn test_rank_distinct_builds() -> Result<()> {
+        let ranked = rank(
+            vec![
+                (build(&["a", "b"]), 10.0),
+                (build(&["c", "d"]), 12.0),
+                (build(&["b", "a"]), 10.0),
+                (build(&["a", "d"]), 9.0),
+            ],
+            3,
+        )?;
+        let scores = ranked.iter().map(|b| (b.score, b.delta)).collect_vec();
+        assert_eq!(scores, vec![(12.0, 0.0), (10.0, 2.0), (9.0, 3.0)]);
+        assert_eq!(ranked[0].sets.len(), 1);
+        assert_eq!(ranked[0].sets[0].1, 2);
+        Ok(())
+    }
+}
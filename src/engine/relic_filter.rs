@@ -0,0 +1,204 @@
+use crate::domain::{Relic, Slot};
+use eyre::{bail, eyre, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Restricts the relics the optimizer may pick from the scan.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct RelicFilter {
+    /// Skip relics worn by characters that are not being optimized.
+    pub exclude_equipped: bool,
+    /// Skip relics marked for discard in the scan.
+    pub exclude_discarded: bool,
+    /// Only use relics locked in game.
+    pub locked_only: bool,
+    pub min_level: u8,
+    pub min_rarity: u8,
+    /// Relic UIDs never used.
+    pub excluded: Vec<String>,
+    /// Relic UIDs forced into their slot. When optimizing a team, a pinned relic stays on the
+    /// member wearing it.
+    pub pinned: Vec<String>,
+    /// Keep the relics currently worn by the optimized character, like pinning all of them.
+    pub keep_equipped: bool,
+}
+
+impl RelicFilter {
+    /// Relics `character_id` may wear. `optimized` lists every character being optimized, whose
+    /// relics stay available unless another of them keeps them.
+    pub fn apply(
+        &self,
+        relic_pool: &HashMap<Slot, Vec<Relic>>,
+        character_id: &str,
+        optimized: &[&str],
+    ) -> Result<HashMap<Slot, Vec<Relic>>> {
+        let relics: HashMap<&str, &Relic> = relic_pool
+            .values()
+            .flatten()
+            .map(|relic| (relic.uid.as_str(), relic))
+            .collect();
+        for uid in self.pinned.iter().chain(&self.excluded) {
+            if !relics.contains_key(uid.as_str()) {
+                bail!("Relic {uid} not found in the scan");
+            }
+        }
+        let team = optimized.len() > 1;
+        let pins = self.pins(&relics, character_id, team)?;
+        let mut reserved = HashSet::new();
+        for other in optimized.iter().filter(|id| **id != character_id) {
+            reserved.extend(self.pins(&relics, other, team)?.into_values());
+        }
+        if team {
+            if let Some(uid) = self.pinned.iter().find(|uid| {
+                !optimized.contains(&location(relics[uid.as_str()]).unwrap_or_default())
+            }) {
+                bail!("Relic {uid} is pinned but worn by none of the team members");
+            }
+        }
+
+        let mut filtered = HashMap::new();
+        for (slot, candidates) in relic_pool {
+            let candidates: Vec<Relic> = match pins.get(slot) {
+                Some(uid) => vec![relics[uid].clone()],
+                None => candidates
+                    .iter()
+                    .filter(|relic| !reserved.contains(relic.uid.as_str()))
+                    .filter(|relic| self.allows(relic, optimized))
+                    .cloned()
+                    .collect(),
+            };
+            filtered.insert(slot.clone(), candidates);
+        }
+        Ok(filtered)
+    }
+
+    fn allows(&self, relic: &Relic, optimized: &[&str]) -> bool {
+        let worn_elsewhere = location(relic).is_some_and(|location| !optimized.contains(&location));
+        !(self.exclude_equipped && worn_elsewhere
+            || self.exclude_discarded && relic.discard
+            || self.locked_only && !relic.lock
+            || relic.level < self.min_level
+            || relic.rarity < self.min_rarity
+            || self.excluded.contains(&relic.uid))
+    }
+
+    /// UIDs of the relics pinned on `character_id`, by slot.
+    fn pins<'a>(
+        &'a self,
+        relics: &HashMap<&'a str, &'a Relic>,
+        character_id: &str,
+        team: bool,
+    ) -> Result<HashMap<Slot, &'a str>> {
+        let mut pins: HashMap<Slot, &str> = HashMap::new();
+        let mut pin = |relic: &'a Relic| -> Result<()> {
+            match pins.insert(relic.slot.clone(), relic.uid.as_str()) {
+                Some(other) if other != relic.uid => Err(eyre!(
+                    "Relics {other} and {} are both pinned to {:?}",
+                    relic.uid,
+                    relic.slot
+                )),
+                _ => Ok(()),
+            }
+        };
+        for uid in &self.pinned {
+            let relic = relics[uid.as_str()];
+            if !team || location(relic) == Some(character_id) {
+                pin(relic)?;
+            }
+        }
+        if self.keep_equipped {
+            for relic in relics.values() {
+                if location(relic) == Some(character_id) {
+                    pin(relic)?;
+                }
+            }
+        }
+        Ok(pins)
+    }
+}
+
+/// The character wearing the relic, if any.
+fn location(relic: &Relic) -> Option<&str> {
+    relic
+        .location
+        .as_deref()
+        .filter(|location| !location.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn relic(uid: &str, slot: Slot, location: &str, level: u8) -> Relic {
+        Relic {
+            uid: uid.to_string(),
+            slot,
+            location: Some(location.to_string()),
+            level,
+            rarity: 5,
+            ..Default::default()
+        }
+    }
+
+    fn pool() -> HashMap<Slot, Vec<Relic>> {
+        HashMap::from([
+            (
+                Slot::Head,
+                vec![
+                    relic("a", Slot::Head, "", 15),
+                    relic("b", Slot::Head, "1308", 15),
+                    relic("c", Slot::Head, "1306", 15),
+                ],
+            ),
+            (Slot::Feet, vec![relic("d", Slot::Feet, "", 3)]),
+        ])
+    }
+
+    fn uids(pool: &HashMap<Slot, Vec<Relic>>, slot: Slot) -> Vec<&str> {
+        let mut uids: Vec<&str> = pool[&slot].iter().map(|r| r.uid.as_str()).collect();
+        uids.sort();
+        uids
+    }
+
+    #[test]
+    fn test_exclusions() -> Result<()> {
+        let filter = RelicFilter {
+            exclude_equipped: true,
+            min_level: 12,
+            ..Default::default()
+        };
+        let filtered = filter.apply(&pool(), "1308", &["1308"])?;
+        assert_eq!(uids(&filtered, Slot::Head), vec!["a", "b"]);
+        assert!(filtered[&Slot::Feet].is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_pins() -> Result<()> {
+        let filter = RelicFilter {
+            pinned: vec!["c".to_string()],
+            ..Default::default()
+        };
+        let filtered = filter.apply(&pool(), "1308", &["1308"])?;
+        assert_eq!(uids(&filtered, Slot::Head), vec!["c"]);
+
+        // In a team the pin follows the member wearing the relic.
+        let team = ["1308", "1306"];
+        assert_eq!(
+            uids(&filter.apply(&pool(), "1308", &team)?, Slot::Head),
+            vec!["a", "b"]
+        );
+        assert_eq!(
+            uids(&filter.apply(&pool(), "1306", &team)?, Slot::Head),
+            vec!["c"]
+        );
+
+        let filter = RelicFilter {
+            pinned: vec!["a".to_string(), "b".to_string()],
+            ..Default::default()
+        };
+        assert!(filter.apply(&pool(), "1308", &["1308"]).is_err());
+        Ok(())
+    }
+}
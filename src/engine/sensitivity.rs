@@ -0,0 +1,115 @@
+use crate::{
+    character::{Evaluator, Support},
+    domain::{Encounter, Relics, Stats, SubStats, SUBSTATS},
+};
+use eyre::{bail, Result};
+use itertools::Itertools;
+
+/// How much one more substat roll raises the evaluation of a build.
+#[derive(Clone, Debug)]
+pub struct SubstatWeight {
+    pub stat: Stats,
+    /// Value of the roll, see `Stats::average_roll`.
+    pub roll: f64,
+    /// Score gained with the roll.
+    pub gain: f64,
+    /// Gain relative to the best substat, between 0 and 1 for the substats that help.
+    pub weight: f64,
+}
+
+/// Weights of every substat for the build, best first, from the score gained by adding an
+/// average roll of each substat to the build.
+pub fn substat_weights<T>(
+    evaluator: &dyn Evaluator<Target = T>,
+    relics: &Relics,
+    encounter: &Encounter,
+    target: &T,
+    teammates: &[Box<dyn Support>],
+) -> Result<Vec<SubstatWeight>> {
+    if relics.relics.is_empty() {
+        bail!("The build has no relic to add substats to");
+    }
+    let base = evaluator.evaluate(relics, encounter, target, teammates)?;
+    let gains = SUBSTATS
+        .iter()
+        .filter_map(|stat| stat.average_roll().map(|roll| (stat, roll)))
+        .map(|(stat, roll)| {
+            let mut rolled = relics.clone();
+            rolled.relics[0].substats.push(SubStats {
+                key: stat.clone(),
+                value: roll,
+            });
+            let score = evaluator.evaluate(&rolled, encounter, target, teammates)?;
+            Ok((stat.clone(), roll, score - base))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let best = gains.iter().map(|(_, _, gain)| *gain).fold(0.0, f64::max);
+    Ok(gains
+        .into_iter()
+        .sorted_by(|(_, _, a), (_, _, b)| b.total_cmp(a))
+        .map(|(stat, roll, gain)| SubstatWeight {
+            stat,
+            roll,
+            gain,
+            weight: if best > 0.0 { gain / best } else { 0.0 },
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::Relic;
+
+    /// Scores a build by its ATK% and twice its CRIT DMG substats.
+    struct SubstatEvaluator;
+
+    impl Evaluator for SubstatEvaluator {
+        type Target = String;
+
+        fn evaluate(
+            &self,
+            relics: &Relics,
+            _encounter: &Encounter,
+            _target: &String,
+            _teammates: &[Box<dyn Support>],
+        ) -> Result<f64> {
+            Ok(relics
+                .relics
+                .iter()
+                .flat_map(|relic| &relic.substats)
+                .map(|substat| match substat.key {
+                    Stats::Atk_ => substat.value,
+                    Stats::CritDmg_ => 2.0 * substat.value,
+                    _ => 0.0,
+                })
+                .sum())
+        }
+
+        fn targets(&self) -> Vec<String> {
+            vec![]
+        }
+    }
+
+    #[test]
+    fn test_substat_weights() -> Result<()> {
+        let relics = Relics {
+            relics: vec![Relic::default()],
+            config: Default::default(),
+        };
+        let weights = substat_weights(
+            &SubstatEvaluator,
+            &relics,
+            &Encounter::default(),
+            &String::new(),
+            &[],
+        )?;
+        assert_eq!(weights.len(), SUBSTATS.len());
+        assert_eq!(weights[0].stat, Stats::CritDmg_);
+        assert_eq!(weights[0].weight, 1.0);
+        assert_eq!(weights[1].stat, Stats::Atk_);
+        assert!((weights[1].weight - 3.888 / (2.0 * 5.832)).abs() < 1e-9);
+        assert!(weights[2..].iter().all(|w| w.gain == 0.0));
+        Ok(())
+    }
+}
@@ -1,9 +1,9 @@
 use crate::{
     character::{Evaluator, Support},
-    domain::{Enemy, Relic, Relics, Slot},
+    domain::{Encounter, Relic, Relics, Slot},
 };
 use eyre::Result;
-use rand::{seq::SliceRandom, thread_rng, Rng};
+use rand::{seq::SliceRandom, Rng};
 use std::{collections::HashMap, sync::Arc};
 
 pub struct SimulatedAnnealing<T> {
@@ -15,12 +15,16 @@ pub struct SimulatedAnnealing<T> {
     pub evaluator: Arc<dyn Evaluator<Target = T> + Sync + Send>,
 
     pub target: T,
-    pub enemy: Enemy,
+    pub encounter: Encounter,
     pub teammates: Vec<Box<dyn Support>>,
 }
 
 impl<T> SimulatedAnnealing<T> {
-    pub fn simulated_annealing(&self, initial_solution: &Relics) -> Result<Relics> {
+    pub fn simulated_annealing(
+        &self,
+        initial_solution: &Relics,
+        rng: &mut impl Rng,
+    ) -> Result<Relics> {
         let mut current_solution = initial_solution.to_owned();
         let mut best_solution = initial_solution.to_owned();
         let mut current_temp = self.initial_temp;
@@ -38,11 +42,10 @@ impl<T> SimulatedAnnealing<T> {
             );
 
             for _ in 0..num_changes {
-                let mut thread_rng = thread_rng();
-                let index = thread_rng.gen_range(0..neighbor.relics.len() - 1);
+                let index = rng.gen_range(0..neighbor.relics.len() - 1);
                 let slot = &neighbor.relics[index].slot;
                 if let Some(candidates) = self.relic_pool.get(slot) {
-                    if let Some(new_relic) = candidates.choose(&mut thread_rng) {
+                    if let Some(new_relic) = candidates.choose(rng) {
                         neighbor.relics[index] = new_relic.clone();
                     }
                 }
@@ -51,13 +54,16 @@ impl<T> SimulatedAnnealing<T> {
             // Calculate fitness of the neighbor and the current solution
             let current_fitness = self.evaluator.evaluate(
                 &current_solution,
-                &self.enemy,
+                &self.encounter,
+                &self.target,
+                &self.teammates,
+            )?;
+            let neighbor_fitness = self.evaluator.evaluate(
+                &neighbor,
+                &self.encounter,
                 &self.target,
                 &self.teammates,
             )?;
-            let neighbor_fitness =
-                self.evaluator
-                    .evaluate(&neighbor, &self.enemy, &self.target, &self.teammates)?;
 
             // Decide if we should accept the neighbor
             if neighbor_fitness > current_fitness {
@@ -65,7 +71,7 @@ impl<T> SimulatedAnnealing<T> {
             } else {
                 // Accept worse solutions with a probability based on temperature
                 let probability = ((neighbor_fitness - current_fitness) / current_temp).exp();
-                if thread_rng().gen::<f64>() < probability {
+                if rng.gen::<f64>() < probability {
                     current_solution = neighbor;
                 }
             }
@@ -73,12 +79,12 @@ impl<T> SimulatedAnnealing<T> {
             // Update the best solution found so far
             if self.evaluator.evaluate(
                 &current_solution,
-                &self.enemy,
+                &self.encounter,
                 &self.target,
                 &self.teammates,
             )? > self.evaluator.evaluate(
                 &best_solution,
-                &self.enemy,
+                &self.encounter,
                 &self.target,
                 &self.teammates,
             )? {
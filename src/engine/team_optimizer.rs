@@ -0,0 +1,313 @@
+use super::{
+    optimizer::{run_seed, task_rng, Optimizer},
+    simulated_annealing::SimulatedAnnealing,
+};
+use crate::{
+    character::{
+        Evaluator, NamedEvaluator, ScannedSupport, Support, SupportConfig, SupportProfile,
+    },
+    domain::{Character, Encounter, LightConeEntity, Relic, RelicSetConfig, Relics, Slot},
+};
+use eyre::{bail, Result};
+use rand::Rng;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+use tracing::info;
+
+/// Damage a team member contributes to the team objective.
+pub struct MemberObjective {
+    pub evaluator: NamedEvaluator,
+    pub target: String,
+    pub weight: f64,
+}
+
+/// A character optimized as part of a team.
+pub struct TeamMember {
+    pub character: Character,
+    pub light_cone: Option<LightConeEntity>,
+    /// `None` for pure supports, which only count through the buffs they give.
+    pub objective: Option<MemberObjective>,
+    pub support_config: SupportConfig,
+    /// Relics the member may wear, shared with the other members.
+    pub relic_pool: HashMap<Slot, Vec<Relic>>,
+}
+
+/// Builds of every member, in the order of the team.
+pub struct TeamBuild {
+    pub builds: Vec<Relics>,
+    /// Score of each member's target, `None` for pure supports.
+    pub scores: Vec<Option<f64>>,
+    /// Weighted sum of the scores.
+    pub objective: f64,
+}
+
+/// Optimizes up to four characters from a single relic inventory, never using a relic twice.
+///
+/// Members are optimized one at a time with the builds of the others fixed, so the relics they
+/// wear are taken out of the pool. The fitness of a member is the team objective, in which every
+/// member buffs the others according to its current build. Rounds repeat this until the builds
+/// settle, a new build only being kept when it improves the objective.
+pub struct TeamOptimizer {
+    pub members: Arc<Vec<TeamMember>>,
+    pub relic_set_config: RelicSetConfig,
+    pub encounter: Encounter,
+    pub rounds: usize,
+    pub generation: usize,
+    pub population_size: usize,
+    pub mutation_rate: f64,
+    pub crossover_rate: f64,
+    /// Seed of the whole run, a random one when `None`.
+    pub seed: Option<u64>,
+}
+
+impl TeamOptimizer {
+    pub const MAX_MEMBERS: usize = 4;
+
+    pub fn optimize(&self) -> Result<TeamBuild> {
+        if self.members.is_empty() || self.members.len() > Self::MAX_MEMBERS {
+            bail!(
+                "A team has 1 to {} members, got {}",
+                Self::MAX_MEMBERS,
+                self.members.len()
+            );
+        }
+        if self.members.iter().all(|m| m.objective.is_none()) {
+            bail!("At least one team member needs an evaluation target");
+        }
+        let mut builds: Vec<Relics> = self
+            .members
+            .iter()
+            .map(|_| Relics {
+                relics: vec![],
+                config: self.relic_set_config.clone(),
+            })
+            .collect();
+        let seed = run_seed(self.seed);
+        let mut best = team_score(&self.members, &builds, &self.encounter)?.0;
+        for round in 0..self.rounds {
+            for member in self.optimization_order() {
+                let fitness = TeamFitness {
+                    members: self.members.clone(),
+                    builds: builds.clone(),
+                    member,
+                };
+                let relic_pool = available(&self.members[member].relic_pool, &builds, member);
+                let seed = task_rng(seed, round, member as u64).gen();
+                let candidate = self.optimizer(fitness, relic_pool, seed).optimize()?;
+                let mut candidates = builds.clone();
+                candidates[member] = candidate;
+                let (objective, _) = team_score(&self.members, &candidates, &self.encounter)?;
+                if objective > best {
+                    best = objective;
+                    builds = candidates;
+                }
+                info!(
+                    "Round {}, {} optimized, team objective {best}",
+                    round + 1,
+                    self.members[member].character.name
+                );
+            }
+        }
+        let (objective, scores) = team_score(&self.members, &builds, &self.encounter)?;
+        Ok(TeamBuild {
+            builds,
+            scores,
+            objective,
+        })
+    }
+
+    /// Heaviest damage dealers first so they get the first pick, pure supports last.
+    fn optimization_order(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.members.len()).collect();
+        let weight = |i: &usize| {
+            self.members[*i]
+                .objective
+                .as_ref()
+                .map_or(f64::MIN, |o| o.weight)
+        };
+        order.sort_by(|a, b| weight(b).total_cmp(&weight(a)));
+        order
+    }
+
+    fn optimizer(
+        &self,
+        fitness: TeamFitness,
+        relic_pool: HashMap<Slot, Vec<Relic>>,
+        seed: u64,
+    ) -> Optimizer<String> {
+        let evaluator: Arc<dyn Evaluator<Target = String> + Sync + Send> = Arc::new(fitness);
+        let target = TeamFitness::TARGET.to_string();
+        Optimizer {
+            relic_pool: relic_pool.clone(),
+            generation: self.generation,
+            population_size: self.population_size,
+            mutation_rate: self.mutation_rate,
+            crossover_rate: self.crossover_rate,
+            evaluator: evaluator.clone(),
+            enable_sa: false,
+            simulated_annealing: SimulatedAnnealing {
+                initial_temp: 0.0,
+                cooling_rate: 0.0,
+                min_temp: 0.0,
+                aggresive_factor: 0.0,
+                relic_pool,
+                evaluator,
+                target: target.clone(),
+                encounter: self.encounter.clone(),
+                teammates: vec![],
+            },
+            target,
+            encounter: self.encounter.clone(),
+            teammates: vec![],
+            relic_set_config: self.relic_set_config.clone(),
+            seed: Some(seed),
+        }
+    }
+}
+
+/// `relic_pool` without the relics worn by the other members.
+fn available(
+    relic_pool: &HashMap<Slot, Vec<Relic>>,
+    builds: &[Relics],
+    member: usize,
+) -> HashMap<Slot, Vec<Relic>> {
+    let taken: HashSet<&str> = builds
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != member)
+        .flat_map(|(_, build)| build.relics.iter().map(|r| r.uid.as_str()))
+        .collect();
+    relic_pool
+        .iter()
+        .map(|(slot, relics)| {
+            let relics = relics
+                .iter()
+                .filter(|r| !taken.contains(r.uid.as_str()))
+                .cloned()
+                .collect();
+            (slot.clone(), relics)
+        })
+        .collect()
+}
+
+/// Team objective as a function of the build of one member, the others being fixed.
+struct TeamFitness {
+    members: Arc<Vec<TeamMember>>,
+    builds: Vec<Relics>,
+    member: usize,
+}
+
+impl TeamFitness {
+    const TARGET: &'static str = "Team";
+}
+
+impl Evaluator for TeamFitness {
+    type Target = String;
+
+    fn evaluate(
+        &self,
+        relics: &Relics,
+        encounter: &Encounter,
+        _target: &Self::Target,
+        _teammates: &[Box<dyn Support>],
+    ) -> Result<f64> {
+        let mut builds = self.builds.clone();
+        builds[self.member] = relics.clone();
+        Ok(team_score(&self.members, &builds, encounter)?.0)
+    }
+
+    fn targets(&self) -> Vec<String> {
+        vec![Self::TARGET.to_string()]
+    }
+}
+
+/// Weighted team objective and the score of each member, every member buffing the others
+/// according to its build.
+pub fn team_score(
+    members: &[TeamMember],
+    builds: &[Relics],
+    encounter: &Encounter,
+) -> Result<(f64, Vec<Option<f64>>)> {
+    let profiles = members
+        .iter()
+        .zip(builds)
+        .map(|(member, relics)| {
+            SupportProfile::new(member.character.clone(), member.light_cone.clone(), relics)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let mut objective = 0.0;
+    let mut scores = Vec::with_capacity(members.len());
+    for (i, member) in members.iter().enumerate() {
+        let Some(member_objective) = &member.objective else {
+            scores.push(None);
+            continue;
+        };
+        let teammates: Vec<Box<dyn Support>> = profiles
+            .iter()
+            .zip(members.iter())
+            .enumerate()
+            .filter(|(j, _)| *j != i)
+            .map(|(_, (profile, teammate))| {
+                Box::new(ScannedSupport::new(
+                    profile.clone(),
+                    teammate.support_config.clone(),
+                )) as Box<dyn Support>
+            })
+            .collect();
+        let score = member_objective.evaluator.evaluate(
+            &builds[i],
+            encounter,
+            &member_objective.target,
+            &teammates,
+        )?;
+        objective += member_objective.weight * score;
+        scores.push(Some(score));
+    }
+    Ok((objective, scores))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn relic(uid: &str, slot: Slot) -> Relic {
+        Relic {
+            slot,
+            uid: uid.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_relics_are_not_shared() {
+        let relic_pool = HashMap::from([
+            (
+                Slot::Head,
+                vec![relic("a", Slot::Head), relic("b", Slot::Head)],
+            ),
+            (Slot::Feet, vec![relic("c", Slot::Feet)]),
+        ]);
+        let builds = vec![
+            Relics {
+                relics: vec![relic("a", Slot::Head), relic("c", Slot::Feet)],
+                config: RelicSetConfig::default(),
+            },
+            Relics {
+                relics: vec![relic("b", Slot::Head)],
+                config: RelicSetConfig::default(),
+            },
+        ];
+        let pool = available(&relic_pool, &builds, 1);
+        let uids = |slot: Slot| {
+            pool[&slot]
+                .iter()
+                .map(|r| r.uid.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(uids(Slot::Head), vec!["b"]);
+        assert!(uids(Slot::Feet).is_empty());
+        assert_eq!(available(&relic_pool, &builds, 0)[&Slot::Feet].len(), 1);
+    }
+}
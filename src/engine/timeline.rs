@@ -0,0 +1,426 @@
+use eyre::{bail, eyre, Result};
+use legion::{Entity, IntoQuery, World};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+/// Action value of the first cycle of Memory of Chaos, the next ones lasting `CYCLE_AV`.
+pub const FIRST_CYCLE_AV: f64 = 150.0;
+pub const CYCLE_AV: f64 = 100.0;
+
+/// A rotation of the character simulated over a number of cycles, the relic set and light cone
+/// entries of its buffs following the turn order instead of being set by hand.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct TimelineSettings {
+    pub cycles: usize,
+    /// Actions of the character, repeated turn after turn.
+    pub rotation: Vec<RotationStep>,
+    /// Used as soon as the character has the energy for it.
+    pub ultimate: Option<UltimateStep>,
+    /// Energy at the start of the fight, as a fraction of the ultimate cost.
+    pub initial_energy: f64,
+    /// SPD of each teammate, whose turns can trigger buffs.
+    pub teammate_spd: Vec<f64>,
+    pub buffs: Vec<TimelineBuff>,
+}
+
+impl Default for TimelineSettings {
+    fn default() -> Self {
+        Self {
+            cycles: 3,
+            rotation: vec![],
+            ultimate: None,
+            initial_energy: 0.5,
+            teammate_spd: vec![],
+            buffs: vec![],
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ActionKind {
+    Basic,
+    Skill,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct RotationStep {
+    /// Evaluation target scoring the action.
+    pub target: String,
+    pub action: ActionKind,
+    /// Energy gained before ERR, 20 for a basic attack and 30 for a skill by default.
+    pub energy: Option<f64>,
+}
+
+impl RotationStep {
+    fn energy(&self) -> f64 {
+        self.energy.unwrap_or(match self.action {
+            ActionKind::Basic => 20.0,
+            ActionKind::Skill => 30.0,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct UltimateStep {
+    pub target: String,
+    pub cost: f64,
+    /// Energy given back before ERR.
+    #[serde(default = "default_ultimate_energy")]
+    pub energy: f64,
+}
+
+fn default_ultimate_energy() -> f64 {
+    5.0
+}
+
+/// Event of the fight a buff is gained on.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Trigger {
+    BattleStart,
+    Basic,
+    Skill,
+    Ultimate,
+    /// Any action of the character.
+    Attack,
+    TeammateTurn,
+}
+
+/// A relic set or light cone config entry driven by the simulation, e.g. `activate_109` gained
+/// on skill for one turn, or `stack_105` stacking up on every attack.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct TimelineBuff {
+    pub config: String,
+    pub on: Trigger,
+    /// Turns of the character the buff lasts, for good when not given. A buff gained during
+    /// the character's turn does not count that turn down.
+    pub turns: Option<u32>,
+    #[serde(default = "default_stacks")]
+    pub stacks: u8,
+    #[serde(default = "default_stacks")]
+    pub max: u8,
+}
+
+fn default_stacks() -> u8 {
+    1
+}
+
+/// An action of the fight with the config entries of the buffs when it happens.
+#[derive(Debug, Clone)]
+pub struct TimelineAction {
+    pub av: f64,
+    pub cycle: usize,
+    pub unit: String,
+    /// Evaluation target of the character's actions, `None` for teammates.
+    pub target: Option<String>,
+    pub state: HashMap<String, u8>,
+}
+
+struct Unit {
+    name: String,
+    carry: bool,
+}
+
+struct Speed(f64);
+
+/// Action value left before the next turn of the unit.
+struct ActionValue(f64);
+
+struct Energy(f64);
+
+struct Cursor(usize);
+
+#[derive(Default)]
+struct BuffState {
+    stacks: u8,
+    turns: Option<u32>,
+    /// Gained during the current turn of the character, which doesn't count down.
+    fresh: bool,
+}
+
+#[derive(Default)]
+struct Buffs(HashMap<String, BuffState>);
+
+impl Buffs {
+    fn trigger(&mut self, buffs: &[TimelineBuff], trigger: Trigger, own_turn: bool) {
+        for buff in buffs.iter().filter(|buff| buff.on == trigger) {
+            let state = self.0.entry(buff.config.clone()).or_default();
+            state.stacks = (state.stacks + buff.stacks).min(buff.max);
+            state.turns = buff.turns;
+            state.fresh = own_turn;
+        }
+    }
+
+    fn end_turn(&mut self) {
+        for state in self.0.values_mut() {
+            if state.fresh {
+                state.fresh = false;
+            } else if let Some(turns) = &mut state.turns {
+                *turns = turns.saturating_sub(1);
+            }
+        }
+        self.0.retain(|_, state| state.turns != Some(0));
+    }
+
+    fn snapshot(&self, buffs: &[TimelineBuff]) -> HashMap<String, u8> {
+        buffs
+            .iter()
+            .map(|buff| {
+                let stacks = self.0.get(&buff.config).map_or(0, |state| state.stacks);
+                (buff.config.clone(), stacks)
+            })
+            .collect()
+    }
+}
+
+/// Cycle an action happens in, the first one lasting `FIRST_CYCLE_AV`.
+pub fn cycle_of(av: f64) -> usize {
+    ((av - FIRST_CYCLE_AV).max(0.0) / CYCLE_AV).ceil() as usize
+}
+
+/// Plays the rotation of a character with `spd` and `err` (in percent, 100 without bonus)
+/// along with its teammates, turn after turn by action value.
+pub fn simulate(settings: &TimelineSettings, spd: f64, err: f64) -> Result<Vec<TimelineAction>> {
+    if settings.rotation.is_empty() {
+        bail!("The timeline rotation has no action");
+    }
+    if spd <= 0.0 || settings.teammate_spd.iter().any(|spd| *spd <= 0.0) {
+        bail!("Every unit of the timeline needs a positive SPD");
+    }
+    let err = err / 100.0;
+    let mut world = World::default();
+    let carry = world.push((
+        Unit {
+            name: "Character".to_string(),
+            carry: true,
+        },
+        Speed(spd),
+        ActionValue(10000.0 / spd),
+        Energy(settings.initial_energy * settings.ultimate.as_ref().map_or(0.0, |u| u.cost)),
+        Cursor(0),
+        Buffs::default(),
+    ));
+    for (i, spd) in settings.teammate_spd.iter().enumerate() {
+        world.push((
+            Unit {
+                name: format!("Teammate {}", i + 1),
+                carry: false,
+            },
+            Speed(*spd),
+            ActionValue(10000.0 / spd),
+        ));
+    }
+    let mut entry = world.entry(carry).ok_or(eyre!("No character entity"))?;
+    entry
+        .get_component_mut::<Buffs>()?
+        .trigger(&settings.buffs, Trigger::BattleStart, false);
+
+    let end = FIRST_CYCLE_AV + CYCLE_AV * settings.cycles.saturating_sub(1) as f64;
+    let mut elapsed = 0.0;
+    let mut actions = vec![];
+    loop {
+        let (unit, wait) = <(Entity, &ActionValue)>::query()
+            .iter(&world)
+            .map(|(entity, av)| (*entity, av.0))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .ok_or(eyre!("No unit in the timeline"))?;
+        if elapsed + wait > end {
+            break;
+        }
+        elapsed += wait;
+        for av in <&mut ActionValue>::query().iter_mut(&mut world) {
+            av.0 -= wait;
+        }
+        let mut entry = world.entry(unit).ok_or(eyre!("Unit {unit:?} vanished"))?;
+        let spd = entry.get_component::<Speed>()?.0;
+        entry.get_component_mut::<ActionValue>()?.0 = 10000.0 / spd;
+        let unit = entry.get_component::<Unit>()?;
+        let name = unit.name.clone();
+        if !unit.carry {
+            let mut carry = world.entry(carry).ok_or(eyre!("No character entity"))?;
+            let buffs = carry.get_component_mut::<Buffs>()?;
+            actions.push(TimelineAction {
+                av: elapsed,
+                cycle: cycle_of(elapsed),
+                unit: name,
+                target: None,
+                state: buffs.snapshot(&settings.buffs),
+            });
+            buffs.trigger(&settings.buffs, Trigger::TeammateTurn, false);
+            continue;
+        }
+
+        let cursor = entry.get_component_mut::<Cursor>()?;
+        let step = &settings.rotation[cursor.0 % settings.rotation.len()];
+        cursor.0 += 1;
+        let action = |target: &str, buffs: &Buffs| TimelineAction {
+            av: elapsed,
+            cycle: cycle_of(elapsed),
+            unit: name.clone(),
+            target: Some(target.to_string()),
+            state: buffs.snapshot(&settings.buffs),
+        };
+        let turn = action(&step.target, entry.get_component::<Buffs>()?);
+        actions.push(turn);
+        let trigger = match step.action {
+            ActionKind::Basic => Trigger::Basic,
+            ActionKind::Skill => Trigger::Skill,
+        };
+        let buffs = entry.get_component_mut::<Buffs>()?;
+        buffs.trigger(&settings.buffs, trigger, true);
+        buffs.trigger(&settings.buffs, Trigger::Attack, true);
+        entry.get_component_mut::<Energy>()?.0 += step.energy() * err;
+
+        if let Some(ultimate) = &settings.ultimate {
+            if entry.get_component::<Energy>()?.0 >= ultimate.cost {
+                let ult = action(&ultimate.target, entry.get_component::<Buffs>()?);
+                actions.push(ult);
+                let energy = entry.get_component_mut::<Energy>()?;
+                energy.0 += ultimate.energy * err - ultimate.cost;
+                let buffs = entry.get_component_mut::<Buffs>()?;
+                buffs.trigger(&settings.buffs, Trigger::Ultimate, true);
+                buffs.trigger(&settings.buffs, Trigger::Attack, true);
+            }
+        }
+        entry.get_component_mut::<Buffs>()?.end_turn();
+    }
+    Ok(actions)
+}
+
+/// The config with the entries of the buffs set to the simulated stacks, flags being on with
+/// any stack. Entries the config has no field for are left out.
+pub fn with_state<T: Serialize + DeserializeOwned>(
+    config: &T,
+    state: &HashMap<String, u8>,
+    extra: bool,
+) -> Result<T> {
+    let mut value = serde_json::to_value(config)?;
+    let fields = value
+        .as_object_mut()
+        .ok_or_else(|| eyre!("Config is not a map of fields"))?;
+    for (key, stacks) in state {
+        let flag = match fields.get(key) {
+            Some(field) => field.is_boolean(),
+            None if extra => key.starts_with("activate_"),
+            None => continue,
+        };
+        let value = match flag {
+            true => Value::Bool(*stacks > 0),
+            false => Value::from(*stacks),
+        };
+        fields.insert(key.clone(), value);
+    }
+    Ok(serde_json::from_value(value)?)
+}
+
+/// Damage of the character's actions added up per cycle, `evaluate` scoring one action.
+pub fn damage_per_cycle(
+    actions: &[TimelineAction],
+    cycles: usize,
+    mut evaluate: impl FnMut(&TimelineAction) -> Result<f64>,
+) -> Result<Vec<f64>> {
+    let mut damage = vec![0.0; cycles];
+    for action in actions.iter().filter(|action| action.target.is_some()) {
+        if let Some(total) = damage.get_mut(action.cycle) {
+            *total += evaluate(action)?;
+        }
+    }
+    Ok(damage)
+}
+
+/// Share of the character's actions each buff is up for.
+pub fn uptime(actions: &[TimelineAction]) -> BTreeMap<String, f64> {
+    let own: Vec<_> = actions.iter().filter(|a| a.target.is_some()).collect();
+    let mut uptime = BTreeMap::new();
+    for action in &own {
+        for (key, stacks) in &action.state {
+            *uptime.entry(key.clone()).or_default() +=
+                (*stacks > 0) as u8 as f64 / own.len() as f64;
+        }
+    }
+    uptime
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::RelicSetConfig;
+
+    fn step(target: &str, action: ActionKind) -> RotationStep {
+        RotationStep {
+            target: target.to_string(),
+            action,
+            energy: None,
+        }
+    }
+
+    #[test]
+    fn test_timeline() -> Result<()> {
+        let settings = TimelineSettings {
+            cycles: 3,
+            rotation: vec![
+                step("Skill", ActionKind::Skill),
+                step("Basic", ActionKind::Basic),
+            ],
+            ultimate: Some(UltimateStep {
+                target: "Ultimate".to_string(),
+                cost: 100.0,
+                energy: 5.0,
+            }),
+            teammate_spd: vec![100.0],
+            buffs: vec![
+                TimelineBuff {
+                    config: "activate_109".to_string(),
+                    on: Trigger::Skill,
+                    turns: Some(1),
+                    stacks: 1,
+                    max: 1,
+                },
+                TimelineBuff {
+                    config: "stack_105".to_string(),
+                    on: Trigger::Attack,
+                    turns: None,
+                    stacks: 1,
+                    max: 5,
+                },
+            ],
+            ..Default::default()
+        };
+        let actions = simulate(&settings, 134.0, 100.0)?;
+        let own: Vec<_> = actions
+            .iter()
+            .filter_map(|a| Some((a.target.as_deref()?, a.cycle, a.state["activate_109"])))
+            .collect();
+        // 74.6 AV per turn: two turns in the first 150 AV, then one per cycle. The second turn
+        // fills the 100 energy started at 50, the ultimate still being within the skill buff.
+        assert_eq!(
+            own,
+            [
+                ("Skill", 0, 0),
+                ("Basic", 0, 1),
+                ("Ultimate", 0, 1),
+                ("Skill", 1, 0),
+                ("Basic", 2, 1),
+            ]
+        );
+        assert_eq!(actions.iter().filter(|a| a.target.is_none()).count(), 3);
+        let last = actions.iter().rfind(|a| a.target.is_some()).unwrap();
+        assert_eq!(last.state["stack_105"], 4);
+
+        let uptime = uptime(&actions);
+        assert!((uptime["activate_109"] - 0.6).abs() < 1e-9);
+        let damage = damage_per_cycle(&actions, settings.cycles, |_| Ok(1.0))?;
+        assert_eq!(damage, vec![3.0, 1.0, 1.0]);
+
+        let config = with_state(&RelicSetConfig::all_active(), &last.state, true)?;
+        assert!(config.activate_109);
+        assert_eq!(config.stack_105, 4);
+        Ok(())
+    }
+}
@@ -0,0 +1,304 @@
+use crate::domain::{Relic, Stats, SubStats, SUBSTATS};
+use eyre::{bail, Result};
+use itertools::Itertools;
+use rand::{distributions::WeightedIndex, prelude::Distribution, Rng};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// How the relics that can still be levelled are valued by the optimizer.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Projection {
+    /// As they are now.
+    #[default]
+    Current,
+    /// Levelled to the maximum with the expected substats.
+    Expected,
+    /// Levelled to the maximum with every roll a high roll of the substat weighing most.
+    BestCase,
+}
+
+/// Level of a fully upgraded relic, e.g. +15 for a 5-star relic.
+pub fn max_level(rarity: u8) -> u8 {
+    rarity * 3
+}
+
+/// Number of substat rolls left before the relic is fully upgraded, one every 3 levels. Only
+/// 5-star relics are counted, the roll tables being those of 5-star relics.
+pub fn remaining_upgrades(relic: &Relic) -> usize {
+    if relic.rarity != 5 {
+        return 0;
+    }
+    (max_level(relic.rarity) / 3 - relic.level.min(max_level(relic.rarity)) / 3) as usize
+}
+
+/// Chance of each substat the relic can still get to be picked when a new substat is added.
+fn new_substats(relic: &Relic) -> Vec<(Stats, f64)> {
+    SUBSTATS
+        .iter()
+        .filter(|stat| **stat != relic.mainstat)
+        .filter(|stat| !relic.substats.iter().any(|s| s.key == **stat))
+        .map(|stat| {
+            let weight = match stat {
+                Stats::Spd => 4.0,
+                Stats::CritRate_ | Stats::CritDmg_ => 6.0,
+                Stats::EffectHitRate_ | Stats::EffectRes_ | Stats::BreakEffect_ => 8.0,
+                _ => 10.0,
+            };
+            (stat.clone(), weight)
+        })
+        .collect()
+}
+
+fn add_roll(relic: &mut Relic, stat: &Stats, value: f64) {
+    match relic.substats.iter_mut().find(|s| s.key == *stat) {
+        Some(substat) => substat.value += value,
+        None => relic.substats.push(SubStats {
+            key: stat.clone(),
+            value,
+        }),
+    }
+}
+
+/// The relic at its maximum level, its main stat levelled and its substats left as they are.
+fn levelled(relic: &Relic) -> Result<Relic> {
+    let level = if remaining_upgrades(relic) > 0 {
+        max_level(relic.rarity)
+    } else {
+        relic.level
+    };
+    Ok(Relic {
+        mainstat_value: relic.mainstat_at(level)?,
+        level,
+        ..relic.clone()
+    })
+}
+
+/// A random outcome of levelling the relic to its maximum level. Each upgrade adds a new substat
+/// while the relic has fewer than 4, then rolls one of its 4 substats.
+pub fn upgrade(relic: &Relic, rng: &mut impl Rng) -> Result<Relic> {
+    let mut upgraded = levelled(relic)?;
+    for _ in 0..remaining_upgrades(relic) {
+        let stat = if upgraded.substats.len() < 4 {
+            let candidates = new_substats(&upgraded);
+            let index = WeightedIndex::new(candidates.iter().map(|(_, weight)| weight))?;
+            candidates[index.sample(rng)].0.clone()
+        } else {
+            upgraded.substats[rng.gen_range(0..upgraded.substats.len())]
+                .key
+                .clone()
+        };
+        let Some((base, step)) = stat.substat_roll() else {
+            bail!("{stat:?} is not a substat");
+        };
+        add_roll(
+            &mut upgraded,
+            &stat,
+            base + step * rng.gen_range(0..=2) as f64,
+        );
+    }
+    Ok(upgraded)
+}
+
+/// The relic levelled to its maximum level with the expected value of each substat. A relic with
+/// 3 substats gets every substat it can still roll, weighted by the chance to roll it.
+pub fn expected(relic: &Relic) -> Result<Relic> {
+    let upgrades = remaining_upgrades(relic);
+    let mut upgraded = levelled(relic)?;
+    if upgrades == 0 {
+        return Ok(upgraded);
+    }
+    let mut rolls = upgrades as f64;
+    if relic.substats.len() < 4 {
+        if relic.substats.len() < 3 {
+            bail!(
+                "Relic {} has too few substats for a 5-star relic",
+                relic.uid
+            );
+        }
+        let candidates = new_substats(relic);
+        let total: f64 = candidates.iter().map(|(_, weight)| weight).sum();
+        rolls -= 1.0;
+        for (stat, weight) in candidates {
+            let roll = stat.average_roll().unwrap_or_default();
+            add_roll(
+                &mut upgraded,
+                &stat,
+                weight / total * (1.0 + rolls / 4.0) * roll,
+            );
+        }
+    }
+    for substat in &relic.substats {
+        let roll = substat.key.average_roll().unwrap_or_default();
+        add_roll(&mut upgraded, &substat.key, rolls / 4.0 * roll);
+    }
+    Ok(upgraded)
+}
+
+/// The relic levelled to its maximum level, every roll being a high roll of the substat that
+/// weighs the most, see `relic_score`.
+pub fn best_case(relic: &Relic, weights: &HashMap<Stats, f64>) -> Result<Relic> {
+    let weight = |stat: &Stats| weights.get(stat).cloned().unwrap_or_default();
+    let mut upgraded = levelled(relic)?;
+    for _ in 0..remaining_upgrades(relic) {
+        let stat = if upgraded.substats.len() < 4 {
+            new_substats(&upgraded)
+                .into_iter()
+                .map(|(stat, _)| stat)
+                .max_by(|a, b| weight(a).total_cmp(&weight(b)))
+        } else {
+            upgraded
+                .substats
+                .iter()
+                .map(|s| s.key.clone())
+                .max_by(|a, b| weight(a).total_cmp(&weight(b)))
+        };
+        let Some((stat, (base, step))) = stat.and_then(|s| s.substat_roll().map(|r| (s, r))) else {
+            bail!("Relic {} has no substat to roll", relic.uid);
+        };
+        add_roll(&mut upgraded, &stat, base + 2.0 * step);
+    }
+    Ok(upgraded)
+}
+
+/// The relic as valued by the projection.
+pub fn project(
+    relic: &Relic,
+    projection: Projection,
+    weights: &HashMap<Stats, f64>,
+) -> Result<Relic> {
+    match projection {
+        Projection::Current => Ok(relic.clone()),
+        Projection::Expected => expected(relic),
+        Projection::BestCase => best_case(relic, weights),
+    }
+}
+
+/// Substats of the relic counted in average rolls, each weighted by how much the substat is
+/// worth, e.g. with the weights of `substat_weights`.
+pub fn relic_score(relic: &Relic, weights: &HashMap<Stats, f64>) -> f64 {
+    relic
+        .substats
+        .iter()
+        .filter_map(|substat| {
+            let roll = substat.key.average_roll()?;
+            let weight = weights.get(&substat.key).cloned().unwrap_or_default();
+            Some(weight * substat.value / roll)
+        })
+        .sum()
+}
+
+/// Distribution of the score of a relic once fully upgraded.
+#[derive(Clone, Debug)]
+pub struct Potential {
+    /// Score of the relic as it is now.
+    pub current: f64,
+    /// Scores of the simulated upgrades, sorted.
+    pub samples: Vec<f64>,
+}
+
+impl Potential {
+    /// Expected score once fully upgraded.
+    pub fn mean(&self) -> f64 {
+        self.samples.iter().sum::<f64>() / self.samples.len() as f64
+    }
+
+    /// Score reached or beaten by `100 - p` percent of the upgrades.
+    pub fn percentile(&self, p: f64) -> f64 {
+        let index = (p / 100.0 * (self.samples.len() - 1) as f64).round() as usize;
+        self.samples[index.min(self.samples.len() - 1)]
+    }
+}
+
+/// Simulates levelling the relic to its maximum level `samples` times.
+pub fn potential(
+    relic: &Relic,
+    weights: &HashMap<Stats, f64>,
+    samples: usize,
+    rng: &mut impl Rng,
+) -> Result<Potential> {
+    if samples == 0 {
+        bail!("At least one sample is needed");
+    }
+    let samples = (0..samples)
+        .map(|_| Ok(relic_score(&upgrade(relic, rng)?, weights)))
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .sorted_by(f64::total_cmp)
+        .collect();
+    Ok(Potential {
+        current: relic_score(relic, weights),
+        samples,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::Slot;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn relic(level: u8, substats: &[(Stats, f64)]) -> Relic {
+        Relic {
+            uid: "relic".to_string(),
+            slot: Slot::Head,
+            rarity: 5,
+            level,
+            mainstat: Stats::Hp,
+            mainstat_value: 112.896 + 39.5136 * level as f64,
+            substats: substats
+                .iter()
+                .map(|(key, value)| SubStats {
+                    key: key.clone(),
+                    value: *value,
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_expected_matches_simulation() -> Result<()> {
+        let relic = relic(
+            0,
+            &[
+                (Stats::CritRate_, 2.592),
+                (Stats::CritDmg_, 5.184),
+                (Stats::Atk_, 3.456),
+            ],
+        );
+        assert_eq!(remaining_upgrades(&relic), 5);
+        let weights = HashMap::from([
+            (Stats::CritRate_, 1.0),
+            (Stats::CritDmg_, 1.0),
+            (Stats::Atk_, 0.5),
+            (Stats::Spd, 0.8),
+        ]);
+
+        let expected = expected(&relic)?;
+        assert_eq!(expected.level, 15);
+        assert!((expected.mainstat_value - (112.896 + 39.5136 * 15.0)).abs() < 1e-9);
+
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+        let potential = potential(&relic, &weights, 20000, &mut rng)?;
+        let score = relic_score(&expected, &weights);
+        assert!((potential.mean() - score).abs() < 0.05 * score);
+        assert!(potential.percentile(10.0) <= potential.percentile(90.0));
+
+        let best = best_case(&relic, &weights)?;
+        assert_eq!(best.substats.len(), 4);
+        assert!(relic_score(&best, &weights) >= potential.percentile(100.0) - 1e-9);
+        Ok(())
+    }
+
+    #[test]
+    fn test_levelled_relics_are_left_as_they_are() -> Result<()> {
+        let relic = relic(15, &[(Stats::Spd, 2.3)]);
+        assert_eq!(remaining_upgrades(&relic), 0);
+        let expected = expected(&relic)?;
+        assert_eq!(expected.mainstat_value, relic.mainstat_value);
+        assert_eq!(expected.substats[0].value, 2.3);
+        Ok(())
+    }
+}
@@ -1,18 +1,9 @@
-use crate::{
-    character::{AcheronEvaluationTarget, Support},
-    domain::{LightConePassiveConfig, Path, RelicSetConfig, ScannerInput},
-};
-use character::{Acheron, Evaluator};
-use client::project_yatta_client::ProjectYattaClient;
-use data_fetcher::project_yatta_data_fetcher::ProjectYattaDataFetcher;
-use domain::Enemy;
-use engine::{optimizer::Optimizer, simulated_annealing::SimulatedAnnealing};
-use eyre::{eyre, Result};
-use service::scanner_parser_service::ScannerParserService;
-use std::{collections::HashMap, fs, sync::Arc};
-use tokio::sync::Mutex;
+use clap::Parser;
+use cli::Cli;
+use eyre::Result;
 
 mod character;
+mod cli;
 mod client;
 mod data_fetcher;
 mod domain;
@@ -23,173 +14,7 @@ mod utils;
 #[tokio::main]
 async fn main() -> Result<()> {
     setup_logging();
-
-    // let data_fetcher = Arc::new(HoyowikiDataFetcherService {
-    //     client: HoyowikiClient {
-    //         base_url: "https://sg-wiki-api-static.hoyolab.com/hoyowiki/hsr/wapi".to_string(),
-    //         language: "en-us".to_string(),
-    //         wiki_app: "hsr".to_string(),
-    //     },
-    // });
-    let data_fetcher = Arc::new(Mutex::new(ProjectYattaDataFetcher {
-        client: ProjectYattaClient {
-            url: "https://sr.yatta.moe/api/v2/en/".to_string(),
-            light_cone_cache: HashMap::new(),
-        },
-    }));
-    let mut scanner_parser_service = ScannerParserService { data_fetcher };
-    let input = load_input_data("scanned_data/HSRScanData_20250730_111506.json").await?;
-    let (characters, light_cones, relic_pool) =
-        scanner_parser_service.parse_scanner_input(&input).await?;
-    // let evaluator = create_evaluator(
-    //     characters
-    //         .get("1308")
-    //         .ok_or_else(|| eyre!("Acheron not found"))?,
-    //     light_cones
-    //         .get("light_cone_10")
-    //         .ok_or_else(|| eyre!("Acheron's light cone not found"))?,
-    // )
-    // .await?;
-    let mut light_cone = light_cones
-        .get("light_cone_15")
-        .ok_or_else(|| eyre!("Acheron's light cone not found"))?
-        .clone();
-    light_cone.config = LightConePassiveConfig {
-        stack_21001: 2,
-        ..Default::default()
-    };
-    let a_evaluator: Arc<dyn Evaluator<Target = AcheronEvaluationTarget> + Send + Sync> =
-        Arc::new(Acheron {
-            character: characters
-                .get("1308")
-                .ok_or_else(|| eyre!("Acheron not found"))?
-                .clone(),
-            light_cone: Some(light_cone),
-            crimson_knot: 9,
-            crit: domain::CritEnum::Avg,
-            thunder_core_bonus_stack: 3,
-            activate_eidolon_1: false,
-        });
-    let enemy = Enemy {
-        level: 80,
-        resistance: 0.0,
-        dmg_mitigation: vec![],
-        def_bonus: 0.0,
-        vulnerability: 0.0,
-        toughness_break: false,
-        weaken: 0.0,
-    };
-
-    // let battle_conditions = vec![
-    //     BattleConditionEnum::AfterUsingSkill {
-    //         number_of_turns_since_using_the_skill: 1,
-    //     },
-    //     BattleConditionEnum::AfterUsingUltimate {
-    //         next_attack_after_ultimate: false,
-    //         next_skill_after_ultimate: false,
-    //         number_of_turns_since_using_ultimate: 1,
-    //     },
-    //     BattleConditionEnum::AfterWearerAttack { number_of_times: 3 },
-    //     BattleConditionEnum::AfterWearerIsHit {
-    //         number_of_times: 2,
-    //         within_number_of_turns: 1,
-    //     },
-    //     BattleConditionEnum::AfterAttackingDebuffedEnemy,
-    //     BattleConditionEnum::AfterWearerInflictingDebuffs {
-    //         number_of_times: 1,
-    //         within_number_of_turns: 1,
-    //     },
-    //     BattleConditionEnum::WhenAttackingEnemyWithDebuff {
-    //         number_of_debuffs_enemy_has: 3,
-    //         within_number_of_turns: 1,
-    //     },
-    //     BattleConditionEnum::TeammatesSamePathWithWearer {
-    //         number_of_teammates_having_same_path: 1,
-    //     },
-    //     BattleConditionEnum::HittingEnemyWithCrimsonKnot {
-    //         number_of_crinsom_knot_enemy_has: 3,
-    //     },
-    //     BattleConditionEnum::CriticalHit(CritEnum::Avg),
-    //     BattleConditionEnum::ToughnessBreak(true),
-    //     BattleConditionEnum::AfterHittingEnemyWithCrinsomKnot { number_of_times: 3 },
-    // ];
-
-    struct Pela {}
-
-    impl Support for Pela {
-        fn get_path(&self) -> Path {
-            Path::Nihility
-        }
-    }
-
-    struct Jiaoqiu {}
-
-    impl Support for Jiaoqiu {
-        fn get_path(&self) -> Path {
-            Path::Nihility
-        }
-    }
-
-    let simulated_annealing = SimulatedAnnealing {
-        initial_temp: 1000.0,
-        cooling_rate: 0.99,
-        min_temp: 0.1,
-        aggresive_factor: 0.9,
-        relic_pool: relic_pool.clone(),
-        evaluator: a_evaluator.clone(),
-        teammates: vec![Box::new(Pela {}), Box::new(Jiaoqiu {})],
-        enemy: enemy.clone(),
-        target: AcheronEvaluationTarget::UltimateAoe,
-    };
-
-    let optimizer = Optimizer {
-        relic_pool,
-        generation: 100,
-        population_size: 1000,
-        mutation_rate: 0.1,
-        crossover_rate: 0.7,
-        evaluator: a_evaluator,
-        enable_sa: false,
-        simulated_annealing,
-        enemy,
-        target: AcheronEvaluationTarget::UltimateAoe,
-        teammates: vec![Box::new(Pela {}), Box::new(Jiaoqiu {})],
-        relic_set_config: RelicSetConfig {
-            activate_102: true,
-            activate_104: true,
-            stack_105: 5,
-            activate_107: true,
-            activate_108: true,
-            activate_109: true,
-            activate_112_1: true,
-            activate_112_2: true,
-            stack_113: 5,
-            stack_115: 5,
-            stack_116: 5,
-            activate_117_2pcs: true,
-            stack_117: 5,
-            activate_117_4pcs_extra: true,
-            activate_120: true,
-            activate_122: true,
-            activate_123_1: true,
-            activate_123_2: true,
-            activate_125: true,
-            activate_126: true,
-            activate_305: true,
-            stack_313: 5,
-            stack_315: 5,
-            activate_316: true,
-            activate_318: true,
-            stack_321: 4,
-        },
-    };
-
-    println!("----------------- Optimizing Character -----------------");
-
-    let res = optimizer.optimize()?;
-    println!("Optimized relics: {:#?}", res);
-
-    Ok(())
+    Cli::parse().run().await
 }
 
 /// Sets up logging configuration.
@@ -199,15 +24,6 @@ fn setup_logging() {
         .init();
 }
 
-/// Loads and deserializes the input JSON data.
-async fn load_input_data(file_path: &str) -> Result<ScannerInput> {
-    let file = fs::File::open(file_path)?;
-    let json: serde_json::Value = serde_json::from_reader(file)?;
-    let mut input: ScannerInput = serde_json::from_value(json)?;
-    input.update().await?;
-    Ok(input)
-}
-
 // / Creates an evaluator instance using the input data.
 // async fn create_evaluator(
 //     character: &CharacterEntity,
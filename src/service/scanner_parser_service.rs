@@ -1,14 +1,19 @@
 use crate::{
     data_fetcher::DataFetcher,
     domain::{
-        Character, LightCone, LightConeEntity, RawCharacter, RawRelic, Relic, ScannerInput, Slot,
+        relic_sets, Character, DataError, LightCone, LightConeEntity, RawCharacter, RawRelic,
+        Relic, ScannerInput, Slot,
     },
 };
 use eyre::Result;
 use futures::future::try_join_all;
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 use tokio::sync::Mutex;
+use tracing::warn;
 
 pub struct ScannerParserService {
     pub data_fetcher: Arc<Mutex<dyn DataFetcher + Send + Sync>>,
@@ -36,15 +41,22 @@ impl ScannerParserService {
 
         // Join all the futures and await their results
         let results = try_join_all(futures).await?;
-        Ok(results
-            .into_par_iter()
-            .map(|r| {
-                let character_entity = r?;
-                Ok::<_, eyre::Report>((character_entity.id.clone(), character_entity))
-            })
-            .collect::<Result<Vec<_>>>()?
-            .into_iter()
-            .collect())
+        let mut character_entities = HashMap::new();
+        for (character, result) in characters.iter().zip(results) {
+            match result {
+                Ok(character_entity) => {
+                    character_entities.insert(character_entity.id.clone(), character_entity);
+                }
+                Err(e) => match e.downcast_ref::<DataError>() {
+                    Some(e) => warn!(
+                        "Skipping character {} ({}): {e}",
+                        character.id, character.name
+                    ),
+                    None => return Err(e),
+                },
+            }
+        }
+        Ok(character_entities)
     }
 
     async fn populate_light_cone(
@@ -71,7 +83,16 @@ impl ScannerParserService {
         Ok(results
             .into_par_iter()
             .map(|r| {
-                let light_cone_entity = r?;
+                let mut light_cone_entity = r?;
+                if let Err(e) = light_cone_entity.get_bonus_before_battle() {
+                    match e.downcast_ref::<DataError>() {
+                        Some(e) => {
+                            warn!("{e}, only its base stats are used");
+                            light_cone_entity.ignore_passive = true;
+                        }
+                        None => return Err(e),
+                    }
+                }
                 Ok::<_, eyre::Report>((
                     light_cone_entity._light_cone._uid.clone(),
                     light_cone_entity,
@@ -84,11 +105,26 @@ impl ScannerParserService {
 
     fn categorise_relics(&self, relics: &[RawRelic]) -> Result<HashMap<Slot, Vec<Relic>>> {
         let mut relic_pool = HashMap::new();
+        let sets = relic_sets()?;
+        let unknown_sets = relics
+            .iter()
+            .filter(|relic| !sets.contains_key(&relic.set_id))
+            .map(|relic| (&relic.set_id, &relic.name))
+            .collect::<HashSet<_>>();
+        for (set_id, name) in unknown_sets {
+            warn!("Relic set {set_id} ({name}) has no set data, its set bonus is ignored");
+        }
         for relic in relics {
-            relic_pool
-                .entry(relic.slot.clone())
-                .or_insert_with(Vec::new)
-                .push(relic.clone().try_into()?);
+            match Relic::try_from(relic.clone()) {
+                Ok(parsed) => relic_pool
+                    .entry(relic.slot.clone())
+                    .or_insert_with(Vec::new)
+                    .push(parsed),
+                Err(e) => match e.downcast_ref::<DataError>() {
+                    Some(e) => warn!("Skipping relic {}: {e}", relic._uid),
+                    None => return Err(e),
+                },
+            }
         }
         Ok(relic_pool)
     }
@@ -1,8 +1,8 @@
 use crate::{
-    character::Support,
+    character::{Carry, Support},
     domain::{
-        AttackType, Character, CritEnum, DamageType, Enemy, LightConeEntity, Relics, SkillType,
-        Stats,
+        AttackType, BattleContext, Character, CritEnum, DamageType, Enemy, LightConeEntity, Relics,
+        SkillType, Stats,
     },
 };
 use eyre::{eyre, Result};
@@ -68,16 +68,10 @@ pub fn calculate_stats(
     base_stats
 }
 
-pub fn base_stats_and_bonus(
-    character: &Character,
-    light_cone: &Option<LightConeEntity>,
-    relics: &Relics,
-    attack_type: &AttackType,
-    skill_type: &SkillType,
-    damage_type: &DamageType,
-    teammates: &[Box<dyn Support>],
-) -> Result<(HashMap<Stats, f64>, HashMap<Stats, f64>)> {
-    let mut bonus = relics.calculate_bonus_before_battle(attack_type)?;
+/// Stats unlocked by the traces of the character, its elemental DMG boost counting as
+/// `DmgBoost_`.
+fn trace_bonus(character: &Character) -> HashMap<Stats, f64> {
+    let mut bonus = HashMap::new();
     *bonus.entry(Stats::DmgBoost_).or_default() += if character.attack_type == AttackType::Lightning
     {
         character.stat_bonus.lightning_damage_boost
@@ -109,7 +103,22 @@ pub fn base_stats_and_bonus(
     *bonus.entry(Stats::BreakEffect_).or_default() += character.stat_bonus.break_effect;
     *bonus.entry(Stats::EffectRes_).or_default() += character.stat_bonus.effect_resistance;
     *bonus.entry(Stats::EffectHitRate_).or_default() += character.stat_bonus.effect_hit_rate;
-    *bonus.entry(Stats::OutgoingHealingBoost_).or_default() += character.stat_bonus.ougoing_healing_boost;
+    *bonus.entry(Stats::OutgoingHealingBoost_).or_default() +=
+        character.stat_bonus.ougoing_healing_boost;
+    bonus
+}
+
+/// Bonus of a character outside of battle: relics, traces and the light cone.
+pub fn bonus_before_battle(
+    character: &Character,
+    light_cone: &Option<LightConeEntity>,
+    relics: &Relics,
+    attack_type: &AttackType,
+) -> Result<HashMap<Stats, f64>> {
+    let mut bonus = relics.calculate_bonus_before_battle(attack_type)?;
+    for (stat, value) in trace_bonus(character) {
+        *bonus.entry(stat).or_default() += value;
+    }
     let initial_light_cone_bonus = light_cone
         .as_ref()
         .map(|lc| lc.get_bonus_before_battle())
@@ -119,6 +128,19 @@ pub fn base_stats_and_bonus(
             *bonus.entry(s.clone()).or_default() += b;
         }
     }
+    Ok(bonus)
+}
+
+pub fn base_stats_and_bonus(
+    character: &Character,
+    light_cone: &Option<LightConeEntity>,
+    relics: &Relics,
+    attack_type: &AttackType,
+    skill_type: &SkillType,
+    damage_type: &DamageType,
+    teammates: &[Box<dyn Support>],
+) -> Result<(HashMap<Stats, f64>, HashMap<Stats, f64>)> {
+    let mut bonus = bonus_before_battle(character, light_cone, relics, attack_type)?;
     let base_stats = calculate_stats(&bonus, &character, &light_cone);
     let bonus_during_battle = relics.calculate_bonus_during_battle(
         character.path.clone(),
@@ -140,9 +162,272 @@ pub fn base_stats_and_bonus(
             *bonus.entry(stat).or_default() += val;
         }
     }
+    let carry = Carry {
+        character,
+        light_cone,
+        skill_type,
+    };
+    for teammate in teammates {
+        for (stat, value) in teammate
+            .buffs(&carry)?
+            .into_iter()
+            .chain(teammate.debuffs(&carry)?)
+        {
+            *bonus.entry(stat).or_default() += value;
+        }
+    }
     Ok((calculate_stats(&bonus, &character, &light_cone), bonus))
 }
 
+/// Where the bonus of `base_stats_and_bonus` comes from, as `(source, stat, bonus)`.
+pub fn bonus_sources(
+    character: &Character,
+    light_cone: &Option<LightConeEntity>,
+    relics: &Relics,
+    attack_type: &AttackType,
+    skill_type: &SkillType,
+    damage_type: &DamageType,
+    teammates: &[Box<dyn Support>],
+) -> Result<Vec<(String, Stats, f64)>> {
+    let mut sources = vec![];
+    let mut add = |source: &str, bonus: HashMap<Stats, f64>| {
+        sources.extend(
+            bonus
+                .into_iter()
+                .filter(|(_, value)| *value != 0.0)
+                .map(|(stat, value)| (source.to_string(), stat, value)),
+        );
+    };
+    add("Relic main stats", relics.main_stat_bonus(attack_type));
+    add("Relic substats", relics.substat_bonus());
+    add("Traces", trace_bonus(character));
+    if let Some(lc) = light_cone {
+        add("Light cone", lc.get_bonus_before_battle()?);
+    }
+    let base_stats = calculate_stats(
+        &bonus_before_battle(character, light_cone, relics, attack_type)?,
+        character,
+        light_cone,
+    );
+    if let Some(lc) = light_cone {
+        add(
+            "Light cone in battle",
+            lc.get_bonus_during_battle(skill_type, damage_type, &base_stats)?,
+        );
+    }
+    let carry = Carry {
+        character,
+        light_cone,
+        skill_type,
+    };
+    for teammate in teammates {
+        sources.extend(
+            teammate
+                .sources(&carry)?
+                .into_iter()
+                .filter(|(_, _, value)| *value != 0.0)
+                .map(|(source, stat, value)| (format!("Teammate {source}"), stat, value)),
+        );
+    }
+    let battle = BattleContext {
+        path: &character.path,
+        skill_type,
+        damage_type,
+        base_stats: &base_stats,
+        teammates,
+    };
+    let set_effects = relics
+        .set_effects(attack_type, None)?
+        .into_iter()
+        .chain(relics.set_effects(attack_type, Some(&battle))?)
+        .filter(|(_, _, value)| *value != 0.0);
+    sources.extend(set_effects);
+    Ok(sources)
+}
+
+/// Level multiplier of break, super break and break DoT DMG, by attacker level from 1 to 80.
+pub const LEVEL_MULTIPLIERS: [f64; 80] = [
+    54.0000, 58.0000, 62.0000, 67.5264, 70.5094, 73.5228, 76.5660, 79.6385, 82.7395, 85.8684,
+    91.4944, 97.0680, 102.5892, 108.0579, 113.4743, 118.8383, 124.1499, 129.4091, 134.6159,
+    139.7703, 149.3323, 158.8011, 168.1768, 177.4594, 186.6489, 195.7452, 204.7484, 213.6585,
+    222.4754, 231.1992, 246.4276, 261.1810, 275.4733, 289.3179, 302.7275, 315.7144, 328.2905,
+    340.4671, 352.2554, 363.6658, 408.1240, 451.7883, 494.6798, 536.8188, 578.2249, 618.9172,
+    658.9138, 698.2325, 736.8905, 774.9041, 871.0599, 964.8705, 1056.4206, 1145.7910, 1233.0585,
+    1318.2965, 1401.5750, 1482.9608, 1562.5178, 1640.3068, 1752.3215, 1861.9011, 1969.1242,
+    2074.0659, 2176.7983, 2277.3904, 2375.9085, 2472.4160, 2566.9739, 2659.6406, 2780.3044,
+    2898.6022, 3014.6029, 3128.3729, 3239.9758, 3349.4730, 3456.9236, 3562.3843, 3665.9099,
+    3767.5533,
+];
+
+/// Toughness a basic attack usually reduces, the unit of super break DMG.
+pub const BASIC_TOUGHNESS_REDUCTION: f64 = 30.0;
+
+pub fn level_multiplier(level: u8) -> Result<f64> {
+    LEVEL_MULTIPLIERS
+        .get((level as usize).wrapping_sub(1))
+        .cloned()
+        .ok_or(eyre!("No level multiplier for level {level}"))
+}
+
+/// Break DMG of the element relative to the level multiplier.
+pub fn break_element_multiplier(element: &AttackType) -> f64 {
+    match element {
+        AttackType::Physical | AttackType::Fire => 2.0,
+        AttackType::Wind => 1.5,
+        AttackType::Ice | AttackType::Lightning => 1.0,
+        AttackType::Quantum | AttackType::Imaginary => 0.5,
+    }
+}
+
+/// Break DMG grows with the maximum toughness of the enemy.
+pub fn max_toughness_multiplier(enemy: &Enemy) -> f64 {
+    0.5 + enemy.toughness / (4.0 * BASIC_TOUGHNESS_REDUCTION)
+}
+
+/// Base DMG of the weakness break of the character's element.
+pub fn break_base_dmg(character: &Character, enemy: &Enemy) -> Result<f64> {
+    Ok(level_multiplier(character.level)?
+        * break_element_multiplier(&character.attack_type)
+        * max_toughness_multiplier(enemy))
+}
+
+/// Base DMG of the super break of a hit reducing `toughness`.
+pub fn super_break_base_dmg(character: &Character, toughness: f64) -> Result<f64> {
+    Ok(level_multiplier(character.level)? * toughness / BASIC_TOUGHNESS_REDUCTION)
+}
+
+/// Base DMG of a tick of the DoT the weakness break of the character's element inflicts: burn,
+/// shock, bleed or a stack of wind shear. The other elements inflict no DoT.
+pub fn break_dot_base_dmg(character: &Character, enemy: &Enemy) -> Result<f64> {
+    let level = level_multiplier(character.level)?;
+    Ok(match character.attack_type {
+        AttackType::Fire | AttackType::Wind => level,
+        AttackType::Lightning => 2.0 * level,
+        AttackType::Physical => {
+            let bleed = 2.0 * level * max_toughness_multiplier(enemy);
+            let cap = if enemy.elite { 0.07 } else { 0.16 } * enemy.hp;
+            if enemy.hp > 0.0 {
+                bleed.min(cap)
+            } else {
+                bleed
+            }
+        }
+        AttackType::Ice | AttackType::Quantum | AttackType::Imaginary => 0.0,
+    })
+}
+
+/// Every factor of the damage of a single hit.
+#[derive(Clone, Debug, Default)]
+pub struct DamageMultipliers {
+    /// Ability multiplier times the stat it scales with, or the break base DMG.
+    pub base_dmg: f64,
+    pub crit: f64,
+    /// DMG boost, break effect for break DMG which DMG boosts don't apply to, or the healing or
+    /// shield bonus.
+    pub dmg_boost: f64,
+    pub weaken: f64,
+    pub def: f64,
+    pub res: f64,
+    pub vul: f64,
+    pub dmg_mit: f64,
+    pub toughness: f64,
+}
+
+impl DamageMultipliers {
+    /// Multipliers of a hit, `stats` being the stat sheet the crit is read from.
+    pub fn new(
+        base_dmg: f64,
+        crit: CritEnum,
+        stats: &HashMap<Stats, f64>,
+        bonus: &HashMap<Stats, f64>,
+        enemy: &Enemy,
+        character: &Character,
+    ) -> Result<Self> {
+        Ok(Self {
+            base_dmg,
+            crit: crit_dmg(crit, stats, character),
+            dmg_boost: dmg_boost(bonus),
+            weaken: weaken(enemy),
+            def: def(enemy, bonus, character),
+            res: res(enemy, &character.attack_type, bonus),
+            vul: vul(enemy, bonus),
+            dmg_mit: dmg_mit(enemy)?,
+            toughness: toughness(enemy),
+        })
+    }
+
+    /// Multipliers of break, super break and break DoT DMG, which never crit and scale with
+    /// the break effect of the stat sheet instead of DMG boosts.
+    pub fn for_break(
+        base_dmg: f64,
+        damage_type: &DamageType,
+        stats: &HashMap<Stats, f64>,
+        bonus: &HashMap<Stats, f64>,
+        enemy: &Enemy,
+        character: &Character,
+    ) -> Result<Self> {
+        let def_ignore = match damage_type {
+            DamageType::SuperBreakDamage => Stats::SuperBreakDmgDefIgnore_,
+            _ => Stats::BreakDmgDefIgnore_,
+        };
+        let mut def_bonus = bonus.clone();
+        *def_bonus.entry(Stats::DefIgnore_).or_default() +=
+            bonus.get(&def_ignore).cloned().unwrap_or_default();
+        Ok(Self {
+            base_dmg,
+            crit: 1.0,
+            dmg_boost: 1.0 + stats.get(&Stats::BreakEffect_).cloned().unwrap_or_default() / 100.0,
+            weaken: weaken(enemy),
+            def: def(enemy, &def_bonus, character),
+            res: res(enemy, &character.attack_type, bonus),
+            vul: vul(enemy, bonus),
+            dmg_mit: dmg_mit(enemy)?,
+            // Break DMG lands before the enemy is broken, super break and DoTs after.
+            toughness: match damage_type {
+                DamageType::BreakDamage => 0.9,
+                _ => 1.0,
+            },
+        })
+    }
+
+    /// Multipliers of a heal or a shield, boosted by the outgoing healing boost of the stat
+    /// sheet or the shield DMG absorption bonus, and left alone by the enemy.
+    pub fn for_sustain(
+        base: f64,
+        damage_type: &DamageType,
+        stats: &HashMap<Stats, f64>,
+        bonus: &HashMap<Stats, f64>,
+    ) -> Self {
+        let boost = match damage_type {
+            DamageType::Shield => bonus.get(&Stats::ShieldDmgAbsorption_),
+            _ => stats.get(&Stats::OutgoingHealingBoost_),
+        };
+        Self {
+            base_dmg: base,
+            crit: 1.0,
+            dmg_boost: 1.0 + boost.cloned().unwrap_or_default() / 100.0,
+            weaken: 1.0,
+            def: 1.0,
+            res: 1.0,
+            vul: 1.0,
+            dmg_mit: 1.0,
+            toughness: 1.0,
+        }
+    }
+
+    pub fn damage(&self) -> f64 {
+        self.base_dmg
+            * self.crit
+            * self.dmg_boost
+            * self.weaken
+            * self.def
+            * self.res
+            * self.vul
+            * self.dmg_mit
+            * self.toughness
+    }
+}
+
 pub fn toughness(enemy: &Enemy) -> f64 {
     let toughness_break = match enemy.toughness_break {
         true => 1.0,
@@ -169,14 +454,20 @@ pub fn dmg_mit(enemy: &Enemy) -> Result<f64> {
     Ok(dmg_mitigation)
 }
 
-pub fn vul(enemy: &Enemy) -> f64 {
-    let vulnerebility = 1.0 + enemy.vulnerability / 100.0;
+pub fn vul(enemy: &Enemy, bonus: &HashMap<Stats, f64>) -> f64 {
+    let vulnerebility = 1.0
+        + (enemy.vulnerability
+            + bonus
+                .get(&Stats::Vulnerebility_)
+                .cloned()
+                .unwrap_or_default())
+            / 100.0;
     vulnerebility
 }
 
-pub fn res(enemy: &Enemy, bonus: &HashMap<Stats, f64>) -> f64 {
+pub fn res(enemy: &Enemy, element: &AttackType, bonus: &HashMap<Stats, f64>) -> f64 {
     let res = 1.0
-        - ((enemy.resistance
+        - ((enemy.resistance_to(element)
             - bonus
                 .get(&Stats::ResPenentration_)
                 .cloned()
@@ -224,3 +515,212 @@ pub fn def(enemy: &Enemy, bonus: &HashMap<Stats, f64>, character: &Character) ->
             + (character.level + 20) as f64);
     def
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        character::CustomSupport,
+        domain::{
+            BaseStats, CharacterSkills, CharacterTraces, Path, Relic, RelicSetConfig, Slot,
+            SubStats,
+        },
+    };
+
+    fn relic(slot: Slot, set_id: &str, mainstat: Stats, mainstat_value: f64) -> Relic {
+        Relic {
+            set_id: set_id.to_string(),
+            slot,
+            mainstat,
+            mainstat_value,
+            substats: vec![
+                SubStats {
+                    key: Stats::CritRate_,
+                    value: 3.24,
+                },
+                SubStats {
+                    key: Stats::Spd,
+                    value: 4.6,
+                },
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_bonus_sources_add_up() -> Result<()> {
+        let character = Character {
+            id: "1102".to_string(),
+            name: "Seele".to_string(),
+            path: Path::TheHunt,
+            attack_type: AttackType::Quantum,
+            level: 80,
+            ascension: 6,
+            eidolon: 0,
+            skills: CharacterSkills {
+                basic: 6,
+                skill: 10,
+                ult: 10,
+                talent: 10,
+            },
+            traces: CharacterTraces {
+                ability_1: true,
+                ability_2: true,
+                ability_3: true,
+                stat_1: true,
+                stat_2: true,
+                stat_3: true,
+                stat_4: true,
+                stat_5: true,
+                stat_6: true,
+                stat_7: true,
+                stat_8: true,
+                stat_9: true,
+                stat_10: true,
+            },
+            base_hp: 931.0,
+            base_atk: 640.0,
+            base_def: 363.0,
+            base_spd: 115.0,
+            base_aggro: 75,
+            critical_chance: 5.0,
+            critical_damage: 50.0,
+            stat_bonus: BaseStats {
+                atk_percentage: 28.0,
+                quantum_damage_boost: 8.0,
+                ..Default::default()
+            },
+        };
+        let relics = Relics {
+            relics: vec![
+                relic(Slot::Head, "102", Stats::Hp, 705.6),
+                relic(Slot::Hands, "102", Stats::Atk, 352.8),
+                relic(Slot::Body, "102", Stats::CritDmg_, 64.8),
+                relic(Slot::Feet, "102", Stats::Spd, 25.0),
+                relic(Slot::PlanarSphere, "301", Stats::QuantumDmgBoost_, 38.8),
+                relic(Slot::LinkRope, "301", Stats::Atk_, 43.2),
+            ],
+            config: RelicSetConfig {
+                activate_102: true,
+                ..Default::default()
+            },
+        };
+        let teammates: Vec<Box<dyn Support>> = vec![Box::new(CustomSupport {
+            path: Path::Harmony,
+            buffs: HashMap::from([(Stats::CritDmg_, 50.0), (Stats::DefReduction_, 20.0)]),
+        })];
+
+        let (_, bonus) = base_stats_and_bonus(
+            &character,
+            &None,
+            &relics,
+            &character.attack_type,
+            &SkillType::BasicAttack,
+            &DamageType::Normal,
+            &teammates,
+        )?;
+        let sources = bonus_sources(
+            &character,
+            &None,
+            &relics,
+            &character.attack_type,
+            &SkillType::BasicAttack,
+            &DamageType::Normal,
+            &teammates,
+        )?;
+        let mut total = HashMap::<Stats, f64>::new();
+        for (_, stat, value) in &sources {
+            *total.entry(stat.clone()).or_default() += value;
+        }
+        for (stat, value) in bonus.iter().filter(|(_, value)| **value != 0.0) {
+            let sum = total.get(stat).cloned().unwrap_or_default();
+            assert!((sum - value).abs() < 1e-9, "{stat:?}: {sum} != {value}");
+        }
+        assert!(sources
+            .iter()
+            .any(|(source, _, _)| source == "4pc Musketeer of Wild Wheat"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_break_formulas() -> Result<()> {
+        assert_eq!(level_multiplier(80)?, 3767.5533);
+        assert!(level_multiplier(0).is_err());
+        let mut character = Character {
+            id: "1310".to_string(),
+            name: "Firefly".to_string(),
+            path: Path::Destruction,
+            attack_type: AttackType::Fire,
+            level: 80,
+            ascension: 6,
+            eidolon: 0,
+            skills: CharacterSkills {
+                basic: 6,
+                skill: 10,
+                ult: 10,
+                talent: 10,
+            },
+            traces: CharacterTraces {
+                ability_1: false,
+                ability_2: false,
+                ability_3: false,
+                stat_1: false,
+                stat_2: false,
+                stat_3: false,
+                stat_4: false,
+                stat_5: false,
+                stat_6: false,
+                stat_7: false,
+                stat_8: false,
+                stat_9: false,
+                stat_10: false,
+            },
+            base_hp: 815.0,
+            base_atk: 524.0,
+            base_def: 776.0,
+            base_spd: 104.0,
+            base_aggro: 125,
+            critical_chance: 5.0,
+            critical_damage: 50.0,
+            stat_bonus: BaseStats::default(),
+        };
+        let enemy = Enemy {
+            toughness: 300.0,
+            ..Default::default()
+        };
+        let base = break_base_dmg(&character, &enemy)?;
+        assert!((base - 3767.5533 * 2.0 * 3.0).abs() < 1e-9);
+
+        let stats = HashMap::from([(Stats::BreakEffect_, 100.0)]);
+        let bonus = HashMap::from([(Stats::SuperBreakDmgDefIgnore_, 100.0)]);
+        let hit = DamageMultipliers::for_break(
+            base,
+            &DamageType::BreakDamage,
+            &stats,
+            &bonus,
+            &enemy,
+            &character,
+        )?;
+        assert_eq!((hit.crit, hit.dmg_boost, hit.def), (1.0, 2.0, 0.5));
+        assert!((hit.damage() - base * 2.0 * 0.5 * 0.9).abs() < 1e-6);
+        let super_break = DamageMultipliers::for_break(
+            super_break_base_dmg(&character, 60.0)?,
+            &DamageType::SuperBreakDamage,
+            &stats,
+            &bonus,
+            &enemy,
+            &character,
+        )?;
+        assert!((super_break.damage() - 3767.5533 * 2.0 * 2.0).abs() < 1e-6);
+
+        assert_eq!(break_dot_base_dmg(&character, &enemy)?, 3767.5533);
+        character.attack_type = AttackType::Physical;
+        let elite = Enemy {
+            hp: 100000.0,
+            elite: true,
+            ..enemy
+        };
+        assert!((break_dot_base_dmg(&character, &elite)? - 7000.0).abs() < 1e-9);
+        Ok(())
+    }
+}
@@ -1,11 +1,11 @@
-use crate::domain::Stats;
+use crate::domain::{DataError, Stats};
 
-pub fn title_mapper(title: &str) -> Stats {
+pub fn title_mapper(title: &str) -> Result<Stats, DataError> {
     let title: String = title
         .chars()
         .map(|c| if c == '\u{00A0}' { ' ' } else { c })
         .collect();
-    match title.as_str() {
+    Ok(match title.as_str() {
         "HP Boost" => Stats::Hp_,
         "ATK Boost" => Stats::Atk_,
         "DEF Boost" => Stats::Def_,
@@ -24,6 +24,53 @@ pub fn title_mapper(title: &str) -> Stats {
         "DMG Boost: Imaginary" => Stats::ImaginaryDmgBoost_,
         "DMG Boost: Quantum" => Stats::QuantumDmgBoost_,
         "DMG Boost: Physical" => Stats::PhysicalDmgBoost_,
-        _ => todo!(),
+        _ => return Err(DataError::UnknownTrace(title)),
+    })
+}
+
+/// Stat granted by a StarRailRes property, e.g. `AttackAddedRatio` for ATK%.
+pub fn property_mapper(property: &str) -> Result<Stats, DataError> {
+    Ok(match property {
+        "HPDelta" => Stats::Hp,
+        "HPAddedRatio" => Stats::Hp_,
+        "AttackDelta" => Stats::Atk,
+        "AttackAddedRatio" => Stats::Atk_,
+        "DefenceDelta" => Stats::Def,
+        "DefenceAddedRatio" => Stats::Def_,
+        "SpeedDelta" => Stats::Spd,
+        "SpeedAddedRatio" => Stats::Spd_,
+        "CriticalChanceBase" => Stats::CritRate_,
+        "CriticalDamageBase" => Stats::CritDmg_,
+        "StatusProbabilityBase" => Stats::EffectHitRate_,
+        "StatusResistanceBase" => Stats::EffectRes_,
+        "BreakDamageAddedRatioBase" => Stats::BreakEffect_,
+        "HealRatioBase" => Stats::OutgoingHealingBoost_,
+        "SPRatioBase" => Stats::EnergyRegenerationRate_,
+        "PhysicalAddedRatio" => Stats::PhysicalDmgBoost_,
+        "FireAddedRatio" => Stats::FireDmgBoost_,
+        "IceAddedRatio" => Stats::IceDmgBoost_,
+        "ThunderAddedRatio" => Stats::LightningDmgBoost_,
+        "WindAddedRatio" => Stats::WindDmgBoost_,
+        "QuantumAddedRatio" => Stats::QuantumDmgBoost_,
+        "ImaginaryAddedRatio" => Stats::ImaginaryDmgBoost_,
+        _ => return Err(DataError::UnknownTrace(property.to_string())),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::BaseStats;
+
+    #[test]
+    fn test_unknown_trace_is_an_error() -> eyre::Result<()> {
+        let mut base_stats = BaseStats::default();
+        base_stats.add(&title_mapper("DMG Boost")?, 8.0)?;
+        assert_eq!(base_stats.quantum_damage_boost, 8.0);
+        assert_eq!(
+            title_mapper("Memosprite Boost"),
+            Err(DataError::UnknownTrace("Memosprite Boost".to_string()))
+        );
+        Ok(())
     }
 }